@@ -11,7 +11,7 @@ static TANH: Lazy<Tanh> = Lazy::new(|| Tanh);
 
 /// Trait que define métodos para funções de ativação.
 /// Implementa o cálculo da função de ativação e sua derivada,
-/// tanto para um único valor quanto para uma matriz `Array2<f32>`.
+/// tanto para um único valor quanto para um tensor `ArrayD<f32>` de qualquer dimensão.
 pub trait Activation: Send + Sync {
     /// Aplica a função de ativação a um único valor `x`.
     ///
@@ -35,34 +35,34 @@ pub trait Activation: Send + Sync {
     /// Retorna o valor da derivada da função de ativação em `x`.
     fn derivative(&self, x: f32) -> f32;
 
-    /// Aplica a função de ativação a cada elemento de uma matriz (`Array2<f32>`).
-    /// Esta versão utiliza paralelismo para maior eficiência em grandes matrizes.
+    /// Aplica a função de ativação a cada elemento de um tensor (`ArrayD<f32>`).
+    /// Esta versão utiliza paralelismo para maior eficiência em grandes tensores.
     ///
     /// # Argumentos
     ///
-    /// * `x` - Referência para uma matriz `Array2<f32>` cujos elementos terão a função de ativação aplicada.
+    /// * `x` - Referência para um tensor `ArrayD<f32>` cujos elementos terão a função de ativação aplicada.
     ///
     /// # Retorno
     ///
-    /// Retorna uma nova matriz com a função de ativação aplicada a cada elemento.
-    fn activate_array(&self, x: &Array2<f32>) -> Array2<f32> {
-        let mut result = x.clone(); // Clona a matriz de entrada
+    /// Retorna um novo tensor, com a mesma forma, com a função de ativação aplicada a cada elemento.
+    fn activate_array(&self, x: &ArrayD<f32>) -> ArrayD<f32> {
+        let mut result = x.clone(); // Clona o tensor de entrada
         result.par_mapv_inplace(|elem| self.activate(elem)); // Aplica a ativação de forma paralela
         result
     }
 
-    /// Calcula a derivada da função de ativação para cada elemento de uma matriz (`Array2<f32>`).
-    /// Esta versão utiliza paralelismo para maior eficiência em grandes matrizes.
+    /// Calcula a derivada da função de ativação para cada elemento de um tensor (`ArrayD<f32>`).
+    /// Esta versão utiliza paralelismo para maior eficiência em grandes tensores.
     ///
     /// # Argumentos
     ///
-    /// * `x` - Referência para uma matriz `Array2<f32>` cujos elementos terão a derivada da função aplicada.
+    /// * `x` - Referência para um tensor `ArrayD<f32>` cujos elementos terão a derivada da função aplicada.
     ///
     /// # Retorno
     ///
-    /// Retorna uma nova matriz com a derivada da função de ativação aplicada a cada elemento.
-    fn derivative_array(&self, x: &Array2<f32>) -> Array2<f32> {
-        let mut result = x.clone(); // Clona a matriz de entrada
+    /// Retorna um novo tensor, com a mesma forma, com a derivada da função de ativação aplicada a cada elemento.
+    fn derivative_array(&self, x: &ArrayD<f32>) -> ArrayD<f32> {
+        let mut result = x.clone(); // Clona o tensor de entrada
         result.par_mapv_inplace(|elem| self.derivative(elem)); // Aplica a derivada de forma paralela
         result
     }
@@ -169,8 +169,8 @@ impl Activation for ActivationType {
         }
     }
 
-    /// Aplica a função de ativação correspondente a uma matriz (`Array2<f32>`) em paralelo.
-    fn activate_array(&self, x: &Array2<f32>) -> Array2<f32> {
+    /// Aplica a função de ativação correspondente a um tensor (`ArrayD<f32>`) em paralelo.
+    fn activate_array(&self, x: &ArrayD<f32>) -> ArrayD<f32> {
         match self {
             ActivationType::ReLU => RELU.activate_array(x),
             ActivationType::Sigmoid => SIGMOID.activate_array(x),
@@ -179,8 +179,8 @@ impl Activation for ActivationType {
         }
     }
 
-    /// Calcula a derivada da função de ativação correspondente para cada elemento de uma matriz (`Array2<f32>`) em paralelo.
-    fn derivative_array(&self, x: &Array2<f32>) -> Array2<f32> {
+    /// Calcula a derivada da função de ativação correspondente para cada elemento de um tensor (`ArrayD<f32>`) em paralelo.
+    fn derivative_array(&self, x: &ArrayD<f32>) -> ArrayD<f32> {
         match self {
             ActivationType::ReLU => RELU.derivative_array(x),
             ActivationType::Sigmoid => SIGMOID.derivative_array(x),
@@ -0,0 +1,267 @@
+// bran/src/callbacks.rs
+
+use crate::model::NeuralNetwork;
+use crate::optimizer::Optimizer;
+use crate::visualization::TrainingStats;
+use ndarray::ArrayD;
+use serde::{Deserialize, Serialize};
+
+/// Estado do treinamento exposto aos callbacks em cada gancho.
+pub struct CallbackContext<'a> {
+    /// A rede neural sendo treinada.
+    pub model: &'a mut NeuralNetwork,
+    /// O otimizador usado no treinamento.
+    pub optimizer: &'a mut (dyn Optimizer + Send),
+    /// As estatísticas acumuladas até o momento.
+    pub stats: &'a mut TrainingStats,
+    /// Número total de épocas configurado.
+    pub epochs: usize,
+    /// Número de mini-lotes por época.
+    pub steps_per_epoch: usize,
+    /// Tamanho do lote configurado.
+    pub batch_size: usize,
+    /// Número de amostras de treinamento por época.
+    pub samples: usize,
+    /// Defina como verdadeiro para interromper o treinamento ao final do gancho atual.
+    pub stop_training: bool,
+}
+
+/// Trait que define ganchos chamados durante o laço de treinamento de `NeuralNetwork::train`
+/// e de `GraphModel::fit`.
+///
+/// Todos os métodos têm implementação vazia, então basta sobrescrever os ganchos
+/// necessários. As épocas são numeradas a partir de 1, como em `TrainingStats`,
+/// e os mini-lotes a partir de 0 dentro de cada época.
+pub trait Callback {
+    /// Verifica a configuração do callback antes do início do treinamento.
+    ///
+    /// `series` lista os nomes aceitos por `TrainingStats::latest` que serão registrados a
+    /// cada época. Um erro interrompe o treinamento antes de qualquer gancho.
+    fn validate(&self, _series: &[String]) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Chamado uma vez antes da primeira época.
+    fn on_train_begin(&mut self, _ctx: &mut CallbackContext) {}
+
+    /// Chamado uma vez após a última época ou após uma interrupção.
+    fn on_train_end(&mut self, _ctx: &mut CallbackContext) {}
+
+    /// Chamado no início de cada época.
+    fn on_epoch_begin(&mut self, _epoch: usize, _ctx: &mut CallbackContext) {}
+
+    /// Chamado ao final de cada época, depois que as estatísticas foram registradas.
+    fn on_epoch_end(&mut self, _epoch: usize, _ctx: &mut CallbackContext) {}
+
+    /// Chamado antes da passagem forward de cada mini-lote.
+    fn on_batch_begin(&mut self, _batch: usize, _ctx: &mut CallbackContext) {}
+
+    /// Chamado após a atualização dos pesos de cada mini-lote, com a perda do lote.
+    fn on_batch_end(&mut self, _batch: usize, _loss: f32, _ctx: &mut CallbackContext) {}
+
+    /// Estado interno serializado, salvo nos checkpoints de treinamento.
+    ///
+    /// Callbacks sem estado (ou cujo estado depende apenas da época) não precisam sobrescrever.
+    fn state(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Restaura o estado salvo por `state` ao retomar um treinamento.
+    /// É chamado logo após `on_train_begin`.
+    fn load_state(&mut self, _state: &[u8]) {}
+}
+
+/// Direção em que a métrica monitorada é considerada uma melhora.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MonitorMode {
+    /// Valores menores são melhores (ex.: perdas e erros).
+    Min,
+    /// Valores maiores são melhores (ex.: acurácias).
+    Max,
+}
+
+/// Interrompe o treinamento quando a métrica monitorada para de melhorar.
+pub struct EarlyStopping {
+    /// Nome da métrica monitorada (ex.: `"val_loss"`, `"loss"`, `"val_accuracy"`).
+    pub monitor: String,
+    /// Número de épocas sem melhora toleradas antes de interromper.
+    pub patience: usize,
+    /// Variação mínima para que uma mudança conte como melhora.
+    pub min_delta: f32,
+    /// Direção de melhora da métrica.
+    pub mode: MonitorMode,
+    /// Se verdadeiro, restaura os pesos da melhor época ao interromper ou terminar.
+    pub restore_best_weights: bool,
+
+    best: Option<f32>,
+    best_epoch: usize,
+    wait: usize,
+    best_weights: Option<Vec<Vec<ArrayD<f32>>>>,
+}
+
+impl EarlyStopping {
+    /// Cria um novo `EarlyStopping` monitorando `monitor` com a paciência indicada.
+    ///
+    /// O modo é inferido pelo nome: métricas de acurácia usam `MonitorMode::Max`,
+    /// as demais (perdas e erros) usam `MonitorMode::Min`.
+    pub fn new(monitor: &str, patience: usize) -> Self {
+        let mode = if monitor.contains("accuracy") {
+            MonitorMode::Max
+        } else {
+            MonitorMode::Min
+        };
+        EarlyStopping {
+            monitor: monitor.to_string(),
+            patience,
+            min_delta: 0.0,
+            mode,
+            restore_best_weights: false,
+            best: None,
+            best_epoch: 0,
+            wait: 0,
+            best_weights: None,
+        }
+    }
+
+    /// Define a variação mínima considerada como melhora.
+    pub fn with_min_delta(mut self, min_delta: f32) -> Self {
+        self.min_delta = min_delta.abs();
+        self
+    }
+
+    /// Define explicitamente a direção de melhora.
+    pub fn with_mode(mut self, mode: MonitorMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Restaura os pesos da melhor época ao final do treinamento.
+    pub fn with_restore_best_weights(mut self, restore: bool) -> Self {
+        self.restore_best_weights = restore;
+        self
+    }
+
+    /// Melhor época observada até agora (começando em 1).
+    pub fn best_epoch(&self) -> usize {
+        self.best_epoch
+    }
+
+    /// Melhor valor observado da métrica monitorada.
+    pub fn best(&self) -> Option<f32> {
+        self.best
+    }
+
+    /// Verifica se `value` é uma melhora em relação ao melhor valor atual.
+    fn is_improvement(&self, value: f32) -> bool {
+        match self.best {
+            None => true,
+            Some(best) => match self.mode {
+                MonitorMode::Min => value < best - self.min_delta,
+                MonitorMode::Max => value > best + self.min_delta,
+            },
+        }
+    }
+}
+
+impl Callback for EarlyStopping {
+    fn validate(&self, series: &[String]) -> Result<(), String> {
+        if series.contains(&self.monitor) {
+            return Ok(());
+        }
+        Err(format!(
+            "EarlyStopping: métrica monitorada '{}' não está disponível (séries disponíveis: {})",
+            self.monitor,
+            series.join(", ")
+        ))
+    }
+
+    fn on_train_begin(&mut self, _ctx: &mut CallbackContext) {
+        self.best = None;
+        self.best_epoch = 0;
+        self.wait = 0;
+        self.best_weights = None;
+    }
+
+    fn on_epoch_end(&mut self, epoch: usize, ctx: &mut CallbackContext) {
+        // A métrica foi verificada por `validate` antes do treinamento
+        let Some(value) = ctx.stats.latest(&self.monitor) else {
+            return;
+        };
+
+        if self.is_improvement(value) {
+            self.best = Some(value);
+            self.best_epoch = epoch;
+            self.wait = 0;
+            if self.restore_best_weights {
+                self.best_weights = Some(ctx.model.get_weights());
+            }
+        } else {
+            self.wait += 1;
+            if self.wait >= self.patience {
+                ctx.stats.stopped_epoch = Some(epoch);
+                ctx.stop_training = true;
+            }
+        }
+        ctx.stats.best_epoch = Some(self.best_epoch);
+    }
+
+    /// Restaura os melhores pesos, se configurado.
+    fn on_train_end(&mut self, ctx: &mut CallbackContext) {
+        if let Some(weights) = self.best_weights.take() {
+            ctx.model.set_weights(weights);
+        }
+    }
+
+    fn state(&self) -> Option<Vec<u8>> {
+        let state = EarlyStoppingState {
+            best: self.best,
+            best_epoch: self.best_epoch,
+            wait: self.wait,
+            best_weights: self.best_weights.clone(),
+        };
+        bincode::serialize(&state).ok()
+    }
+
+    fn load_state(&mut self, state: &[u8]) {
+        if let Ok(state) = bincode::deserialize::<EarlyStoppingState>(state) {
+            self.best = state.best;
+            self.best_epoch = state.best_epoch;
+            self.wait = state.wait;
+            self.best_weights = state.best_weights;
+        }
+    }
+}
+
+/// Estado do `EarlyStopping` salvo nos checkpoints.
+#[derive(Serialize, Deserialize)]
+struct EarlyStoppingState {
+    best: Option<f32>,
+    best_epoch: usize,
+    wait: usize,
+    best_weights: Option<Vec<Vec<ArrayD<f32>>>>,
+}
+
+/// Ajusta a taxa de aprendizado do otimizador no início de cada época.
+pub struct LearningRateScheduler {
+    schedule: Box<dyn Fn(usize, f32) -> f32>,
+}
+
+impl LearningRateScheduler {
+    /// Cria um novo agendador.
+    ///
+    /// # Argumentos
+    ///
+    /// * `schedule` - Função que recebe a época (começando em 1) e a taxa atual e retorna a nova taxa.
+    pub fn new(schedule: impl Fn(usize, f32) -> f32 + 'static) -> Self {
+        LearningRateScheduler {
+            schedule: Box::new(schedule),
+        }
+    }
+}
+
+impl Callback for LearningRateScheduler {
+    fn on_epoch_begin(&mut self, epoch: usize, ctx: &mut CallbackContext) {
+        let learning_rate = (self.schedule)(epoch, ctx.optimizer.learning_rate());
+        ctx.optimizer.set_learning_rate(learning_rate);
+    }
+}
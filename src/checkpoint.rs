@@ -0,0 +1,68 @@
+// bran/src/checkpoint.rs
+
+use crate::model::{self, NeuralNetwork};
+use crate::optimizer::OptimizerState;
+use crate::visualization::TrainingStats;
+use ndarray::ArrayD;
+use serde::{Deserialize, Serialize};
+use std::{fs::File, io::Read, io::Write};
+
+/// Checkpoint completo de treinamento.
+///
+/// Ao contrário de `NeuralNetwork::save`, que guarda apenas os pesos, o checkpoint
+/// inclui tudo o que é necessário para continuar o treinamento exatamente de onde parou:
+/// os momentos do otimizador em cada camada, o estado do otimizador, as estatísticas,
+/// a época concluída, a semente do embaralhamento e o estado dos callbacks.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Checkpoint {
+    /// Última época concluída (começando em 1).
+    pub epoch: usize,
+    /// A rede neural com seus pesos e vieses.
+    pub model: NeuralNetwork,
+    /// Momentos do otimizador de cada camada, como retornado por `NeuralNetwork::moments`.
+    pub moments: Vec<Vec<ArrayD<f32>>>,
+    /// Estado do otimizador (taxa de aprendizado e contador de passos).
+    pub optimizer: OptimizerState,
+    /// Estatísticas acumuladas até a época salva.
+    pub stats: TrainingStats,
+    /// Semente usada no embaralhamento dos mini-lotes, se configurada.
+    pub seed: Option<u64>,
+    /// Estado serializado de cada callback, na ordem de `TrainConfig::callbacks`.
+    pub callbacks: Vec<Option<Vec<u8>>>,
+}
+
+impl Checkpoint {
+    /// Salva o checkpoint utilizando a serialização binária `bincode`.
+    ///
+    /// # Parâmetros
+    /// - `filename`: O caminho onde o checkpoint será salvo.
+    ///
+    /// # Retornos
+    /// - `Result<(), Box<dyn std::error::Error>>`: Retorna Ok se o checkpoint foi salvo com sucesso, ou um erro caso contrário.
+    pub fn save(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let encoded = model::encode(self)?;
+        let mut file = File::create(filename)?;
+        file.write_all(&encoded)?;
+        Ok(())
+    }
+
+    /// Carrega um checkpoint a partir de um arquivo serializado com `bincode`.
+    ///
+    /// # Parâmetros
+    /// - `filename`: O caminho de onde o checkpoint será carregado.
+    ///
+    /// # Retornos
+    /// - `Result<Checkpoint, Box<dyn std::error::Error>>`: O checkpoint carregado ou um erro, caso a operação falhe
+    ///   ou a versão do formato não seja suportada.
+    pub fn load(filename: &str) -> Result<Checkpoint, Box<dyn std::error::Error>> {
+        let mut file = File::open(filename)?;
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer)?;
+        match model::strip_header(&buffer)? {
+            Some(payload) => Ok(bincode::deserialize(payload)?),
+            None => {
+                Err("o arquivo não é um checkpoint do bran: cabeçalho de formato ausente".into())
+            }
+        }
+    }
+}
@@ -1,32 +1,504 @@
 // bran/src/layers.rs
 
 use crate::activations::{Activation, ActivationType};
-use ndarray::{Array1, Array2, Axis};
+use crate::optimizer::{Optimizer, Parameter};
+use ndarray::{Array1, Array2, Array3, ArrayD, ArrayView2, Axis, Ix2, IxDyn};
 use rand::distributions::{Distribution, Uniform};
 use rand::thread_rng;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
+
+mod attention;
+mod constraints;
+mod conv;
+mod dropout;
+mod embedding;
+mod graph;
+mod normalization;
+mod pool;
+mod positional;
+mod recurrent;
+mod reshape;
+mod transformer;
+mod upsample;
+mod wrappers;
+
+pub use attention::MultiHeadAttention;
+use constraints::CONSTRAINT_WITH_NORMALIZATION;
+pub use constraints::{WeightConstraint, WeightNormalization};
+pub use conv::{Conv1D, Conv2D, ConvTranspose2D, Padding, Padding1D, SeparableConv2D};
+pub use dropout::{AlphaDropout, Dropout, SpatialDropout2D};
+pub use embedding::Embedding;
+pub use graph::{GraphModel, Merge, NodeId};
+pub use normalization::{BatchNorm1d, BatchNorm2d, GroupNorm, LayerNorm, RMSNorm};
+pub use pool::{AvgPool1D, AvgPool2D, GlobalAvgPool, GlobalMaxPool, MaxPool1D, MaxPool2D};
+pub use positional::{LearnedPositionalEncoding, SinusoidalPositionalEncoding};
+pub use recurrent::{Recurrent, SimpleRNN, GRU, LSTM};
+pub use reshape::{Flatten, Reshape};
+pub use transformer::{TransformerDecoderLayer, TransformerEncoderLayer};
+pub use upsample::{Interpolation, Upsample2D};
+pub use wrappers::{Bidirectional, MergeMode, TimeDistributed};
+
+/// Trait que define a interface comum a todas as camadas de uma rede neural.
+///
+/// Todas as camadas recebem e produzem tensores `ArrayD<f32>` com o lote no primeiro eixo.
+pub trait Layer {
+    /// Realiza a passagem forward, guardando o necessário para a passagem backward.
+    ///
+    /// # Argumentos
+    ///
+    /// * `input` - Tensor de entrada da camada.
+    ///
+    /// # Retorno
+    ///
+    /// Retorna o tensor de saída da camada.
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32>;
+
+    /// Realiza a passagem backward, atualizando os parâmetros com o otimizador.
+    ///
+    /// # Argumentos
+    ///
+    /// * `output_error` - Gradiente da perda em relação à saída da camada.
+    /// * `optimizer` - Otimizador usado para atualizar os parâmetros.
+    ///
+    /// # Retorno
+    ///
+    /// Retorna o gradiente da perda em relação à entrada da camada.
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32>;
+
+    /// Calcula a forma da saída para uma entrada de forma `input_shape` (incluindo o lote).
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize>;
+
+    /// Retorna uma cópia do estado persistente da camada (pesos, vieses e estatísticas).
+    ///
+    /// As implementações padrão destes métodos servem para camadas sem parâmetros.
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        Vec::new()
+    }
+
+    /// Substitui o estado persistente da camada, na ordem retornada por `weights`.
+    fn set_weights(&mut self, _weights: Vec<ArrayD<f32>>) {}
+
+    /// Retorna uma cópia dos momentos do otimizador guardados na camada.
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        Vec::new()
+    }
+
+    /// Substitui os momentos do otimizador, na ordem retornada por `moments`.
+    fn set_moments(
+        &mut self,
+        _moments: Vec<ArrayD<f32>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    /// Número de parâmetros treináveis da camada.
+    fn param_count(&self) -> usize {
+        0
+    }
+
+    /// Alterna entre o modo de treinamento (`true`) e o de inferência (`false`).
+    ///
+    /// Apenas camadas com comportamento diferente nos dois modos, como o dropout,
+    /// precisam sobrescrever este método. Camadas compostas devem repassá-lo às internas.
+    fn set_training(&mut self, _training: bool) {}
+
+    /// Indica se os parâmetros da camada são atualizados na passagem backward.
+    fn trainable(&self) -> bool {
+        true
+    }
+
+    /// Congela (`false`) ou libera (`true`) os parâmetros da camada.
+    ///
+    /// Uma camada congelada não calcula os gradientes dos seus parâmetros nem os atualiza,
+    /// mas continua propagando o erro para a entrada. Estatísticas que não são parâmetros,
+    /// como as médias móveis da normalização em lote, seguem apenas o modo de treinamento.
+    /// Camadas sem parâmetros ignoram a chamada; camadas compostas devem repassá-la às internas.
+    fn set_trainable(&mut self, _trainable: bool) {}
+}
+
+/// Gera a implementação de `Layer` para o enum `LayerType`, delegando a cada variante.
+macro_rules! layer_types {
+    ($($variant:ident($layer:ty)),* $(,)?) => {
+        /// Enum que representa as camadas disponíveis.
+        /// Facilita a serialização e a desserialização de redes com camadas de tipos diferentes.
+        #[derive(Serialize, Deserialize, Clone)]
+        #[allow(clippy::large_enum_variant)]
+        pub enum LayerType {
+            $($variant($layer)),*
+        }
+
+        $(
+            impl From<$layer> for LayerType {
+                fn from(layer: $layer) -> Self {
+                    LayerType::$variant(layer)
+                }
+            }
+        )*
+
+        impl LayerType {
+            /// Acessa a camada como `&dyn Layer`.
+            pub fn as_layer(&self) -> &dyn Layer {
+                match self {
+                    $(LayerType::$variant(layer) => layer),*
+                }
+            }
+
+            /// Acessa a camada como `&mut dyn Layer`.
+            pub fn as_layer_mut(&mut self) -> &mut dyn Layer {
+                match self {
+                    $(LayerType::$variant(layer) => layer),*
+                }
+            }
+        }
+    };
+}
+
+layer_types! {
+    Dense(DenseLayer),
+    Conv1D(Conv1D),
+    Conv2D(Conv2D),
+    ConvTranspose2D(ConvTranspose2D),
+    SeparableConv2D(SeparableConv2D),
+    Upsample2D(Upsample2D),
+    MaxPool1D(MaxPool1D),
+    MaxPool2D(MaxPool2D),
+    AvgPool1D(AvgPool1D),
+    AvgPool2D(AvgPool2D),
+    GlobalAvgPool(GlobalAvgPool),
+    GlobalMaxPool(GlobalMaxPool),
+    Flatten(Flatten),
+    Reshape(Reshape),
+    Dropout(Dropout),
+    AlphaDropout(AlphaDropout),
+    SpatialDropout2D(SpatialDropout2D),
+    BatchNorm1d(BatchNorm1d),
+    BatchNorm2d(BatchNorm2d),
+    LayerNorm(LayerNorm),
+    RMSNorm(RMSNorm),
+    GroupNorm(GroupNorm),
+    Embedding(Embedding),
+    SimpleRNN(SimpleRNN),
+    LSTM(LSTM),
+    GRU(GRU),
+    Bidirectional(Bidirectional),
+    TimeDistributed(TimeDistributed),
+    MultiHeadAttention(MultiHeadAttention),
+    TransformerEncoderLayer(TransformerEncoderLayer),
+    TransformerDecoderLayer(TransformerDecoderLayer),
+    SinusoidalPositionalEncoding(SinusoidalPositionalEncoding),
+    LearnedPositionalEncoding(LearnedPositionalEncoding),
+    Graph(GraphModel),
+}
+
+impl LayerType {
+    /// Acessa a camada como `&dyn TiedWeights`, se ela puder compartilhar pesos.
+    pub(crate) fn as_tied(&self) -> Option<&dyn TiedWeights> {
+        match self {
+            LayerType::Dense(layer) => Some(layer),
+            LayerType::Embedding(layer) => Some(layer),
+            _ => None,
+        }
+    }
+
+    /// Acessa a camada como `&mut dyn TiedWeights`, se ela puder compartilhar pesos.
+    pub(crate) fn as_tied_mut(&mut self) -> Option<&mut dyn TiedWeights> {
+        match self {
+            LayerType::Dense(layer) => Some(layer),
+            LayerType::Embedding(layer) => Some(layer),
+            _ => None,
+        }
+    }
+}
+
+impl Layer for LayerType {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        self.as_layer_mut().forward(input)
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        self.as_layer_mut().backward(output_error, optimizer)
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        self.as_layer().output_shape(input_shape)
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        self.as_layer().weights()
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        self.as_layer_mut().set_weights(weights)
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        self.as_layer().moments()
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        self.as_layer_mut().set_moments(moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.as_layer().param_count()
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.as_layer_mut().set_training(training)
+    }
+
+    fn trainable(&self) -> bool {
+        self.as_layer().trainable()
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.as_layer_mut().set_trainable(trainable)
+    }
+}
+
+/// Inicialização uniforme de Xavier/Glorot, usada por todas as camadas com pesos.
+///
+/// # Argumentos
+///
+/// * `shape` - Forma do tensor de pesos.
+/// * `fan_in` - Número de entradas de cada neurônio.
+/// * `fan_out` - Número de saídas de cada neurônio.
+pub(crate) fn glorot_uniform(shape: &[usize], fan_in: usize, fan_out: usize) -> ArrayD<f32> {
+    let mut rng = thread_rng();
+    let std_dev = (2.0 / (fan_in + fan_out) as f32).sqrt();
+    let dist = Uniform::new(-std_dev, std_dev);
+    ArrayD::from_shape_fn(IxDyn(shape), |_| dist.sample(&mut rng))
+}
+
+/// Retorna cópias dos valores de uma lista de parâmetros.
+pub(crate) fn parameter_values(params: &[&Parameter]) -> Vec<ArrayD<f32>> {
+    params.iter().map(|param| param.value.clone()).collect()
+}
+
+/// Substitui os valores de uma lista de parâmetros, verificando as formas.
+pub(crate) fn set_parameter_values(params: &mut [&mut Parameter], values: Vec<ArrayD<f32>>) {
+    assert_eq!(
+        params.len(),
+        values.len(),
+        "número de tensores difere do número de parâmetros"
+    );
+    for (param, value) in params.iter_mut().zip(values) {
+        assert_eq!(
+            param.value.shape(),
+            value.shape(),
+            "forma do parâmetro incompatível"
+        );
+        param.value = value;
+    }
+}
+
+/// Retorna os momentos (`m` e `v`) de uma lista de parâmetros.
+pub(crate) fn parameter_moments(params: &[&Parameter]) -> Vec<ArrayD<f32>> {
+    params
+        .iter()
+        .flat_map(|param| [param.m.clone(), param.v.clone()])
+        .collect()
+}
+
+/// Substitui os momentos de uma lista de parâmetros, na ordem de `parameter_moments`.
+pub(crate) fn set_parameter_moments(
+    params: &mut [&mut Parameter],
+    moments: Vec<ArrayD<f32>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if moments.len() != 2 * params.len() {
+        return Err("número de momentos difere do número de parâmetros".into());
+    }
+    let mut moments = moments.into_iter();
+    for param in params.iter_mut() {
+        let (m, v) = (moments.next().unwrap(), moments.next().unwrap());
+        if m.shape() != param.value.shape() || v.shape() != param.value.shape() {
+            return Err("forma dos momentos incompatível com o parâmetro".into());
+        }
+        param.m = m;
+        param.v = v;
+    }
+    Ok(())
+}
+
+/// Concatena o estado persistente de uma lista de subcamadas.
+pub(crate) fn collect_weights(layers: Vec<&dyn Layer>) -> Vec<ArrayD<f32>> {
+    layers
+        .into_iter()
+        .flat_map(|layer| layer.weights())
+        .collect()
+}
+
+/// Distribui o estado persistente entre as subcamadas, na ordem de `collect_weights`.
+pub(crate) fn distribute_weights(layers: Vec<&mut dyn Layer>, weights: Vec<ArrayD<f32>>) {
+    let mut weights = weights.into_iter();
+    for layer in layers {
+        let count = layer.weights().len();
+        layer.set_weights(weights.by_ref().take(count).collect());
+    }
+}
+
+/// Concatena os momentos do otimizador de uma lista de subcamadas.
+pub(crate) fn collect_moments(layers: Vec<&dyn Layer>) -> Vec<ArrayD<f32>> {
+    layers
+        .into_iter()
+        .flat_map(|layer| layer.moments())
+        .collect()
+}
+
+/// Distribui os momentos entre as subcamadas, na ordem de `collect_moments`.
+pub(crate) fn distribute_moments(
+    layers: Vec<&mut dyn Layer>,
+    moments: Vec<ArrayD<f32>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut moments = moments.into_iter();
+    for layer in layers {
+        let count = layer.moments().len();
+        layer.set_moments(moments.by_ref().take(count).collect())?;
+    }
+    if moments.next().is_some() {
+        return Err("número de momentos difere do esperado".into());
+    }
+    Ok(())
+}
+
+/// Indica se alguma das subcamadas com parâmetros é treinável.
+pub(crate) fn any_trainable(layers: Vec<&dyn Layer>) -> bool {
+    layers
+        .into_iter()
+        .any(|layer| layer.param_count() > 0 && layer.trainable())
+}
+
+/// Visão de um parâmetro bidimensional.
+pub(crate) fn matrix(param: &Parameter) -> ArrayView2<'_, f32> {
+    param.value.view().into_dimensionality::<Ix2>().unwrap()
+}
+
+/// Projeção afim do último eixo de uma sequência: `(N, T, F)` → `(N, T, G)`, com pesos
+/// `(G, F)` e viés `(G,)`.
+pub(crate) fn project(input: &Array3<f32>, weights: &Parameter, bias: &Parameter) -> Array3<f32> {
+    let (batch, steps, features) = input.dim();
+    let rows = input.view().into_shape((batch * steps, features)).unwrap();
+    let projection = rows.dot(&matrix(weights).t()) + &bias.value;
+    let outputs = projection.len_of(Axis(1));
+    projection.into_shape((batch, steps, outputs)).unwrap()
+}
+
+/// Passagem backward de `project`: guarda os gradientes dos pesos e do viés a partir do
+/// gradiente da projeção `(N, T, G)` e retorna o erro da entrada `(N, T, F)`. Com
+/// `trainable` falso, os gradientes dos parâmetros não são calculados.
+pub(crate) fn project_backward(
+    d_projection: &Array3<f32>,
+    input: &Array3<f32>,
+    weights: &mut Parameter,
+    bias: &mut Parameter,
+    trainable: bool,
+) -> Array3<f32> {
+    let (batch, steps, features) = input.dim();
+    let outputs = d_projection.len_of(Axis(2));
+    let d_rows = d_projection
+        .as_standard_layout()
+        .into_owned()
+        .into_shape((batch * steps, outputs))
+        .unwrap();
+    let rows = input.view().into_shape((batch * steps, features)).unwrap();
+
+    let input_error = d_rows.dot(&matrix(weights));
+    if trainable {
+        weights.grad = d_rows.t().dot(&rows).into_dyn();
+        bias.grad = d_rows.sum_axis(Axis(0)).into_dyn();
+    }
+    input_error.into_shape((batch, steps, features)).unwrap()
+}
+
+/// Camada cuja matriz de pesos bidimensional pode ser compartilhada com outra camada
+/// (veja `NeuralNetwork::tie_weights`).
+///
+/// A passagem backward é dividida em duas metades, para que os gradientes de todas as camadas
+/// que usam os mesmos pesos sejam somados antes de uma única atualização.
+pub(crate) trait TiedWeights {
+    /// Matriz compartilhável, ou a razão pela qual a camada não pode compartilhá-la.
+    fn tied_weights(&self) -> Result<ArrayView2<'_, f32>, String>;
+
+    /// Substitui a matriz compartilhada, que deve ter a forma de `tied_weights`.
+    fn set_tied_weights(&mut self, weights: Array2<f32>);
+
+    /// Primeira metade da passagem backward: calcula o erro da entrada e, se a camada é
+    /// treinável, guarda os gradientes dos parâmetros sem aplicá-los.
+    ///
+    /// Com `shared_weights_trainable`, o gradiente da matriz compartilhada é guardado mesmo com
+    /// a camada congelada, como quando ela usa os pesos de uma camada treinável; os demais
+    /// parâmetros continuam congelados.
+    fn compute_gradients(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        shared_weights_trainable: bool,
+    ) -> ArrayD<f32>;
+
+    /// Retira o gradiente da matriz compartilhada guardado por `compute_gradients`, se houver.
+    /// Os gradientes dos demais parâmetros continuam guardados para `apply_gradients`.
+    fn take_weight_gradients(&mut self) -> Option<Array2<f32>>;
+
+    /// Soma `gradients` ao gradiente guardado da matriz compartilhada. Não tem efeito se a
+    /// camada não calculou gradientes, por exemplo por estar congelada.
+    fn add_weight_gradients(&mut self, gradients: ArrayView2<'_, f32>);
+
+    /// Segunda metade da passagem backward: aplica os gradientes guardados com o otimizador.
+    fn apply_gradients(&mut self, optimizer: &mut dyn Optimizer);
+}
 
 /// Representa uma camada densa (totalmente conectada) em uma rede neural.
+///
+/// A camada opera sobre o último eixo da entrada: uma entrada de forma
+/// `(..., input_size)` produz uma saída de forma `(..., output_size)`, e os demais
+/// eixos (lote, tempo, posição, ...) são tratados como amostras independentes.
 #[derive(Serialize)]
 pub struct DenseLayer {
-    /// Matriz de pesos da camada.
+    /// Matriz de pesos da camada. Com `normalization`, guarda os pesos antes da normalização.
     pub weights: Array2<f32>,
-    /// Vetor de vieses da camada.
+    /// Vetor de vieses da camada (vazio se a camada não tem viés).
     pub biases: Array1<f32>,
     /// Tipo de função de ativação usada pela camada.
     pub activation_type: ActivationType,
+    /// Restrição aplicada aos pesos após cada atualização (opcional).
+    pub constraint: Option<WeightConstraint>,
+    /// Reparametrização dos pesos (opcional).
+    pub normalization: Option<WeightNormalization>,
 
     /// Função de ativação da camada (ignorada na serialização).
     #[serde(skip)]
     pub activation: Option<Box<dyn Activation>>,
 
+    /// Se os pesos e vieses são atualizados na passagem backward.
+    trainable: bool,
+
     /// Última entrada processada pela camada (ignorada na serialização).
     #[serde(skip)]
-    pub input: Option<Array2<f32>>,
+    pub input: Option<ArrayD<f32>>,
 
     /// Última saída produzida pela camada (ignorada na serialização).
     #[serde(skip)]
-    pub output: Option<Array2<f32>>,
+    pub output: Option<ArrayD<f32>>,
+
+    /// Valores antes da ativação na última passagem forward, usados para calcular a derivada
+    /// da ativação (ignorados na serialização).
+    #[serde(skip)]
+    pre_activation: Option<ArrayD<f32>>,
+
+    /// Gradiente dos pesos calculado por `compute_gradients` e ainda não aplicado.
+    #[serde(skip)]
+    weight_gradients: Option<Array2<f32>>,
+    /// Gradiente dos vieses calculado por `compute_gradients` e ainda não aplicado.
+    #[serde(skip)]
+    bias_gradients: Option<Array1<f32>>,
 
     // Campos para o otimizador Adam (todos ignorados na serialização)
     /// Momento de primeira ordem para pesos (Adam).
@@ -56,10 +528,9 @@ impl DenseLayer {
     ///
     /// Retorna uma nova instância de `DenseLayer`.
     pub fn new(input_size: usize, output_size: usize, activation_type: ActivationType) -> Self {
-        let mut rng = thread_rng();
-        let std_dev = (2.0 / (input_size + output_size) as f32).sqrt();
-        let dist = Uniform::new(-std_dev, std_dev);
-        let weights = Array2::from_shape_fn((output_size, input_size), |_| dist.sample(&mut rng));
+        let weights = glorot_uniform(&[output_size, input_size], input_size, output_size)
+            .into_dimensionality()
+            .unwrap();
         let biases = Array1::zeros(output_size);
 
         let m_w = Array2::zeros((output_size, input_size));
@@ -72,8 +543,14 @@ impl DenseLayer {
             biases,
             activation: Some(Box::new(activation_type.clone())),
             activation_type: activation_type.clone(),
+            constraint: None,
+            normalization: None,
+            trainable: true,
             input: None,
             output: None,
+            pre_activation: None,
+            weight_gradients: None,
+            bias_gradients: None,
             m_w,
             v_w,
             m_b,
@@ -81,24 +558,251 @@ impl DenseLayer {
         }
     }
 
+    /// Ativa ou remove o vetor de vieses. Sem viés, a camada calcula apenas `x * W^T`, o que
+    /// é comum antes de camadas de normalização, que já têm o seu próprio deslocamento.
+    pub fn with_bias(mut self, use_bias: bool) -> Self {
+        let size = if use_bias { self.weights.nrows() } else { 0 };
+        if self.biases.len() != size {
+            self.biases = Array1::zeros(size);
+            self.m_b = Array1::zeros(size);
+            self.v_b = Array1::zeros(size);
+        }
+        self
+    }
+
+    /// Indica se a camada soma um vetor de vieses à saída.
+    pub fn use_bias(&self) -> bool {
+        !self.biases.is_empty()
+    }
+
+    /// Define uma restrição para os pesos, aplicada imediatamente e após cada atualização.
+    /// Não pode ser combinada com normalização de pesos.
+    pub fn with_constraint(mut self, constraint: WeightConstraint) -> Self {
+        assert!(
+            self.normalization.is_none(),
+            "{}",
+            CONSTRAINT_WITH_NORMALIZATION
+        );
+        constraint.apply(&mut self.weights);
+        self.constraint = Some(constraint);
+        self
+    }
+
+    /// Reparametriza os pesos com normalização de pesos, `w = g * v / ||v||` por neurônio,
+    /// preservando os pesos atuais.
+    /// Não pode ser combinada com uma restrição de pesos.
+    pub fn with_weight_norm(mut self) -> Self {
+        assert!(
+            self.constraint.is_none(),
+            "{}",
+            CONSTRAINT_WITH_NORMALIZATION
+        );
+        self.normalization = Some(WeightNormalization::weight(&self.weights));
+        self
+    }
+
+    /// Divide os pesos pelo seu maior valor singular, estimado com `power_iterations`
+    /// iterações de potência após cada atualização.
+    /// Não pode ser combinada com uma restrição de pesos.
+    pub fn with_spectral_norm(mut self, power_iterations: usize) -> Self {
+        assert!(
+            self.constraint.is_none(),
+            "{}",
+            CONSTRAINT_WITH_NORMALIZATION
+        );
+        self.normalization = Some(WeightNormalization::spectral(
+            &self.weights,
+            power_iterations,
+        ));
+        self
+    }
+
+    fn normalization_parameters(&self) -> Vec<&Parameter> {
+        self.normalization
+            .iter()
+            .flat_map(|normalization| normalization.parameters())
+            .collect()
+    }
+
+    fn normalization_parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        self.normalization
+            .iter_mut()
+            .flat_map(|normalization| normalization.parameters_mut())
+            .collect()
+    }
+
+    /// Pesos usados nas passagens forward e backward.
+    fn effective_weights(&self) -> Cow<'_, Array2<f32>> {
+        WeightNormalization::normalize(self.normalization.as_ref(), &self.weights)
+    }
+
     /// Restaura a função de ativação após a desserialização.
     pub fn restore_activation(&mut self) {
         self.activation = Some(Box::new(self.activation_type.clone()));
     }
+}
+
+impl TiedWeights for DenseLayer {
+    fn tied_weights(&self) -> Result<ArrayView2<'_, f32>, String> {
+        if self.normalization.is_some() {
+            return Err("camadas com pesos normalizados não podem compartilhar pesos".into());
+        }
+        Ok(self.weights.view())
+    }
+
+    fn set_tied_weights(&mut self, weights: Array2<f32>) {
+        self.weights = weights;
+    }
+
+    fn compute_gradients(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        shared_weights_trainable: bool,
+    ) -> ArrayD<f32> {
+        let input = self.input.as_ref().unwrap();
+        let z = self.pre_activation.as_ref().unwrap();
+
+        let activation_derivative = self.activation.as_ref().unwrap().derivative_array(z);
+        let delta = to_matrix(&(output_error * &activation_derivative));
+
+        let input_error = delta.dot(&*self.effective_weights());
+
+        if self.trainable || shared_weights_trainable {
+            let mut weight_gradients = delta.t().dot(&to_matrix(input));
+            if let Some(normalization) = &mut self.normalization {
+                weight_gradients = normalization.backward(&self.weights, &weight_gradients);
+            }
+            self.weight_gradients = Some(weight_gradients);
+        }
+        if self.trainable {
+            self.bias_gradients = Some(if self.use_bias() {
+                delta.sum_axis(Axis(0))
+            } else {
+                Array1::zeros(0)
+            });
+        }
+
+        input_error.into_shape(input.raw_dim()).unwrap()
+    }
+
+    fn take_weight_gradients(&mut self) -> Option<Array2<f32>> {
+        self.weight_gradients.take()
+    }
+
+    fn add_weight_gradients(&mut self, gradients: ArrayView2<'_, f32>) {
+        if let Some(weight_gradients) = &mut self.weight_gradients {
+            *weight_gradients += &gradients;
+        }
+    }
+
+    /// Se o gradiente dos pesos foi retirado com `take_weight_gradients`, os pesos recebem um
+    /// gradiente nulo; se além disso a camada não tem viés, nada é atualizado.
+    fn apply_gradients(&mut self, optimizer: &mut dyn Optimizer) {
+        let Some(bias_gradients) = self.bias_gradients.take() else {
+            return;
+        };
+        let weight_gradients = match self.weight_gradients.take() {
+            Some(weight_gradients) => weight_gradients,
+            None if self.use_bias() => Array2::zeros(self.weights.raw_dim()),
+            None => return,
+        };
+
+        match self
+            .normalization
+            .as_mut()
+            .filter(|normalization| !normalization.parameters().is_empty())
+        {
+            // Pesos, vieses e parâmetros da normalização formam um único passo do otimizador
+            Some(normalization) => {
+                let mut weights = Parameter {
+                    value: std::mem::take(&mut self.weights).into_dyn(),
+                    decay: true,
+                    grad: weight_gradients.into_dyn(),
+                    m: std::mem::take(&mut self.m_w).into_dyn(),
+                    v: std::mem::take(&mut self.v_w).into_dyn(),
+                };
+                let mut biases = Parameter {
+                    value: std::mem::take(&mut self.biases).into_dyn(),
+                    decay: false,
+                    grad: bias_gradients.into_dyn(),
+                    m: std::mem::take(&mut self.m_b).into_dyn(),
+                    v: std::mem::take(&mut self.v_b).into_dyn(),
+                };
+                let mut parameters = vec![&mut weights, &mut biases];
+                parameters.extend(normalization.parameters_mut());
+                optimizer.update_parameters(&mut parameters);
+
+                self.weights = weights.value.into_dimensionality().unwrap();
+                self.m_w = weights.m.into_dimensionality().unwrap();
+                self.v_w = weights.v.into_dimensionality().unwrap();
+                self.biases = biases.value.into_dimensionality().unwrap();
+                self.m_b = biases.m.into_dimensionality().unwrap();
+                self.v_b = biases.v.into_dimensionality().unwrap();
+            }
+            None => optimizer.update(
+                &mut self.weights,
+                &mut self.biases,
+                &weight_gradients,
+                &bias_gradients,
+                &mut self.m_w,
+                &mut self.v_w,
+                &mut self.m_b,
+                &mut self.v_b,
+            ),
+        }
+
+        if let Some(constraint) = &self.constraint {
+            constraint.apply(&mut self.weights);
+        }
+        if let Some(normalization) = &mut self.normalization {
+            normalization.refresh(&self.weights);
+        }
+    }
+}
+
+impl Layer for DenseLayer {
+    /// Calcula a forma da saída para uma entrada de forma `input_shape`.
+    ///
+    /// # Argumentos
+    ///
+    /// * `input_shape` - Forma da entrada, com as features no último eixo.
+    ///
+    /// # Retorno
+    ///
+    /// Retorna a forma da saída, igual à da entrada com o último eixo trocado por `output_size`.
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        let (output_size, input_size) = self.weights.dim();
+        assert_eq!(
+            input_shape.last(),
+            Some(&input_size),
+            "DenseLayer espera {} features no último eixo, recebeu a forma {:?}",
+            input_size,
+            input_shape
+        );
+        let mut shape = input_shape.to_vec();
+        *shape.last_mut().unwrap() = output_size;
+        shape
+    }
 
     /// Realiza a passagem forward para um lote de entradas.
     ///
     /// # Argumentos
     ///
-    /// * `input` - Array 2D contendo o lote de entradas.
+    /// * `input` - Tensor de forma `(..., input_size)` contendo o lote de entradas.
     ///
     /// # Retorno
     ///
-    /// Retorna um Array 2D contendo as saídas da camada para o lote de entradas.
-    pub fn forward(&mut self, input: &Array2<f32>) -> Array2<f32> {
+    /// Retorna um tensor de forma `(..., output_size)` contendo as saídas da camada.
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let output_shape = self.output_shape(input.shape());
         self.input = Some(input.clone());
-        let z = input.dot(&self.weights.t()) + &self.biases;
+        let mut z = to_matrix(input).dot(&self.effective_weights().t());
+        if self.use_bias() {
+            z += &self.biases;
+        }
+        let z = z.into_shape(IxDyn(&output_shape)).unwrap();
         let output = self.activation.as_ref().unwrap().activate_array(&z);
+        self.pre_activation = Some(z);
         self.output = Some(output.clone());
         output
     }
@@ -107,40 +811,126 @@ impl DenseLayer {
     ///
     /// # Argumentos
     ///
-    /// * `output_error` - Array 2D contendo os erros da camada seguinte.
+    /// * `output_error` - Tensor de forma `(..., output_size)` contendo os erros da camada seguinte.
     /// * `optimizer` - Otimizador usado para atualizar os pesos e vieses.
     ///
     /// # Retorno
     ///
-    /// Retorna um Array 2D contendo os erros propagados para a camada anterior.
-    pub fn backward(
+    /// Retorna um tensor com a forma da entrada contendo os erros propagados para a camada anterior.
+    fn backward(
         &mut self,
-        output_error: &Array2<f32>,
-        optimizer: &mut dyn crate::optimizer::Optimizer,
-    ) -> Array2<f32> {
-        let input = self.input.as_ref().unwrap();
-        let output = self.output.as_ref().unwrap();
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let input_error = self.compute_gradients(output_error, false);
+        self.apply_gradients(optimizer);
+        input_error
+    }
 
-        let activation_derivative = self.activation.as_ref().unwrap().derivative_array(&output);
-        let delta = output_error * &activation_derivative;
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        let mut weights = vec![
+            self.weights.clone().into_dyn(),
+            self.biases.clone().into_dyn(),
+        ];
+        weights.extend(parameter_values(&self.normalization_parameters()));
+        weights
+    }
 
-        let input_error = delta.dot(&self.weights);
+    fn set_weights(&mut self, mut weights: Vec<ArrayD<f32>>) {
+        assert!(
+            weights.len() >= 2,
+            "DenseLayer espera pesos, vieses e os parâmetros da normalização"
+        );
+        let extra = weights.split_off(2);
+        let [w, b]: [ArrayD<f32>; 2] = weights.try_into().unwrap();
+        self.weights = w
+            .into_dimensionality()
+            .expect("forma dos pesos incompatível");
+        self.biases = b
+            .into_dimensionality()
+            .expect("forma dos vieses incompatível");
+        set_parameter_values(&mut self.normalization_parameters_mut(), extra);
+        if let Some(normalization) = &mut self.normalization {
+            normalization.reset(&self.weights);
+        }
+    }
 
-        let weight_gradients = delta.t().dot(input);
-        let bias_gradients = delta.sum_axis(Axis(0));
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        let mut moments = vec![
+            self.m_w.clone().into_dyn(),
+            self.v_w.clone().into_dyn(),
+            self.m_b.clone().into_dyn(),
+            self.v_b.clone().into_dyn(),
+        ];
+        moments.extend(parameter_moments(&self.normalization_parameters()));
+        moments
+    }
 
-        optimizer.update(
-            &mut self.weights,
-            &mut self.biases,
-            &weight_gradients,
-            &bias_gradients,
-            &mut self.m_w,
-            &mut self.v_w,
-            &mut self.m_b,
-            &mut self.v_b,
-        );
+    fn set_moments(
+        &mut self,
+        mut moments: Vec<ArrayD<f32>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if moments.len() < 4 {
+            return Err("DenseLayer espera ao menos quatro momentos".into());
+        }
+        let extra = moments.split_off(4);
+        let [m_w, v_w, m_b, v_b]: [ArrayD<f32>; 4] = moments.try_into().unwrap();
+        self.m_w = m_w.into_dimensionality()?;
+        self.v_w = v_w.into_dimensionality()?;
+        self.m_b = m_b.into_dimensionality()?;
+        self.v_b = v_b.into_dimensionality()?;
+        set_parameter_moments(&mut self.normalization_parameters_mut(), extra)
+    }
 
-        input_error
+    fn param_count(&self) -> usize {
+        let normalization: usize = self
+            .normalization_parameters()
+            .iter()
+            .map(|param| param.len())
+            .sum();
+        self.weights.len() + self.biases.len() + normalization
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
+
+/// Achata todos os eixos, exceto o último, em um único eixo de amostras.
+fn to_matrix(x: &ArrayD<f32>) -> Array2<f32> {
+    let features = x.shape().last().copied().unwrap_or(1);
+    let rows = x.len() / features.max(1);
+    x.as_standard_layout()
+        .into_owned()
+        .into_shape((rows, features))
+        .unwrap()
+}
+
+/// Implementação manual de `Clone`, recriando a função de ativação a partir do tipo.
+impl Clone for DenseLayer {
+    fn clone(&self) -> Self {
+        DenseLayer {
+            weights: self.weights.clone(),
+            biases: self.biases.clone(),
+            activation_type: self.activation_type.clone(),
+            constraint: self.constraint,
+            normalization: self.normalization.clone(),
+            activation: Some(Box::new(self.activation_type.clone())),
+            trainable: self.trainable,
+            input: self.input.clone(),
+            output: self.output.clone(),
+            pre_activation: self.pre_activation.clone(),
+            weight_gradients: self.weight_gradients.clone(),
+            bias_gradients: self.bias_gradients.clone(),
+            m_w: self.m_w.clone(),
+            v_w: self.v_w.clone(),
+            m_b: self.m_b.clone(),
+            v_b: self.v_b.clone(),
+        }
     }
 }
 
@@ -155,6 +945,9 @@ impl<'de> Deserialize<'de> for DenseLayer {
             weights: Array2<f32>,
             biases: Array1<f32>,
             activation_type: ActivationType,
+            constraint: Option<WeightConstraint>,
+            normalization: Option<WeightNormalization>,
+            trainable: bool,
         }
 
         let data = DenseLayerData::deserialize(deserializer)?;
@@ -168,9 +961,15 @@ impl<'de> Deserialize<'de> for DenseLayer {
             weights: data.weights,
             biases: data.biases,
             activation_type: data.activation_type.clone(),
+            constraint: data.constraint,
+            normalization: data.normalization,
             activation: None,
+            trainable: data.trainable,
             input: None,
             output: None,
+            pre_activation: None,
+            weight_gradients: None,
+            bias_gradients: None,
             m_w,
             v_w,
             m_b,
@@ -0,0 +1,480 @@
+// bran/src/layers/attention.rs
+
+use super::dropout::MaskRng;
+use super::{
+    glorot_uniform, parameter_moments, parameter_values, project, project_backward,
+    set_parameter_moments, set_parameter_values, Layer,
+};
+use crate::optimizer::{Optimizer, Parameter};
+use ndarray::{
+    linalg::general_mat_mul, Array2, Array3, Array4, ArrayD, ArrayView3, Axis, Ix3, Zip,
+};
+use serde::{Deserialize, Serialize};
+
+/// Valor somado às pontuações de posições mascaradas antes do softmax.
+const MASKED: f32 = -1e9;
+
+/// Produto matricial em lote: `(B, M, K) x (B, K, P)` → `(B, M, P)`, em paralelo.
+fn batched_dot(a: ArrayView3<f32>, b: ArrayView3<f32>) -> Array3<f32> {
+    let mut output = Array3::zeros((a.len_of(Axis(0)), a.len_of(Axis(1)), b.len_of(Axis(2))));
+    Zip::from(output.outer_iter_mut())
+        .and(a.outer_iter())
+        .and(b.outer_iter())
+        .par_for_each(|mut output, a, b| general_mat_mul(1.0, &a, &b, 0.0, &mut output));
+    output
+}
+
+/// Transpõe as duas últimas dimensões de um lote de matrizes.
+fn transposed(x: &Array3<f32>) -> ArrayView3<'_, f32> {
+    x.view().permuted_axes([0, 2, 1])
+}
+
+/// Converte um tensor em uma sequência `(N, T, embed_dim)`, verificando a forma.
+fn sequence(x: &ArrayD<f32>, embed_dim: usize, name: &str) -> Array3<f32> {
+    let x = x
+        .view()
+        .into_dimensionality::<Ix3>()
+        .unwrap_or_else(|_| panic!("{} deve ter forma (N, T, embed_dim)", name));
+    assert_eq!(
+        x.len_of(Axis(2)),
+        embed_dim,
+        "{} deve ter {} features",
+        name,
+        embed_dim
+    );
+    x.as_standard_layout().into_owned()
+}
+
+/// Valores guardados na passagem forward para uso na passagem backward.
+#[derive(Clone)]
+struct AttentionCache {
+    query_input: Array3<f32>,
+    key_input: Array3<f32>,
+    value_input: Array3<f32>,
+    /// Projeções separadas por cabeça, de forma `(N * H, T, D)`.
+    q: Array3<f32>,
+    k: Array3<f32>,
+    v: Array3<f32>,
+    /// Pesos de atenção após o softmax, de forma `(N * H, Tq, Tk)`.
+    weights: Array3<f32>,
+    /// Máscara do dropout já escalada por `1 / (1 - rate)`, se aplicada.
+    dropout_mask: Option<Array3<f32>>,
+    /// Saída das cabeças reunidas, antes da projeção de saída, de forma `(N, Tq, E)`.
+    context: Array3<f32>,
+}
+
+/// Atenção multi-cabeça com produto escalar escalado.
+///
+/// As entradas `(N, T, embed_dim)` são projetadas em consultas, chaves e valores, divididas em
+/// `num_heads` cabeças de dimensão `embed_dim / num_heads`, e cada cabeça calcula
+/// `softmax(Q K^T / sqrt(D)) V`. As cabeças são reunidas e passam por uma projeção de saída.
+///
+/// Como `Layer`, a camada faz autoatenção (consultas, chaves e valores vêm da mesma entrada).
+/// Para atenção cruzada, use `forward_attention` e `backward_attention`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MultiHeadAttention {
+    /// Dimensão das entradas e da saída.
+    pub embed_dim: usize,
+    /// Número de cabeças.
+    pub num_heads: usize,
+    /// Pesos e viés da projeção das consultas, de formas `(E, E)` e `(E,)`.
+    pub wq: Parameter,
+    pub bq: Parameter,
+    /// Pesos e viés da projeção das chaves.
+    pub wk: Parameter,
+    pub bk: Parameter,
+    /// Pesos e viés da projeção dos valores.
+    pub wv: Parameter,
+    pub bv: Parameter,
+    /// Pesos e viés da projeção de saída.
+    pub wo: Parameter,
+    pub bo: Parameter,
+    /// Se cada posição só pode atender a posições anteriores ou iguais (máscara causal).
+    pub causal: bool,
+    /// Probabilidade de descartar cada peso de atenção durante o treinamento.
+    pub dropout: f32,
+    rng: MaskRng,
+    trainable: bool,
+
+    #[serde(skip)]
+    padding_mask: Option<Array2<bool>>,
+    #[serde(skip)]
+    training: bool,
+    #[serde(skip)]
+    cache: Option<AttentionCache>,
+}
+
+impl MultiHeadAttention {
+    /// Cria uma nova camada de atenção multi-cabeça.
+    ///
+    /// # Argumentos
+    ///
+    /// * `embed_dim` - Dimensão das entradas e da saída.
+    /// * `num_heads` - Número de cabeças; deve dividir `embed_dim`.
+    pub fn new(embed_dim: usize, num_heads: usize) -> Self {
+        assert!(
+            num_heads > 0 && embed_dim.is_multiple_of(num_heads),
+            "embed_dim deve ser divisível por num_heads"
+        );
+        let weights = || {
+            Parameter::new(
+                glorot_uniform(&[embed_dim, embed_dim], embed_dim, embed_dim),
+                true,
+            )
+        };
+        let bias = || Parameter::new(ArrayD::zeros(vec![embed_dim]), false);
+        MultiHeadAttention {
+            embed_dim,
+            num_heads,
+            wq: weights(),
+            bq: bias(),
+            wk: weights(),
+            bk: bias(),
+            wv: weights(),
+            bv: bias(),
+            wo: weights(),
+            bo: bias(),
+            causal: false,
+            dropout: 0.0,
+            rng: MaskRng::default(),
+            trainable: true,
+            padding_mask: None,
+            training: false,
+            cache: None,
+        }
+    }
+
+    /// Ativa a máscara causal: a consulta `i` só atende às chaves `j <= i`.
+    pub fn with_causal(mut self, causal: bool) -> Self {
+        self.causal = causal;
+        self
+    }
+
+    /// Define a taxa de dropout aplicada aos pesos de atenção durante o treinamento.
+    pub fn with_dropout(mut self, rate: f32) -> Self {
+        assert!(
+            (0.0..1.0).contains(&rate),
+            "a taxa de dropout deve estar no intervalo [0, 1)"
+        );
+        self.dropout = rate;
+        self
+    }
+
+    /// Define a semente do gerador das máscaras de dropout, tornando-as reproduzíveis.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = MaskRng::seeded(seed);
+        self
+    }
+
+    /// Define a máscara de preenchimento `(N, Tk)` usada nas próximas passagens forward:
+    /// chaves marcadas com `true` são ignoradas.
+    pub fn set_padding_mask(&mut self, mask: Array2<bool>) {
+        self.padding_mask = Some(mask);
+    }
+
+    /// Remove a máscara de preenchimento.
+    pub fn clear_padding_mask(&mut self) {
+        self.padding_mask = None;
+    }
+
+    /// Pesos de atenção da última passagem forward, de forma `(N, H, Tq, Tk)`, antes do dropout.
+    pub fn attention_weights(&self) -> Option<Array4<f32>> {
+        self.cache.as_ref().map(|cache| {
+            let (bh, tq, tk) = cache.weights.dim();
+            cache
+                .weights
+                .clone()
+                .into_shape((bh / self.num_heads, self.num_heads, tq, tk))
+                .unwrap()
+        })
+    }
+
+    /// Separa as cabeças: `(N, T, E)` → `(N * H, T, D)`.
+    fn split_heads(&self, x: Array3<f32>) -> Array3<f32> {
+        let (n, t, e) = x.dim();
+        let d = e / self.num_heads;
+        x.into_shape((n, t, self.num_heads, d))
+            .unwrap()
+            .permuted_axes([0, 2, 1, 3])
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((n * self.num_heads, t, d))
+            .unwrap()
+    }
+
+    /// Reúne as cabeças: `(N * H, T, D)` → `(N, T, E)`.
+    fn merge_heads(&self, x: Array3<f32>) -> Array3<f32> {
+        let (bh, t, d) = x.dim();
+        let n = bh / self.num_heads;
+        x.into_shape((n, self.num_heads, t, d))
+            .unwrap()
+            .permuted_axes([0, 2, 1, 3])
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((n, t, self.num_heads * d))
+            .unwrap()
+    }
+
+    /// Aplica as máscaras causal e de preenchimento às pontuações `(N * H, Tq, Tk)`.
+    fn apply_masks(&self, scores: &mut Array3<f32>) {
+        let (bh, _, tk) = scores.dim();
+        if self.causal {
+            for mut matrix in scores.outer_iter_mut() {
+                for ((i, j), score) in matrix.indexed_iter_mut() {
+                    if j > i {
+                        *score = MASKED;
+                    }
+                }
+            }
+        }
+        if let Some(mask) = &self.padding_mask {
+            assert_eq!(
+                mask.dim(),
+                (bh / self.num_heads, tk),
+                "a máscara de preenchimento deve ter forma (N, Tk)"
+            );
+            for (index, mut matrix) in scores.outer_iter_mut().enumerate() {
+                let padding = mask.row(index / self.num_heads);
+                for mut row in matrix.outer_iter_mut() {
+                    Zip::from(&mut row)
+                        .and(&padding)
+                        .for_each(|score, &masked| {
+                            if masked {
+                                *score = MASKED;
+                            }
+                        });
+                }
+            }
+        }
+    }
+
+    /// Atenção com consultas, chaves e valores possivelmente diferentes (atenção cruzada).
+    ///
+    /// # Argumentos
+    ///
+    /// * `query` - Consultas `(N, Tq, E)`.
+    /// * `key` - Chaves `(N, Tk, E)`.
+    /// * `value` - Valores `(N, Tk, E)`.
+    ///
+    /// # Retorno
+    ///
+    /// A saída `(N, Tq, E)`.
+    pub fn forward_attention(
+        &mut self,
+        query: &ArrayD<f32>,
+        key: &ArrayD<f32>,
+        value: &ArrayD<f32>,
+    ) -> ArrayD<f32> {
+        let query_input = sequence(query, self.embed_dim, "query");
+        let key_input = sequence(key, self.embed_dim, "key");
+        let value_input = sequence(value, self.embed_dim, "value");
+        assert_eq!(
+            key_input.dim().1,
+            value_input.dim().1,
+            "chaves e valores devem ter o mesmo comprimento"
+        );
+
+        let q = self.split_heads(project(&query_input, &self.wq, &self.bq));
+        let k = self.split_heads(project(&key_input, &self.wk, &self.bk));
+        let v = self.split_heads(project(&value_input, &self.wv, &self.bv));
+
+        let scale = 1.0 / ((self.embed_dim / self.num_heads) as f32).sqrt();
+        let mut scores = batched_dot(q.view(), transposed(&k)) * scale;
+        self.apply_masks(&mut scores);
+
+        // Softmax estável no eixo das chaves
+        for mut row in scores.lanes_mut(Axis(2)) {
+            let max = row.fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+            row.mapv_inplace(|x| (x - max).exp());
+            let sum = row.sum();
+            row /= sum;
+        }
+        let weights = scores;
+
+        let dropout_mask = (self.training && self.dropout > 0.0).then(|| {
+            let keep = 1.0 - self.dropout;
+            self.rng
+                .mask(weights.shape(), keep)
+                .into_dimensionality::<Ix3>()
+                .unwrap()
+                / keep
+        });
+        let attended = match &dropout_mask {
+            Some(mask) => batched_dot((&weights * mask).view(), v.view()),
+            None => batched_dot(weights.view(), v.view()),
+        };
+
+        let context = self.merge_heads(attended);
+        let output = project(&context, &self.wo, &self.bo);
+        self.cache = Some(AttentionCache {
+            query_input,
+            key_input,
+            value_input,
+            q,
+            k,
+            v,
+            weights,
+            dropout_mask,
+            context,
+        });
+        output.into_dyn()
+    }
+
+    /// Passagem backward de `forward_attention`, atualizando os parâmetros com o otimizador.
+    ///
+    /// # Retorno
+    ///
+    /// Os erros em relação às consultas, às chaves e aos valores.
+    pub fn backward_attention(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> (ArrayD<f32>, ArrayD<f32>, ArrayD<f32>) {
+        let cache = self
+            .cache
+            .take()
+            .expect("MultiHeadAttention::backward chamado antes de forward");
+        let output_error = sequence(output_error, self.embed_dim, "output_error");
+
+        let d_context = project_backward(
+            &output_error,
+            &cache.context,
+            &mut self.wo,
+            &mut self.bo,
+            self.trainable,
+        );
+        let d_attended = self.split_heads(d_context);
+
+        let dropped = match &cache.dropout_mask {
+            Some(mask) => &cache.weights * mask,
+            None => cache.weights.clone(),
+        };
+        let dv = batched_dot(transposed(&dropped), d_attended.view());
+        let mut d_weights = batched_dot(d_attended.view(), transposed(&cache.v));
+        if let Some(mask) = &cache.dropout_mask {
+            d_weights *= mask;
+        }
+
+        // Derivada do softmax: dS = A * (dA - sum(dA * A))
+        let mut d_scores = d_weights;
+        Zip::from(d_scores.lanes_mut(Axis(2)))
+            .and(cache.weights.lanes(Axis(2)))
+            .for_each(|mut d, a| {
+                let dot = d.dot(&a);
+                Zip::from(&mut d)
+                    .and(&a)
+                    .for_each(|d, &a| *d = a * (*d - dot));
+            });
+        let scale = 1.0 / ((self.embed_dim / self.num_heads) as f32).sqrt();
+        d_scores *= scale;
+
+        let dq = batched_dot(d_scores.view(), cache.k.view());
+        let dk = batched_dot(transposed(&d_scores), cache.q.view());
+
+        let query_error = project_backward(
+            &self.merge_heads(dq),
+            &cache.query_input,
+            &mut self.wq,
+            &mut self.bq,
+            self.trainable,
+        );
+        let key_error = project_backward(
+            &self.merge_heads(dk),
+            &cache.key_input,
+            &mut self.wk,
+            &mut self.bk,
+            self.trainable,
+        );
+        let value_error = project_backward(
+            &self.merge_heads(dv),
+            &cache.value_input,
+            &mut self.wv,
+            &mut self.bv,
+            self.trainable,
+        );
+        if self.trainable {
+            optimizer.update_parameters(&mut self.parameters_mut());
+        }
+        (
+            query_error.into_dyn(),
+            key_error.into_dyn(),
+            value_error.into_dyn(),
+        )
+    }
+
+    fn parameters(&self) -> Vec<&Parameter> {
+        vec![
+            &self.wq, &self.bq, &self.wk, &self.bk, &self.wv, &self.bv, &self.wo, &self.bo,
+        ]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        vec![
+            &mut self.wq,
+            &mut self.bq,
+            &mut self.wk,
+            &mut self.bk,
+            &mut self.wv,
+            &mut self.bv,
+            &mut self.wo,
+            &mut self.bo,
+        ]
+    }
+}
+
+impl Layer for MultiHeadAttention {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        self.forward_attention(input, input, input)
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let (query_error, key_error, value_error) =
+            self.backward_attention(output_error, optimizer);
+        query_error + key_error + value_error
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        assert!(
+            input_shape.len() == 3 && input_shape[2] == self.embed_dim,
+            "MultiHeadAttention espera uma entrada (N, T, {})",
+            self.embed_dim
+        );
+        input_shape.to_vec()
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&self.parameters())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut self.parameters_mut(), weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&self.parameters())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut self.parameters_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.parameters().iter().map(|param| param.len()).sum()
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
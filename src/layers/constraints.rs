@@ -0,0 +1,252 @@
+// bran/src/layers/constraints.rs
+
+use crate::optimizer::Parameter;
+use ndarray::{Array, Array1, Array2, Axis, Dimension, RemoveAxis};
+use rand::distributions::{Distribution, Uniform};
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Menor norma usada como divisor, para evitar divisões por zero.
+const MIN_NORM: f32 = 1e-12;
+
+/// Iterações de potência usadas ao criar a normalização espectral ou ao substituir os pesos.
+const INITIAL_POWER_ITERATIONS: usize = 20;
+
+/// Mensagem dos construtores que recusam combinar uma restrição com uma normalização: a
+/// restrição agiria sobre os pesos brutos `v`, e não sobre os pesos usados pela camada.
+pub(crate) const CONSTRAINT_WITH_NORMALIZATION: &str =
+    "uma restrição de pesos não pode ser combinada com normalização de pesos";
+
+/// Restrição aplicada aos pesos depois de cada atualização do otimizador.
+///
+/// As normas são calculadas por fatia do primeiro eixo dos pesos: uma linha `(input_size,)`
+/// da matriz de uma `DenseLayer` ou um filtro `(in_channels, altura, largura)` de uma `Conv2D`.
+/// Uma camada não pode ter ao mesmo tempo uma restrição e uma `WeightNormalization`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum WeightConstraint {
+    /// Reescala as fatias com norma maior que o limite para que tenham exatamente essa norma.
+    MaxNorm(f32),
+    /// Substitui os pesos negativos por zero.
+    NonNeg,
+}
+
+impl WeightConstraint {
+    /// Aplica a restrição aos pesos, no lugar.
+    pub fn apply<D: RemoveAxis>(&self, weights: &mut Array<f32, D>) {
+        match *self {
+            WeightConstraint::MaxNorm(max_norm) => {
+                assert!(max_norm > 0.0, "a norma máxima deve ser positiva");
+                for mut slice in weights.outer_iter_mut() {
+                    let norm = slice.iter().map(|w| w * w).sum::<f32>().sqrt();
+                    if norm > max_norm {
+                        slice *= max_norm / norm;
+                    }
+                }
+            }
+            WeightConstraint::NonNeg => weights.mapv_inplace(|w| w.max(0.0)),
+        }
+    }
+}
+
+/// Reparametrização dos pesos de uma camada. A camada guarda e atualiza os pesos brutos `v`;
+/// as passagens forward e backward usam os pesos normalizados `w`.
+///
+/// Os pesos são vistos como uma matriz com uma linha por fatia do primeiro eixo.
+#[derive(Serialize, Deserialize, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum WeightNormalization {
+    /// Normalização de pesos: `w = g * v / ||v||`, separando a direção de cada linha do seu
+    /// comprimento `g`, que é aprendido.
+    Weight {
+        /// Comprimento de cada linha, inicializado com a norma dos pesos originais.
+        magnitude: Parameter,
+    },
+    /// Normalização espectral: `w = v / sigma(v)`, em que `sigma` é o maior valor singular,
+    /// estimado por iteração de potência. Limita a constante de Lipschitz da camada, como nos
+    /// discriminadores de GANs.
+    Spectral {
+        /// Estimativa do vetor singular à esquerda, refinada após cada atualização dos pesos.
+        u: Array1<f32>,
+        /// Iterações de potência feitas após cada atualização.
+        power_iterations: usize,
+    },
+}
+
+impl WeightNormalization {
+    /// Cria uma normalização de pesos que preserva os pesos atuais.
+    pub fn weight<D: Dimension>(weights: &Array<f32, D>) -> Self {
+        let norms = row_norms(&matrix(weights));
+        WeightNormalization::Weight {
+            magnitude: Parameter::new(norms.into_dyn(), false),
+        }
+    }
+
+    /// Cria uma normalização espectral para os pesos atuais.
+    ///
+    /// # Argumentos
+    ///
+    /// * `weights` - Pesos brutos da camada.
+    /// * `power_iterations` - Iterações de potência após cada atualização (em geral, 1).
+    pub fn spectral<D: Dimension>(weights: &Array<f32, D>, power_iterations: usize) -> Self {
+        assert!(
+            power_iterations > 0,
+            "a normalização espectral precisa de ao menos uma iteração de potência"
+        );
+        let dist = Uniform::new_inclusive(-1.0, 1.0);
+        let mut rng = thread_rng();
+        let u = Array1::from_shape_simple_fn(weights.shape()[0], || dist.sample(&mut rng));
+        let mut normalization = WeightNormalization::Spectral {
+            u: normalized(u),
+            power_iterations,
+        };
+        normalization.reset(weights);
+        normalization
+    }
+
+    /// Cria uma normalização do mesmo tipo para novos pesos, como após recriar o kernel.
+    pub(crate) fn renew<D: Dimension>(&self, weights: &Array<f32, D>) -> Self {
+        match self {
+            WeightNormalization::Weight { .. } => WeightNormalization::weight(weights),
+            WeightNormalization::Spectral {
+                power_iterations, ..
+            } => WeightNormalization::spectral(weights, *power_iterations),
+        }
+    }
+
+    /// Pesos normalizados, ou os próprios pesos se não houver normalização.
+    pub(crate) fn normalize<'a, D: Dimension>(
+        normalization: Option<&Self>,
+        weights: &'a Array<f32, D>,
+    ) -> Cow<'a, Array<f32, D>> {
+        let Some(normalization) = normalization else {
+            return Cow::Borrowed(weights);
+        };
+        let v = matrix(weights);
+        let w = match normalization {
+            WeightNormalization::Weight { magnitude } => {
+                let scale = magnitude_vector(magnitude) / row_norms(&v);
+                v * &scale.insert_axis(Axis(1))
+            }
+            WeightNormalization::Spectral { u, .. } => {
+                let (_, sigma, _) = power_step(&v, u);
+                v / sigma
+            }
+        };
+        Cow::Owned(w.into_shape(weights.raw_dim()).unwrap())
+    }
+
+    /// Converte o gradiente dos pesos normalizados no gradiente dos pesos brutos. Na
+    /// normalização de pesos, também guarda o gradiente de `magnitude`.
+    ///
+    /// Na normalização espectral, os vetores singulares são tratados como constantes.
+    pub(crate) fn backward<D: Dimension>(
+        &mut self,
+        weights: &Array<f32, D>,
+        grad: &Array<f32, D>,
+    ) -> Array<f32, D> {
+        let v = matrix(weights);
+        let grad = matrix(grad);
+        let raw_grad = match self {
+            WeightNormalization::Weight { magnitude } => {
+                // dg = (dw . v) / ||v||;  dv = g / ||v|| * (dw - dg * v / ||v||)
+                let norms = row_norms(&v);
+                let magnitude_grad = (&grad * &v).sum_axis(Axis(1)) / &norms;
+                let projection = (&magnitude_grad / &norms).insert_axis(Axis(1));
+                let scale = (magnitude_vector(magnitude) / &norms).insert_axis(Axis(1));
+                magnitude.grad = magnitude_grad.into_dyn();
+                (grad - &v * &projection) * &scale
+            }
+            WeightNormalization::Spectral { u, .. } => {
+                // dv = (dw - (dw . w) u' v'^T) / sigma, com u' = v v' / sigma
+                let (right, sigma, left) = power_step(&v, u);
+                let inner = (&grad * &v).sum() / sigma;
+                let outer = left.insert_axis(Axis(1)).dot(&right.insert_axis(Axis(0)));
+                (grad - outer * inner) / sigma
+            }
+        };
+        raw_grad.into_shape(weights.raw_dim()).unwrap()
+    }
+
+    /// Refina a estimativa do valor singular após uma atualização dos pesos brutos.
+    pub(crate) fn refresh<D: Dimension>(&mut self, weights: &Array<f32, D>) {
+        if let WeightNormalization::Spectral {
+            power_iterations, ..
+        } = *self
+        {
+            self.iterate(weights, power_iterations);
+        }
+    }
+
+    /// Recalcula a estimativa do valor singular depois que os pesos brutos foram substituídos.
+    pub(crate) fn reset<D: Dimension>(&mut self, weights: &Array<f32, D>) {
+        if let WeightNormalization::Spectral {
+            power_iterations, ..
+        } = *self
+        {
+            self.iterate(weights, power_iterations.max(INITIAL_POWER_ITERATIONS));
+        }
+    }
+
+    fn iterate<D: Dimension>(&mut self, weights: &Array<f32, D>, iterations: usize) {
+        if let WeightNormalization::Spectral { u, .. } = self {
+            let v = matrix(weights);
+            for _ in 0..iterations {
+                let (_, _, left) = power_step(&v, u);
+                *u = left;
+            }
+        }
+    }
+
+    /// Parâmetros treináveis introduzidos pela normalização.
+    pub(crate) fn parameters(&self) -> Vec<&Parameter> {
+        match self {
+            WeightNormalization::Weight { magnitude } => vec![magnitude],
+            WeightNormalization::Spectral { .. } => Vec::new(),
+        }
+    }
+
+    pub(crate) fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        match self {
+            WeightNormalization::Weight { magnitude } => vec![magnitude],
+            WeightNormalization::Spectral { .. } => Vec::new(),
+        }
+    }
+}
+
+/// Pesos como uma matriz com uma linha por fatia do primeiro eixo.
+fn matrix<D: Dimension>(weights: &Array<f32, D>) -> Array2<f32> {
+    let rows = weights.shape()[0];
+    weights
+        .as_standard_layout()
+        .into_owned()
+        .into_shape((rows, weights.len() / rows))
+        .unwrap()
+}
+
+fn row_norms(matrix: &Array2<f32>) -> Array1<f32> {
+    matrix.map_axis(Axis(1), |row| row.dot(&row).sqrt().max(MIN_NORM))
+}
+
+fn magnitude_vector(magnitude: &Parameter) -> Array1<f32> {
+    magnitude
+        .value
+        .view()
+        .into_shape(magnitude.len())
+        .unwrap()
+        .to_owned()
+}
+
+fn normalized(x: Array1<f32>) -> Array1<f32> {
+    let norm = x.dot(&x).sqrt().max(MIN_NORM);
+    x / norm
+}
+
+/// Um passo da iteração de potência a partir de `u`: retorna o vetor singular à direita
+/// `v' = normalize(W^T u)`, a estimativa `sigma = ||W v'||` e o novo `u' = W v' / sigma`.
+fn power_step(w: &Array2<f32>, u: &Array1<f32>) -> (Array1<f32>, f32, Array1<f32>) {
+    let right = normalized(w.t().dot(u));
+    let image = w.dot(&right);
+    let sigma = image.dot(&image).sqrt().max(MIN_NORM);
+    (right, sigma, image / sigma)
+}
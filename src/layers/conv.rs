@@ -0,0 +1,1475 @@
+// bran/src/layers/conv.rs
+
+use super::{
+    distribute_weights, glorot_uniform, parameter_moments, parameter_values, set_parameter_moments,
+    set_parameter_values, Layer, WeightConstraint, WeightNormalization,
+    CONSTRAINT_WITH_NORMALIZATION,
+};
+use crate::activations::{Activation, ActivationType};
+use crate::optimizer::{Optimizer, Parameter};
+use ndarray::linalg::general_mat_mul;
+use ndarray::{s, Array2, Array3, Array4, ArrayD, ArrayView4, Axis, Ix1, Ix3, Ix4, Zip};
+use serde::{Deserialize, Serialize};
+
+/// Modo de preenchimento (padding) das bordas em camadas convolucionais.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Padding {
+    /// Sem preenchimento: o kernel só é aplicado onde cabe inteiramente na entrada.
+    Valid,
+    /// Preenchimento com zeros para que a saída tenha `ceil(entrada / stride)` posições.
+    /// Quando o total é ímpar, o lado final recebe o zero extra.
+    Same,
+    /// Preenchimento simétrico explícito com zeros: `(altura, largura)`.
+    Explicit(usize, usize),
+}
+
+/// Geometria de uma convolução 2D: dimensões de entrada e saída, kernel, stride,
+/// dilatação e preenchimento de cada borda.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Geometry2D {
+    pub input: (usize, usize),
+    pub output: (usize, usize),
+    pub kernel: (usize, usize),
+    pub stride: (usize, usize),
+    pub dilation: (usize, usize),
+    /// Preenchimento `(topo, base, esquerda, direita)`.
+    pub padding: (usize, usize, usize, usize),
+}
+
+impl Geometry2D {
+    /// Calcula a geometria de uma convolução sobre uma entrada `(altura, largura)`.
+    pub fn new(
+        input: (usize, usize),
+        kernel: (usize, usize),
+        stride: (usize, usize),
+        dilation: (usize, usize),
+        padding: Padding,
+    ) -> Self {
+        let (top, bottom) = pad_amounts(input.0, kernel.0, stride.0, dilation.0, padding, 0);
+        let (left, right) = pad_amounts(input.1, kernel.1, stride.1, dilation.1, padding, 1);
+        Self::with_padding(input, kernel, stride, dilation, (top, bottom, left, right))
+    }
+
+    /// Calcula a geometria com o preenchimento de cada borda já definido.
+    pub fn with_padding(
+        input: (usize, usize),
+        kernel: (usize, usize),
+        stride: (usize, usize),
+        dilation: (usize, usize),
+        padding: (usize, usize, usize, usize),
+    ) -> Self {
+        let output_len = |len: usize, pad: usize, k: usize, s: usize, d: usize| {
+            let span = d * (k - 1) + 1;
+            assert!(
+                len + pad >= span,
+                "kernel efetivo ({}) maior que a entrada preenchida ({})",
+                span,
+                len + pad
+            );
+            (len + pad - span) / s + 1
+        };
+        let output = (
+            output_len(
+                input.0,
+                padding.0 + padding.1,
+                kernel.0,
+                stride.0,
+                dilation.0,
+            ),
+            output_len(
+                input.1,
+                padding.2 + padding.3,
+                kernel.1,
+                stride.1,
+                dilation.1,
+            ),
+        );
+        Geometry2D {
+            input,
+            output,
+            kernel,
+            stride,
+            dilation,
+            padding,
+        }
+    }
+
+    /// Geometria de uma operação 1D sobre uma sequência de tamanho `len`, vista como uma
+    /// imagem `(1, len)`.
+    pub fn sequence(
+        len: usize,
+        kernel: usize,
+        stride: usize,
+        dilation: usize,
+        padding: Padding1D,
+    ) -> Self {
+        let (left, right) = match padding {
+            Padding1D::Valid => (0, 0),
+            Padding1D::Same => pad_amounts(len, kernel, stride, dilation, Padding::Same, 1),
+            Padding1D::Causal => (dilation * (kernel - 1), 0),
+            Padding1D::Explicit(pad) => (pad, pad),
+        };
+        Self::with_padding(
+            (1, len),
+            (1, kernel),
+            (1, stride),
+            (1, dilation),
+            (0, 0, left, right),
+        )
+    }
+
+    /// Posição na entrada (sem preenchimento) lida pela saída `(oy, ox)` e pelo elemento `(ky, kx)`
+    /// do kernel, ou `None` se cair no preenchimento.
+    #[inline]
+    pub fn source(&self, oy: usize, ox: usize, ky: usize, kx: usize) -> Option<(usize, usize)> {
+        let y = (oy * self.stride.0 + ky * self.dilation.0) as isize - self.padding.0 as isize;
+        let x = (ox * self.stride.1 + kx * self.dilation.1) as isize - self.padding.2 as isize;
+        if y < 0 || x < 0 || y >= self.input.0 as isize || x >= self.input.1 as isize {
+            None
+        } else {
+            Some((y as usize, x as usize))
+        }
+    }
+}
+
+/// Preenchimento `(início, fim)` de um eixo para o modo de padding indicado.
+fn pad_amounts(
+    len: usize,
+    kernel: usize,
+    stride: usize,
+    dilation: usize,
+    padding: Padding,
+    axis: usize,
+) -> (usize, usize) {
+    match padding {
+        Padding::Valid => (0, 0),
+        Padding::Explicit(h, w) => {
+            let pad = if axis == 0 { h } else { w };
+            (pad, pad)
+        }
+        Padding::Same => {
+            let span = dilation * (kernel - 1) + 1;
+            let output = len.div_ceil(stride);
+            let total = ((output - 1) * stride + span).saturating_sub(len);
+            (total / 2, total - total / 2)
+        }
+    }
+}
+
+/// Reorganiza as janelas da convolução em uma matriz (im2col), em paralelo sobre o lote.
+///
+/// # Retorno
+///
+/// Matriz de forma `(N * OH * OW, C * KH * KW)`, em que cada linha é uma janela da entrada.
+pub(crate) fn im2col(input: &ArrayView4<f32>, geometry: &Geometry2D) -> Array2<f32> {
+    let (n, c, _, _) = input.dim();
+    let (oh, ow) = geometry.output;
+    let (kh, kw) = geometry.kernel;
+    let mut cols = Array3::<f32>::zeros((n, oh * ow, c * kh * kw));
+
+    Zip::from(cols.outer_iter_mut())
+        .and(input.outer_iter())
+        .par_for_each(|mut cols, image| {
+            for oy in 0..oh {
+                for ox in 0..ow {
+                    let mut row = cols.row_mut(oy * ow + ox);
+                    for ky in 0..kh {
+                        for kx in 0..kw {
+                            if let Some((y, x)) = geometry.source(oy, ox, ky, kx) {
+                                for ch in 0..c {
+                                    row[(ch * kh + ky) * kw + kx] = image[[ch, y, x]];
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+    cols.into_shape((n * oh * ow, c * kh * kw)).unwrap()
+}
+
+/// Operação inversa de `im2col`: acumula cada janela de volta na posição de origem (col2im).
+///
+/// # Retorno
+///
+/// Tensor de forma `(N, C, H, W)` com a soma das contribuições de todas as janelas.
+pub(crate) fn col2im(
+    cols: &Array2<f32>,
+    geometry: &Geometry2D,
+    batch: usize,
+    channels: usize,
+) -> Array4<f32> {
+    let (h, w) = geometry.input;
+    let (oh, ow) = geometry.output;
+    let (kh, kw) = geometry.kernel;
+    let cols = cols
+        .view()
+        .into_shape((batch, oh * ow, channels * kh * kw))
+        .unwrap();
+    let mut images = Array4::<f32>::zeros((batch, channels, h, w));
+
+    Zip::from(images.outer_iter_mut())
+        .and(cols.outer_iter())
+        .par_for_each(|mut image, cols| {
+            for oy in 0..oh {
+                for ox in 0..ow {
+                    let row = cols.row(oy * ow + ox);
+                    for ky in 0..kh {
+                        for kx in 0..kw {
+                            if let Some((y, x)) = geometry.source(oy, ox, ky, kx) {
+                                for ch in 0..channels {
+                                    image[[ch, y, x]] += row[(ch * kh + ky) * kw + kx];
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+    images
+}
+
+/// Converte `(N, C, H, W)` em uma matriz `(N * H * W, C)` com os canais nas colunas.
+pub(crate) fn channels_last(x: &ArrayD<f32>) -> Array2<f32> {
+    let x = x.view().into_dimensionality::<Ix4>().unwrap();
+    let (n, c, h, w) = x.dim();
+    x.permuted_axes([0, 2, 3, 1])
+        .as_standard_layout()
+        .into_owned()
+        .into_shape((n * h * w, c))
+        .unwrap()
+}
+
+/// Operação inversa de `channels_last`, produzindo `(N, C, H, W)`.
+pub(crate) fn channels_first(x: Array2<f32>, n: usize, h: usize, w: usize) -> ArrayD<f32> {
+    let c = x.ncols();
+    x.into_shape((n, h, w, c))
+        .unwrap()
+        .permuted_axes([0, 3, 1, 2])
+        .as_standard_layout()
+        .into_owned()
+        .into_dyn()
+}
+
+/// Passagem forward compartilhada pelas convoluções: im2col seguido do produto pelo kernel.
+///
+/// Com `groups > 1`, os canais de entrada e de saída são divididos em grupos e cada grupo de
+/// saída só enxerga o grupo de entrada correspondente. Como as colunas do im2col são ordenadas
+/// por canal, cada grupo é uma faixa contígua de colunas.
+///
+/// # Retorno
+///
+/// As janelas `(N * OH * OW, C * KH * KW)` e a saída pré-ativação `(N * OH * OW, out_channels)`.
+fn convolve(
+    input: &ArrayView4<f32>,
+    geometry: &Geometry2D,
+    kernel: &ArrayD<f32>,
+    bias: Option<&Parameter>,
+    groups: usize,
+) -> (Array2<f32>, Array2<f32>) {
+    let cols = im2col(input, geometry);
+    let weights = kernel_matrix(kernel);
+    let (out_channels, group_cols) = weights.dim();
+    let group_out = out_channels / groups;
+
+    let mut z = Array2::zeros((cols.nrows(), out_channels));
+    for g in 0..groups {
+        general_mat_mul(
+            1.0,
+            &cols.slice(s![.., g * group_cols..(g + 1) * group_cols]),
+            &weights
+                .slice(s![g * group_out..(g + 1) * group_out, ..])
+                .t(),
+            0.0,
+            &mut z.slice_mut(s![.., g * group_out..(g + 1) * group_out]),
+        );
+    }
+    if let Some(bias) = bias {
+        z += &bias.value.view().into_dimensionality::<Ix1>().unwrap();
+    }
+    (cols, z)
+}
+
+/// Passagem backward compartilhada: calcula os gradientes do kernel e do viés a partir do erro
+/// pré-ativação `delta` `(N * OH * OW, out_channels)` e retorna o erro da entrada `(N, C, H, W)`.
+/// Com `trainable` falso, apenas o erro da entrada é calculado.
+#[allow(clippy::too_many_arguments)]
+fn convolve_backward(
+    delta: &Array2<f32>,
+    cache: &ConvCache,
+    kernel: &ArrayD<f32>,
+    kernel_grad: &mut ArrayD<f32>,
+    bias: Option<&mut Parameter>,
+    in_channels: usize,
+    groups: usize,
+    trainable: bool,
+) -> Array4<f32> {
+    let weights = kernel_matrix(kernel);
+    let (out_channels, group_cols) = weights.dim();
+    let group_out = out_channels / groups;
+
+    let mut weights_grad = Array2::zeros((out_channels, group_cols));
+    let mut input_cols = Array2::zeros(cache.cols.dim());
+    for g in 0..groups {
+        let delta = delta.slice(s![.., g * group_out..(g + 1) * group_out]);
+        let window = s![.., g * group_cols..(g + 1) * group_cols];
+        if trainable {
+            general_mat_mul(
+                1.0,
+                &delta.t(),
+                &cache.cols.slice(window),
+                0.0,
+                &mut weights_grad.slice_mut(s![g * group_out..(g + 1) * group_out, ..]),
+            );
+        }
+        general_mat_mul(
+            1.0,
+            &delta,
+            &weights.slice(s![g * group_out..(g + 1) * group_out, ..]),
+            0.0,
+            &mut input_cols.slice_mut(window),
+        );
+    }
+    if trainable {
+        *kernel_grad = weights_grad.into_shape(kernel.raw_dim()).unwrap();
+        if let Some(bias) = bias {
+            bias.grad = delta.sum_axis(Axis(0)).into_dyn();
+        }
+    }
+    col2im(&input_cols, &cache.geometry, cache.batch, in_channels)
+}
+
+/// Kernel `(out_channels, in_channels, ...)` achatado em uma matriz `(out_channels, resto)`.
+fn kernel_matrix(kernel: &ArrayD<f32>) -> Array2<f32> {
+    let out_channels = kernel.shape()[0];
+    let rest = kernel.len() / out_channels;
+    kernel
+        .view()
+        .into_shape((out_channels, rest))
+        .unwrap()
+        .to_owned()
+}
+
+/// Gera os construtores de restrição e normalização do kernel de uma camada convolucional,
+/// e os passos comuns da passagem backward.
+macro_rules! kernel_options {
+    ($($name:ident),+) => {
+        $(
+            impl $name {
+                /// Define uma restrição para o kernel, aplicada imediatamente e após cada
+                /// atualização. As normas são calculadas por fatia do primeiro eixo do kernel.
+                /// Não pode ser combinada com normalização de pesos.
+                pub fn with_constraint(mut self, constraint: WeightConstraint) -> Self {
+                    assert!(self.normalization.is_none(), "{}", CONSTRAINT_WITH_NORMALIZATION);
+                    constraint.apply(&mut self.kernel.value);
+                    self.constraint = Some(constraint);
+                    self
+                }
+
+                /// Reparametriza o kernel com normalização de pesos, `w = g * v / ||v||` por
+                /// fatia do primeiro eixo, preservando o kernel atual.
+                /// Não pode ser combinada com uma restrição de pesos.
+                pub fn with_weight_norm(mut self) -> Self {
+                    assert!(self.constraint.is_none(), "{}", CONSTRAINT_WITH_NORMALIZATION);
+                    self.normalization = Some(WeightNormalization::weight(&self.kernel.value));
+                    self
+                }
+
+                /// Divide o kernel, visto como uma matriz com uma linha por fatia do primeiro
+                /// eixo, pelo seu maior valor singular, estimado com `power_iterations`
+                /// iterações de potência após cada atualização.
+                /// Não pode ser combinada com uma restrição de pesos.
+                pub fn with_spectral_norm(mut self, power_iterations: usize) -> Self {
+                    assert!(self.constraint.is_none(), "{}", CONSTRAINT_WITH_NORMALIZATION);
+                    self.normalization = Some(WeightNormalization::spectral(
+                        &self.kernel.value,
+                        power_iterations,
+                    ));
+                    self
+                }
+
+                /// Converte o gradiente do kernel normalizado no gradiente do kernel guardado.
+                fn normalize_gradients(&mut self) {
+                    if let (true, Some(normalization)) = (self.trainable, &mut self.normalization) {
+                        self.kernel.grad = normalization.backward(&self.kernel.value, &self.kernel.grad);
+                    }
+                }
+
+                /// Aplica a restrição e atualiza a normalização após um passo do otimizador.
+                fn after_update(&mut self) {
+                    if let Some(constraint) = &self.constraint {
+                        constraint.apply(&mut self.kernel.value);
+                    }
+                    if let Some(normalization) = &mut self.normalization {
+                        normalization.refresh(&self.kernel.value);
+                    }
+                }
+            }
+        )+
+    };
+}
+
+kernel_options!(Conv1D, Conv2D, ConvTranspose2D);
+
+/// Valores guardados na passagem forward para uso na passagem backward.
+#[derive(Clone)]
+struct ConvCache {
+    cols: Array2<f32>,
+    geometry: Geometry2D,
+    batch: usize,
+    z: ArrayD<f32>,
+}
+
+/// Camada convolucional 2D sobre entradas no formato NCHW `(lote, canais, altura, largura)`.
+///
+/// A convolução é calculada com im2col: as janelas da entrada são reorganizadas em uma
+/// matriz, multiplicada pelo kernel achatado. A montagem das janelas é paralelizada
+/// sobre o lote com rayon.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Conv2D {
+    /// Número de canais de entrada.
+    pub in_channels: usize,
+    /// Número de canais de saída (filtros).
+    pub out_channels: usize,
+    /// Tamanho do kernel `(altura, largura)`.
+    pub kernel_size: (usize, usize),
+    /// Passo da convolução `(vertical, horizontal)`.
+    pub stride: (usize, usize),
+    /// Modo de preenchimento das bordas.
+    pub padding: Padding,
+    /// Dilatação do kernel `(vertical, horizontal)`.
+    pub dilation: (usize, usize),
+    /// Número de grupos em que os canais são divididos.
+    pub groups: usize,
+    /// Kernel de forma `(out_channels, in_channels / groups, altura, largura)`.
+    pub kernel: Parameter,
+    /// Vetor de vieses, um por canal de saída (opcional).
+    pub bias: Option<Parameter>,
+    /// Tipo de função de ativação usada pela camada.
+    pub activation_type: ActivationType,
+    /// Restrição aplicada ao kernel após cada atualização (opcional).
+    pub constraint: Option<WeightConstraint>,
+    /// Reparametrização do kernel (opcional). Com ela, `kernel` guarda os pesos antes da
+    /// normalização.
+    pub normalization: Option<WeightNormalization>,
+    trainable: bool,
+
+    #[serde(skip)]
+    cache: Option<ConvCache>,
+}
+
+impl Conv2D {
+    /// Cria uma nova camada convolucional com stride 1, sem preenchimento, sem dilatação e com viés.
+    ///
+    /// # Argumentos
+    ///
+    /// * `in_channels` - Número de canais de entrada.
+    /// * `out_channels` - Número de canais de saída.
+    /// * `kernel_size` - Tamanho do kernel `(altura, largura)`.
+    /// * `activation_type` - Tipo de função de ativação a ser usada.
+    pub fn new(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: (usize, usize),
+        activation_type: ActivationType,
+    ) -> Self {
+        let (kh, kw) = kernel_size;
+        let kernel = glorot_uniform(
+            &[out_channels, in_channels, kh, kw],
+            in_channels * kh * kw,
+            out_channels * kh * kw,
+        );
+        Conv2D {
+            in_channels,
+            out_channels,
+            kernel_size,
+            stride: (1, 1),
+            padding: Padding::Valid,
+            dilation: (1, 1),
+            groups: 1,
+            kernel: Parameter::new(kernel, true),
+            bias: Some(Parameter::new(ArrayD::zeros(vec![out_channels]), false)),
+            activation_type,
+            constraint: None,
+            normalization: None,
+            trainable: true,
+            cache: None,
+        }
+    }
+
+    /// Cria uma convolução depthwise: cada canal de entrada é filtrado separadamente por
+    /// `depth_multiplier` kernels (equivale a `groups = in_channels`).
+    ///
+    /// # Argumentos
+    ///
+    /// * `in_channels` - Número de canais de entrada.
+    /// * `depth_multiplier` - Número de canais de saída por canal de entrada.
+    /// * `kernel_size` - Tamanho do kernel `(altura, largura)`.
+    /// * `activation_type` - Tipo de função de ativação a ser usada.
+    pub fn depthwise(
+        in_channels: usize,
+        depth_multiplier: usize,
+        kernel_size: (usize, usize),
+        activation_type: ActivationType,
+    ) -> Self {
+        Conv2D::new(
+            in_channels,
+            in_channels * depth_multiplier,
+            kernel_size,
+            activation_type,
+        )
+        .with_groups(in_channels)
+    }
+
+    /// Define o passo da convolução.
+    pub fn with_stride(mut self, stride: (usize, usize)) -> Self {
+        assert!(stride.0 > 0 && stride.1 > 0, "o stride deve ser positivo");
+        self.stride = stride;
+        self
+    }
+
+    /// Divide os canais em `groups` grupos independentes, reinicializando o kernel.
+    ///
+    /// Cada grupo de `out_channels / groups` saídas só enxerga `in_channels / groups` entradas,
+    /// o que divide o número de parâmetros e de operações por `groups`.
+    pub fn with_groups(mut self, groups: usize) -> Self {
+        assert!(
+            groups > 0
+                && self.in_channels.is_multiple_of(groups)
+                && self.out_channels.is_multiple_of(groups),
+            "groups deve dividir in_channels ({}) e out_channels ({})",
+            self.in_channels,
+            self.out_channels
+        );
+        let (kh, kw) = self.kernel_size;
+        let group_in = self.in_channels / groups;
+        let kernel = glorot_uniform(
+            &[self.out_channels, group_in, kh, kw],
+            group_in * kh * kw,
+            self.out_channels / groups * kh * kw,
+        );
+        self.groups = groups;
+        self.kernel = Parameter::new(kernel, true);
+        if let Some(constraint) = &self.constraint {
+            constraint.apply(&mut self.kernel.value);
+        }
+        if let Some(normalization) = &self.normalization {
+            self.normalization = Some(normalization.renew(&self.kernel.value));
+        }
+        self
+    }
+
+    /// Define o modo de preenchimento das bordas.
+    pub fn with_padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Define a dilatação do kernel.
+    pub fn with_dilation(mut self, dilation: (usize, usize)) -> Self {
+        assert!(
+            dilation.0 > 0 && dilation.1 > 0,
+            "a dilatação deve ser positiva"
+        );
+        self.dilation = dilation;
+        self
+    }
+
+    /// Ativa ou remove o viés.
+    pub fn with_bias(mut self, use_bias: bool) -> Self {
+        self.bias = use_bias.then(|| Parameter::new(ArrayD::zeros(vec![self.out_channels]), false));
+        self
+    }
+
+    /// Geometria da convolução para uma entrada `(altura, largura)`.
+    fn geometry(&self, input: (usize, usize)) -> Geometry2D {
+        Geometry2D::new(
+            input,
+            self.kernel_size,
+            self.stride,
+            self.dilation,
+            self.padding,
+        )
+    }
+
+    /// Calcula os gradientes do kernel e do viés sem atualizá-los e retorna o erro da entrada.
+    fn compute_gradients(&mut self, output_error: &ArrayD<f32>) -> ArrayD<f32> {
+        let cache = self
+            .cache
+            .take()
+            .expect("Conv2D::backward chamado antes de forward");
+        let delta = output_error * &self.activation_type.derivative_array(&cache.z);
+        let kernel =
+            WeightNormalization::normalize(self.normalization.as_ref(), &self.kernel.value);
+        let input_error = convolve_backward(
+            &channels_last(&delta),
+            &cache,
+            &kernel,
+            &mut self.kernel.grad,
+            self.bias.as_mut(),
+            self.in_channels,
+            self.groups,
+            self.trainable,
+        );
+        self.normalize_gradients();
+        input_error.into_dyn()
+    }
+
+    fn parameters(&self) -> Vec<&Parameter> {
+        std::iter::once(&self.kernel)
+            .chain(&self.bias)
+            .chain(self.normalization.iter().flat_map(|n| n.parameters()))
+            .collect()
+    }
+
+    fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        std::iter::once(&mut self.kernel)
+            .chain(&mut self.bias)
+            .chain(
+                self.normalization
+                    .iter_mut()
+                    .flat_map(|n| n.parameters_mut()),
+            )
+            .collect()
+    }
+}
+
+impl Layer for Conv2D {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let input = input
+            .view()
+            .into_dimensionality::<Ix4>()
+            .expect("Conv2D espera uma entrada (N, C, H, W)");
+        let (n, c, h, w) = input.dim();
+        assert_eq!(
+            c, self.in_channels,
+            "Conv2D espera {} canais de entrada, recebeu {}",
+            self.in_channels, c
+        );
+
+        let geometry = self.geometry((h, w));
+        let kernel =
+            WeightNormalization::normalize(self.normalization.as_ref(), &self.kernel.value);
+        let (cols, z) = convolve(&input, &geometry, &kernel, self.bias.as_ref(), self.groups);
+        let (oh, ow) = geometry.output;
+        let z = channels_first(z, n, oh, ow);
+        let output = self.activation_type.activate_array(&z);
+
+        self.cache = Some(ConvCache {
+            cols,
+            geometry,
+            batch: n,
+            z,
+        });
+        output
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let input_error = self.compute_gradients(output_error);
+        if self.trainable {
+            optimizer.update_parameters(&mut self.parameters_mut());
+            self.after_update();
+        }
+        input_error
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        assert_eq!(
+            input_shape.len(),
+            4,
+            "Conv2D espera uma entrada (N, C, H, W)"
+        );
+        let geometry = self.geometry((input_shape[2], input_shape[3]));
+        vec![
+            input_shape[0],
+            self.out_channels,
+            geometry.output.0,
+            geometry.output.1,
+        ]
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&self.parameters())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut self.parameters_mut(), weights);
+        if let Some(normalization) = &mut self.normalization {
+            normalization.reset(&self.kernel.value);
+        }
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&self.parameters())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut self.parameters_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.parameters().iter().map(|param| param.len()).sum()
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
+
+/// Modo de preenchimento das bordas em convoluções 1D.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Padding1D {
+    /// Sem preenchimento.
+    Valid,
+    /// Preenchimento com zeros para que a saída tenha `ceil(entrada / stride)` posições.
+    Same,
+    /// Preenchimento apenas à esquerda com `dilation * (kernel - 1)` zeros, de modo que a saída
+    /// no instante `t` dependa somente das entradas até `t` (convolução causal, como em TCNs).
+    Causal,
+    /// Preenchimento simétrico explícito com zeros.
+    Explicit(usize),
+}
+
+/// Camada convolucional 1D sobre sequências no formato `(lote, canais, tempo)`.
+///
+/// Compartilha o cálculo via im2col com `Conv2D`, tratando a sequência como uma imagem de
+/// altura 1.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Conv1D {
+    /// Número de canais de entrada.
+    pub in_channels: usize,
+    /// Número de canais de saída (filtros).
+    pub out_channels: usize,
+    /// Tamanho do kernel.
+    pub kernel_size: usize,
+    /// Passo da convolução.
+    pub stride: usize,
+    /// Modo de preenchimento das bordas.
+    pub padding: Padding1D,
+    /// Dilatação do kernel.
+    pub dilation: usize,
+    /// Kernel de forma `(out_channels, in_channels, kernel_size)`.
+    pub kernel: Parameter,
+    /// Vetor de vieses, um por canal de saída (opcional).
+    pub bias: Option<Parameter>,
+    /// Tipo de função de ativação usada pela camada.
+    pub activation_type: ActivationType,
+    /// Restrição aplicada ao kernel após cada atualização (opcional).
+    pub constraint: Option<WeightConstraint>,
+    /// Reparametrização do kernel (opcional). Com ela, `kernel` guarda os pesos antes da
+    /// normalização.
+    pub normalization: Option<WeightNormalization>,
+    trainable: bool,
+
+    #[serde(skip)]
+    cache: Option<ConvCache>,
+}
+
+impl Conv1D {
+    /// Cria uma nova camada convolucional 1D com stride 1, sem preenchimento, sem dilatação e com viés.
+    ///
+    /// # Argumentos
+    ///
+    /// * `in_channels` - Número de canais de entrada.
+    /// * `out_channels` - Número de canais de saída.
+    /// * `kernel_size` - Tamanho do kernel.
+    /// * `activation_type` - Tipo de função de ativação a ser usada.
+    pub fn new(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: usize,
+        activation_type: ActivationType,
+    ) -> Self {
+        let kernel = glorot_uniform(
+            &[out_channels, in_channels, kernel_size],
+            in_channels * kernel_size,
+            out_channels * kernel_size,
+        );
+        Conv1D {
+            in_channels,
+            out_channels,
+            kernel_size,
+            stride: 1,
+            padding: Padding1D::Valid,
+            dilation: 1,
+            kernel: Parameter::new(kernel, true),
+            bias: Some(Parameter::new(ArrayD::zeros(vec![out_channels]), false)),
+            activation_type,
+            constraint: None,
+            normalization: None,
+            trainable: true,
+            cache: None,
+        }
+    }
+
+    /// Define o passo da convolução.
+    pub fn with_stride(mut self, stride: usize) -> Self {
+        assert!(stride > 0, "o stride deve ser positivo");
+        self.stride = stride;
+        self
+    }
+
+    /// Define o modo de preenchimento das bordas.
+    pub fn with_padding(mut self, padding: Padding1D) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Define a dilatação do kernel.
+    pub fn with_dilation(mut self, dilation: usize) -> Self {
+        assert!(dilation > 0, "a dilatação deve ser positiva");
+        self.dilation = dilation;
+        self
+    }
+
+    /// Ativa ou remove o viés.
+    pub fn with_bias(mut self, use_bias: bool) -> Self {
+        self.bias = use_bias.then(|| Parameter::new(ArrayD::zeros(vec![self.out_channels]), false));
+        self
+    }
+
+    /// Geometria da convolução para uma sequência de tamanho `len`.
+    fn geometry(&self, len: usize) -> Geometry2D {
+        Geometry2D::sequence(
+            len,
+            self.kernel_size,
+            self.stride,
+            self.dilation,
+            self.padding,
+        )
+    }
+
+    fn parameters(&self) -> Vec<&Parameter> {
+        std::iter::once(&self.kernel)
+            .chain(&self.bias)
+            .chain(self.normalization.iter().flat_map(|n| n.parameters()))
+            .collect()
+    }
+
+    fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        std::iter::once(&mut self.kernel)
+            .chain(&mut self.bias)
+            .chain(
+                self.normalization
+                    .iter_mut()
+                    .flat_map(|n| n.parameters_mut()),
+            )
+            .collect()
+    }
+}
+
+impl Layer for Conv1D {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let input = input
+            .view()
+            .into_dimensionality::<Ix3>()
+            .expect("Conv1D espera uma entrada (N, C, T)");
+        let (n, c, len) = input.dim();
+        assert_eq!(
+            c, self.in_channels,
+            "Conv1D espera {} canais de entrada, recebeu {}",
+            self.in_channels, c
+        );
+
+        let geometry = self.geometry(len);
+        let input = input.insert_axis(Axis(2));
+        let kernel =
+            WeightNormalization::normalize(self.normalization.as_ref(), &self.kernel.value);
+        let (cols, z) = convolve(&input, &geometry, &kernel, self.bias.as_ref(), 1);
+        let out_len = geometry.output.1;
+        let z = channels_first(z, n, 1, out_len)
+            .into_shape(vec![n, self.out_channels, out_len])
+            .unwrap();
+        let output = self.activation_type.activate_array(&z);
+
+        self.cache = Some(ConvCache {
+            cols,
+            geometry,
+            batch: n,
+            z,
+        });
+        output
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let cache = self
+            .cache
+            .take()
+            .expect("Conv1D::backward chamado antes de forward");
+        let delta = output_error * &self.activation_type.derivative_array(&cache.z);
+        let delta = delta.insert_axis(Axis(2));
+        let kernel =
+            WeightNormalization::normalize(self.normalization.as_ref(), &self.kernel.value);
+        let input_error = convolve_backward(
+            &channels_last(&delta),
+            &cache,
+            &kernel,
+            &mut self.kernel.grad,
+            self.bias.as_mut(),
+            self.in_channels,
+            1,
+            self.trainable,
+        );
+        self.normalize_gradients();
+
+        if self.trainable {
+            optimizer.update_parameters(&mut self.parameters_mut());
+            self.after_update();
+        }
+
+        input_error.remove_axis(Axis(2)).into_dyn()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        assert_eq!(input_shape.len(), 3, "Conv1D espera uma entrada (N, C, T)");
+        let geometry = self.geometry(input_shape[2]);
+        vec![input_shape[0], self.out_channels, geometry.output.1]
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&self.parameters())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut self.parameters_mut(), weights);
+        if let Some(normalization) = &mut self.normalization {
+            normalization.reset(&self.kernel.value);
+        }
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&self.parameters())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut self.parameters_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.parameters().iter().map(|param| param.len()).sum()
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
+
+/// Camada de convolução transposta 2D sobre entradas NCHW, usada para aumentar a resolução
+/// em decodificadores.
+///
+/// É a operação adjunta de uma `Conv2D` com o mesmo kernel, stride, dilatação e preenchimento:
+/// cada posição da entrada espalha o kernel sobre a saída (col2im), e a passagem backward
+/// recolhe as janelas da saída (im2col).
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConvTranspose2D {
+    /// Número de canais de entrada.
+    pub in_channels: usize,
+    /// Número de canais de saída.
+    pub out_channels: usize,
+    /// Tamanho do kernel `(altura, largura)`.
+    pub kernel_size: (usize, usize),
+    /// Passo da convolução `(vertical, horizontal)`; a resolução cresce por esse fator.
+    pub stride: (usize, usize),
+    /// Preenchimento removido das bordas da saída. `Padding::Same` produz `entrada * stride`.
+    pub padding: Padding,
+    /// Linhas e colunas extras no fim da saída, para desfazer o arredondamento de uma `Conv2D` com stride.
+    pub output_padding: (usize, usize),
+    /// Dilatação do kernel `(vertical, horizontal)`.
+    pub dilation: (usize, usize),
+    /// Kernel de forma `(in_channels, out_channels, altura, largura)`.
+    pub kernel: Parameter,
+    /// Vetor de vieses, um por canal de saída (opcional).
+    pub bias: Option<Parameter>,
+    /// Tipo de função de ativação usada pela camada.
+    pub activation_type: ActivationType,
+    /// Restrição aplicada ao kernel após cada atualização (opcional).
+    pub constraint: Option<WeightConstraint>,
+    /// Reparametrização do kernel (opcional). Com ela, `kernel` guarda os pesos antes da
+    /// normalização.
+    pub normalization: Option<WeightNormalization>,
+    trainable: bool,
+
+    #[serde(skip)]
+    cache: Option<ConvCache>,
+}
+
+impl ConvTranspose2D {
+    /// Cria uma nova convolução transposta com stride 1, sem preenchimento, sem dilatação e com viés.
+    ///
+    /// # Argumentos
+    ///
+    /// * `in_channels` - Número de canais de entrada.
+    /// * `out_channels` - Número de canais de saída.
+    /// * `kernel_size` - Tamanho do kernel `(altura, largura)`.
+    /// * `activation_type` - Tipo de função de ativação a ser usada.
+    pub fn new(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: (usize, usize),
+        activation_type: ActivationType,
+    ) -> Self {
+        let (kh, kw) = kernel_size;
+        let kernel = glorot_uniform(
+            &[in_channels, out_channels, kh, kw],
+            in_channels * kh * kw,
+            out_channels * kh * kw,
+        );
+        ConvTranspose2D {
+            in_channels,
+            out_channels,
+            kernel_size,
+            stride: (1, 1),
+            padding: Padding::Valid,
+            output_padding: (0, 0),
+            dilation: (1, 1),
+            kernel: Parameter::new(kernel, true),
+            bias: Some(Parameter::new(ArrayD::zeros(vec![out_channels]), false)),
+            activation_type,
+            constraint: None,
+            normalization: None,
+            trainable: true,
+            cache: None,
+        }
+    }
+
+    /// Define o passo da convolução.
+    pub fn with_stride(mut self, stride: (usize, usize)) -> Self {
+        assert!(stride.0 > 0 && stride.1 > 0, "o stride deve ser positivo");
+        self.stride = stride;
+        self
+    }
+
+    /// Define o preenchimento removido das bordas da saída.
+    pub fn with_padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Define as linhas e colunas extras no fim da saída (devem ser menores que o stride).
+    /// Vale para todos os modos de preenchimento; com `Padding::Same`, a saída tem
+    /// `entrada * stride + output_padding` posições.
+    pub fn with_output_padding(mut self, output_padding: (usize, usize)) -> Self {
+        self.output_padding = output_padding;
+        self
+    }
+
+    /// Define a dilatação do kernel.
+    pub fn with_dilation(mut self, dilation: (usize, usize)) -> Self {
+        assert!(
+            dilation.0 > 0 && dilation.1 > 0,
+            "a dilatação deve ser positiva"
+        );
+        self.dilation = dilation;
+        self
+    }
+
+    /// Ativa ou remove o viés.
+    pub fn with_bias(mut self, use_bias: bool) -> Self {
+        self.bias = use_bias.then(|| Parameter::new(ArrayD::zeros(vec![self.out_channels]), false));
+        self
+    }
+
+    /// Geometria da convolução adjunta: sua entrada é a saída desta camada e vice-versa.
+    fn geometry(&self, input: (usize, usize)) -> Geometry2D {
+        let axis = |len: usize, k: usize, s: usize, d: usize, pad: Option<usize>, extra: usize| {
+            let span = d * (k - 1) + 1;
+            let (before, after) = match pad {
+                Some(pad) => (pad, pad),
+                None => {
+                    let total = span.saturating_sub(s);
+                    (total / 2, total - total / 2)
+                }
+            };
+            let full = (len - 1) * s + span;
+            assert!(extra < s, "output_padding deve ser menor que o stride");
+            let output = match pad {
+                Some(_) => (full + extra).checked_sub(before + after),
+                None => Some(len * s + extra),
+            }
+            .expect("preenchimento maior que a saída da convolução transposta");
+            (output, before, after)
+        };
+        let (pad_h, pad_w) = match self.padding {
+            Padding::Valid => (Some(0), Some(0)),
+            Padding::Same => (None, None),
+            Padding::Explicit(h, w) => (Some(h), Some(w)),
+        };
+        let (oh, top, bottom) = axis(
+            input.0,
+            self.kernel_size.0,
+            self.stride.0,
+            self.dilation.0,
+            pad_h,
+            self.output_padding.0,
+        );
+        let (ow, left, right) = axis(
+            input.1,
+            self.kernel_size.1,
+            self.stride.1,
+            self.dilation.1,
+            pad_w,
+            self.output_padding.1,
+        );
+        // A saída da adjunta é a entrada desta camada, mesmo quando as posições extras de
+        // `output_padding` ficam além do alcance do kernel e não seriam contadas a partir do
+        // tamanho preenchido
+        Geometry2D {
+            input: (oh, ow),
+            output: input,
+            kernel: self.kernel_size,
+            stride: self.stride,
+            dilation: self.dilation,
+            padding: (top, bottom, left, right),
+        }
+    }
+
+    fn parameters(&self) -> Vec<&Parameter> {
+        std::iter::once(&self.kernel)
+            .chain(&self.bias)
+            .chain(self.normalization.iter().flat_map(|n| n.parameters()))
+            .collect()
+    }
+
+    fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        std::iter::once(&mut self.kernel)
+            .chain(&mut self.bias)
+            .chain(
+                self.normalization
+                    .iter_mut()
+                    .flat_map(|n| n.parameters_mut()),
+            )
+            .collect()
+    }
+}
+
+impl Layer for ConvTranspose2D {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        assert_eq!(
+            input.ndim(),
+            4,
+            "ConvTranspose2D espera uma entrada (N, C, H, W)"
+        );
+        let (n, c, h, w) = (
+            input.shape()[0],
+            input.shape()[1],
+            input.shape()[2],
+            input.shape()[3],
+        );
+        assert_eq!(
+            c, self.in_channels,
+            "ConvTranspose2D espera {} canais de entrada, recebeu {}",
+            self.in_channels, c
+        );
+
+        let geometry = self.geometry((h, w));
+        let x = channels_last(input);
+        let kernel =
+            WeightNormalization::normalize(self.normalization.as_ref(), &self.kernel.value);
+        let cols = x.dot(&kernel_matrix(&kernel));
+        let mut z = col2im(&cols, &geometry, n, self.out_channels);
+        if let Some(bias) = &self.bias {
+            let bias = bias
+                .value
+                .view()
+                .into_shape((self.out_channels, 1, 1))
+                .unwrap();
+            z += &bias;
+        }
+        let z = z.into_dyn();
+        let output = self.activation_type.activate_array(&z);
+
+        self.cache = Some(ConvCache {
+            cols: x,
+            geometry,
+            batch: n,
+            z,
+        });
+        output
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let cache = self
+            .cache
+            .take()
+            .expect("ConvTranspose2D::backward chamado antes de forward");
+        let delta = output_error * &self.activation_type.derivative_array(&cache.z);
+        let delta = delta.into_dimensionality::<Ix4>().unwrap();
+        let delta_cols = im2col(&delta.view(), &cache.geometry);
+
+        if self.trainable {
+            self.kernel.grad = cache
+                .cols
+                .t()
+                .dot(&delta_cols)
+                .into_shape(self.kernel.value.raw_dim())
+                .unwrap();
+            if let Some(bias) = &mut self.bias {
+                bias.grad = delta
+                    .sum_axis(Axis(0))
+                    .sum_axis(Axis(1))
+                    .sum_axis(Axis(1))
+                    .into_dyn();
+            }
+        }
+
+        self.normalize_gradients();
+
+        let kernel =
+            WeightNormalization::normalize(self.normalization.as_ref(), &self.kernel.value);
+        let input_error = delta_cols.dot(&kernel_matrix(&kernel).t());
+        let (h, w) = cache.geometry.output;
+
+        if self.trainable {
+            optimizer.update_parameters(&mut self.parameters_mut());
+            self.after_update();
+        }
+
+        channels_first(input_error, cache.batch, h, w)
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        assert_eq!(
+            input_shape.len(),
+            4,
+            "ConvTranspose2D espera uma entrada (N, C, H, W)"
+        );
+        let geometry = self.geometry((input_shape[2], input_shape[3]));
+        vec![
+            input_shape[0],
+            self.out_channels,
+            geometry.input.0,
+            geometry.input.1,
+        ]
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&self.parameters())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut self.parameters_mut(), weights);
+        if let Some(normalization) = &mut self.normalization {
+            normalization.reset(&self.kernel.value);
+        }
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&self.parameters())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut self.parameters_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.parameters().iter().map(|param| param.len()).sum()
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
+
+/// Convolução separável em profundidade (depthwise-separable): uma convolução depthwise,
+/// que filtra cada canal separadamente, seguida de uma convolução pontual 1x1 que combina
+/// os canais.
+///
+/// Comparada a uma `Conv2D` com o mesmo kernel, usa cerca de `1 / out_channels + 1 / (kh * kw)`
+/// dos parâmetros e das operações.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SeparableConv2D {
+    /// Convolução depthwise, linear e sem viés.
+    pub depthwise: Conv2D,
+    /// Convolução pontual 1x1 com a ativação e o viés da camada.
+    pub pointwise: Conv2D,
+}
+
+impl SeparableConv2D {
+    /// Cria uma nova convolução separável com multiplicador de profundidade 1, stride 1,
+    /// sem preenchimento, sem dilatação e com viés.
+    ///
+    /// # Argumentos
+    ///
+    /// * `in_channels` - Número de canais de entrada.
+    /// * `out_channels` - Número de canais de saída.
+    /// * `kernel_size` - Tamanho do kernel depthwise `(altura, largura)`.
+    /// * `activation_type` - Tipo de função de ativação aplicada após a convolução pontual.
+    pub fn new(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: (usize, usize),
+        activation_type: ActivationType,
+    ) -> Self {
+        SeparableConv2D {
+            depthwise: Conv2D::depthwise(in_channels, 1, kernel_size, ActivationType::Linear)
+                .with_bias(false),
+            pointwise: Conv2D::new(in_channels, out_channels, (1, 1), activation_type),
+        }
+    }
+
+    /// Define quantos canais a convolução depthwise produz para cada canal de entrada.
+    pub fn with_depth_multiplier(mut self, depth_multiplier: usize) -> Self {
+        let depthwise = &self.depthwise;
+        let pointwise = &self.pointwise;
+        let in_channels = depthwise.in_channels;
+        self.depthwise = Conv2D::depthwise(
+            in_channels,
+            depth_multiplier,
+            depthwise.kernel_size,
+            ActivationType::Linear,
+        )
+        .with_stride(depthwise.stride)
+        .with_padding(depthwise.padding)
+        .with_dilation(depthwise.dilation)
+        .with_bias(false);
+        self.pointwise = Conv2D::new(
+            in_channels * depth_multiplier,
+            pointwise.out_channels,
+            (1, 1),
+            pointwise.activation_type.clone(),
+        )
+        .with_bias(pointwise.bias.is_some());
+        self
+    }
+
+    /// Define o passo da convolução depthwise.
+    pub fn with_stride(mut self, stride: (usize, usize)) -> Self {
+        self.depthwise = self.depthwise.with_stride(stride);
+        self
+    }
+
+    /// Define o modo de preenchimento da convolução depthwise.
+    pub fn with_padding(mut self, padding: Padding) -> Self {
+        self.depthwise = self.depthwise.with_padding(padding);
+        self
+    }
+
+    /// Define a dilatação do kernel depthwise.
+    pub fn with_dilation(mut self, dilation: (usize, usize)) -> Self {
+        self.depthwise = self.depthwise.with_dilation(dilation);
+        self
+    }
+
+    /// Ativa ou remove o viés da convolução pontual.
+    pub fn with_bias(mut self, use_bias: bool) -> Self {
+        self.pointwise = self.pointwise.with_bias(use_bias);
+        self
+    }
+
+    fn parameters(&self) -> Vec<&Parameter> {
+        let mut parameters = self.depthwise.parameters();
+        parameters.extend(self.pointwise.parameters());
+        parameters
+    }
+
+    fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        let mut parameters = self.depthwise.parameters_mut();
+        parameters.extend(self.pointwise.parameters_mut());
+        parameters
+    }
+
+    /// Parâmetros das convoluções não congeladas.
+    fn trainable_parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        let mut parameters = Vec::new();
+        if self.depthwise.trainable {
+            parameters.extend(self.depthwise.parameters_mut());
+        }
+        if self.pointwise.trainable {
+            parameters.extend(self.pointwise.parameters_mut());
+        }
+        parameters
+    }
+}
+
+impl Layer for SeparableConv2D {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let hidden = self.depthwise.forward(input);
+        self.pointwise.forward(&hidden)
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        // Os gradientes das duas convoluções são calculados antes de uma única atualização,
+        // para que a camada conte como um passo do otimizador.
+        let hidden_error = self.pointwise.compute_gradients(output_error);
+        let input_error = self.depthwise.compute_gradients(&hidden_error);
+        if self.trainable() {
+            optimizer.update_parameters(&mut self.trainable_parameters_mut());
+            for conv in [&mut self.depthwise, &mut self.pointwise] {
+                if conv.trainable {
+                    conv.after_update();
+                }
+            }
+        }
+        input_error
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        self.pointwise
+            .output_shape(&self.depthwise.output_shape(input_shape))
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&self.parameters())
+    }
+
+    /// Repassa os pesos às duas convoluções, que recalculam suas normalizações.
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        assert_eq!(
+            weights.len(),
+            self.parameters().len(),
+            "número de tensores de pesos incompatível com a camada"
+        );
+        distribute_weights(vec![&mut self.depthwise, &mut self.pointwise], weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&self.parameters())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut self.parameters_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.depthwise.param_count() + self.pointwise.param_count()
+    }
+
+    fn trainable(&self) -> bool {
+        self.depthwise.trainable || self.pointwise.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.depthwise.trainable = trainable;
+        self.pointwise.trainable = trainable;
+    }
+}
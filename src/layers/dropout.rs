@@ -0,0 +1,289 @@
+// bran/src/layers/dropout.rs
+
+use super::Layer;
+use crate::optimizer::Optimizer;
+use ndarray::{ArrayD, IxDyn};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Gerador de números aleatórios das camadas de dropout. Com semente, as máscaras são
+/// reproduzíveis; sem semente, o gerador é inicializado a partir da entropia do sistema.
+///
+/// Com semente, cada máscara usa um gerador derivado da semente e do número de máscaras já
+/// geradas. Esse contador é serializado com a camada, de modo que uma rede restaurada de um
+/// checkpoint continua a sequência de máscaras em vez de repetir a da primeira época.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub(super) struct MaskRng {
+    seed: Option<u64>,
+    draws: u64,
+    #[serde(skip)]
+    rng: Option<StdRng>,
+}
+
+impl MaskRng {
+    /// Gerador reproduzível a partir de `seed`.
+    pub(super) fn seeded(seed: u64) -> Self {
+        MaskRng {
+            seed: Some(seed),
+            draws: 0,
+            rng: None,
+        }
+    }
+
+    /// Máscara de Bernoulli com probabilidade `keep` de manter cada elemento (1.0) ou descartá-lo (0.0).
+    pub(super) fn mask(&mut self, shape: &[usize], keep: f32) -> ArrayD<f32> {
+        let draw = self.draws;
+        self.draws += 1;
+        let mut seeded;
+        let rng = match self.seed {
+            Some(seed) => {
+                let mut key = [0u8; 32];
+                key[..8].copy_from_slice(&seed.to_le_bytes());
+                key[8..16].copy_from_slice(&draw.to_le_bytes());
+                seeded = StdRng::from_seed(key);
+                &mut seeded
+            }
+            None => self.rng.get_or_insert_with(StdRng::from_entropy),
+        };
+        ArrayD::from_shape_simple_fn(IxDyn(shape), || (rng.gen::<f32>() < keep) as u8 as f32)
+    }
+}
+
+fn check_rate(rate: f32) {
+    assert!(
+        (0.0..1.0).contains(&rate),
+        "a taxa de dropout deve estar no intervalo [0, 1)"
+    );
+}
+
+/// Dropout invertido: durante o treinamento, zera cada elemento com probabilidade `rate`
+/// e multiplica os restantes por `1 / (1 - rate)`, mantendo a esperança da saída. Em modo
+/// de inferência, é a identidade.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Dropout {
+    /// Probabilidade de descartar cada elemento.
+    pub rate: f32,
+    rng: MaskRng,
+
+    #[serde(skip)]
+    training: bool,
+    #[serde(skip)]
+    mask: Option<ArrayD<f32>>,
+}
+
+impl Dropout {
+    /// Cria uma nova camada de dropout.
+    ///
+    /// # Argumentos
+    ///
+    /// * `rate` - Probabilidade de descartar cada elemento, em `[0, 1)`.
+    pub fn new(rate: f32) -> Self {
+        check_rate(rate);
+        Dropout {
+            rate,
+            rng: MaskRng::default(),
+            training: false,
+            mask: None,
+        }
+    }
+
+    /// Define a semente do gerador das máscaras, tornando-as reproduzíveis.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = MaskRng::seeded(seed);
+        self
+    }
+}
+
+impl Layer for Dropout {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        if !self.training || self.rate == 0.0 {
+            self.mask = None;
+            return input.clone();
+        }
+        let keep = 1.0 - self.rate;
+        let mask = self.rng.mask(input.shape(), keep) / keep;
+        let output = input * &mask;
+        self.mask = Some(mask);
+        output
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        _optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        match self.mask.take() {
+            Some(mask) => output_error * &mask,
+            None => output_error.clone(),
+        }
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        input_shape.to_vec()
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+}
+
+/// Dropout para redes com ativação SELU: os elementos descartados recebem o valor de
+/// saturação negativa da SELU, e uma transformação afim preserva a média e a variância
+/// da entrada. Em modo de inferência, é a identidade.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AlphaDropout {
+    /// Probabilidade de descartar cada elemento.
+    pub rate: f32,
+    rng: MaskRng,
+
+    #[serde(skip)]
+    training: bool,
+    #[serde(skip)]
+    mask: Option<ArrayD<f32>>,
+}
+
+/// Valor de saturação negativa da SELU: `-lambda * alpha`.
+const SELU_SATURATION: f32 = -1.050_700_9 * 1.673_263_2;
+
+impl AlphaDropout {
+    /// Cria uma nova camada de alpha dropout.
+    ///
+    /// # Argumentos
+    ///
+    /// * `rate` - Probabilidade de descartar cada elemento, em `[0, 1)`.
+    pub fn new(rate: f32) -> Self {
+        check_rate(rate);
+        AlphaDropout {
+            rate,
+            rng: MaskRng::default(),
+            training: false,
+            mask: None,
+        }
+    }
+
+    /// Define a semente do gerador das máscaras, tornando-as reproduzíveis.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = MaskRng::seeded(seed);
+        self
+    }
+
+    /// Coeficientes `(a, b)` da transformação afim que preserva média zero e variância um.
+    fn affine(&self) -> (f32, f32) {
+        let keep = 1.0 - self.rate;
+        let a = (keep * (1.0 + self.rate * SELU_SATURATION.powi(2))).powf(-0.5);
+        let b = -a * SELU_SATURATION * self.rate;
+        (a, b)
+    }
+}
+
+impl Layer for AlphaDropout {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        if !self.training || self.rate == 0.0 {
+            self.mask = None;
+            return input.clone();
+        }
+        let (a, b) = self.affine();
+        let mask = self.rng.mask(input.shape(), 1.0 - self.rate);
+        let mut output = input * &mask + mask.mapv(|m| (1.0 - m) * SELU_SATURATION);
+        output.mapv_inplace(|x| a * x + b);
+        self.mask = Some(mask);
+        output
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        _optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        match self.mask.take() {
+            Some(mask) => output_error * &mask * self.affine().0,
+            None => output_error.clone(),
+        }
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        input_shape.to_vec()
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+}
+
+/// Dropout espacial para mapas de características `(N, C, H, W)`: descarta canais inteiros,
+/// com escala invertida. É mais eficaz que o dropout comum quando pixels vizinhos são
+/// fortemente correlacionados. Em modo de inferência, é a identidade.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpatialDropout2D {
+    /// Probabilidade de descartar cada canal.
+    pub rate: f32,
+    rng: MaskRng,
+
+    #[serde(skip)]
+    training: bool,
+    #[serde(skip)]
+    mask: Option<ArrayD<f32>>,
+}
+
+impl SpatialDropout2D {
+    /// Cria uma nova camada de dropout espacial.
+    ///
+    /// # Argumentos
+    ///
+    /// * `rate` - Probabilidade de descartar cada canal, em `[0, 1)`.
+    pub fn new(rate: f32) -> Self {
+        check_rate(rate);
+        SpatialDropout2D {
+            rate,
+            rng: MaskRng::default(),
+            training: false,
+            mask: None,
+        }
+    }
+
+    /// Define a semente do gerador das máscaras, tornando-as reproduzíveis.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = MaskRng::seeded(seed);
+        self
+    }
+}
+
+impl Layer for SpatialDropout2D {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        assert_eq!(
+            input.ndim(),
+            4,
+            "SpatialDropout2D espera uma entrada (N, C, H, W)"
+        );
+        if !self.training || self.rate == 0.0 {
+            self.mask = None;
+            return input.clone();
+        }
+        let keep = 1.0 - self.rate;
+        let shape = input.shape();
+        let mask = self.rng.mask(&[shape[0], shape[1], 1, 1], keep) / keep;
+        let output = input * &mask;
+        self.mask = Some(mask);
+        output
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        _optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        match self.mask.take() {
+            Some(mask) => output_error * &mask,
+            None => output_error.clone(),
+        }
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        input_shape.to_vec()
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+}
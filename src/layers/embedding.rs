@@ -0,0 +1,314 @@
+// bran/src/layers/embedding.rs
+
+use super::{
+    parameter_moments, parameter_values, set_parameter_moments, set_parameter_values, Layer,
+    TiedWeights,
+};
+use crate::optimizer::{Optimizer, Parameter};
+use ndarray::{Array1, Array2, ArrayD, ArrayView2, Axis, Ix2};
+use rand::distributions::{Distribution, Uniform};
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Valores guardados na passagem forward para uso na passagem backward.
+#[derive(Clone)]
+struct EmbeddingCache {
+    indices: Vec<usize>,
+    input_shape: Vec<usize>,
+}
+
+/// Camada de embedding: mapeia índices inteiros (categorias ou tokens) para vetores treináveis.
+///
+/// A entrada contém índices armazenados como `f32`, com qualquer forma `(N, ...)`, e a saída
+/// tem forma `(N, ..., embedding_dim)`. Na passagem backward, apenas as linhas usadas no lote
+/// são atualizadas (`Optimizer::update_rows`), e o erro devolvido à camada anterior é zero,
+/// pois os índices não são diferenciáveis.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Embedding {
+    /// Número de categorias (tamanho do vocabulário).
+    pub num_embeddings: usize,
+    /// Dimensão de cada vetor.
+    pub embedding_dim: usize,
+    /// Matriz de embeddings de forma `(num_embeddings, embedding_dim)`.
+    pub embeddings: Parameter,
+    /// Índice de preenchimento: seu vetor é zero e nunca é atualizado.
+    pub padding_idx: Option<usize>,
+    /// Norma máxima: vetores consultados com norma maior são reescalados para ela.
+    pub max_norm: Option<f32>,
+    trainable: bool,
+
+    #[serde(skip)]
+    cache: Option<EmbeddingCache>,
+    /// Gradiente de cada linha calculado por `compute_gradients` e ainda não aplicado.
+    #[serde(skip)]
+    row_gradients: Option<BTreeMap<usize, Array1<f32>>>,
+}
+
+impl Embedding {
+    /// Cria uma nova camada de embedding inicializada com valores uniformes em `[-0.05, 0.05]`.
+    ///
+    /// # Argumentos
+    ///
+    /// * `num_embeddings` - Número de categorias.
+    /// * `embedding_dim` - Dimensão de cada vetor.
+    pub fn new(num_embeddings: usize, embedding_dim: usize) -> Self {
+        let dist = Uniform::new_inclusive(-0.05, 0.05);
+        let mut rng = thread_rng();
+        let embeddings = ArrayD::from_shape_simple_fn(vec![num_embeddings, embedding_dim], || {
+            dist.sample(&mut rng)
+        });
+        Embedding {
+            num_embeddings,
+            embedding_dim,
+            embeddings: Parameter::new(embeddings, true),
+            padding_idx: None,
+            max_norm: None,
+            trainable: true,
+            cache: None,
+            row_gradients: None,
+        }
+    }
+
+    /// Cria uma camada de embedding a partir de uma matriz pré-treinada
+    /// `(num_embeddings, embedding_dim)`, como vetores de palavras.
+    pub fn from_pretrained(embeddings: Array2<f32>) -> Self {
+        let (num_embeddings, embedding_dim) = embeddings.dim();
+        Embedding {
+            num_embeddings,
+            embedding_dim,
+            embeddings: Parameter::new(embeddings.into_dyn(), true),
+            padding_idx: None,
+            max_norm: None,
+            trainable: true,
+            cache: None,
+            row_gradients: None,
+        }
+    }
+
+    /// Substitui a matriz de embeddings por uma matriz pré-treinada de mesma forma.
+    ///
+    /// # Retorno
+    ///
+    /// Erro se a forma da matriz não for `(num_embeddings, embedding_dim)`.
+    pub fn load_pretrained(
+        &mut self,
+        embeddings: Array2<f32>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if embeddings.dim() != (self.num_embeddings, self.embedding_dim) {
+            return Err(format!(
+                "matriz pré-treinada {:?} incompatível com ({}, {})",
+                embeddings.dim(),
+                self.num_embeddings,
+                self.embedding_dim
+            )
+            .into());
+        }
+        self.embeddings.value = embeddings.into_dyn();
+        if let Some(padding_idx) = self.padding_idx {
+            self.embeddings
+                .value
+                .index_axis_mut(Axis(0), padding_idx)
+                .fill(0.0);
+        }
+        Ok(())
+    }
+
+    /// Define o índice de preenchimento, zerando seu vetor.
+    pub fn with_padding_idx(mut self, padding_idx: usize) -> Self {
+        assert!(
+            padding_idx < self.num_embeddings,
+            "padding_idx fora do vocabulário"
+        );
+        self.embeddings
+            .value
+            .index_axis_mut(Axis(0), padding_idx)
+            .fill(0.0);
+        self.padding_idx = Some(padding_idx);
+        self
+    }
+
+    /// Define a norma máxima dos vetores consultados.
+    pub fn with_max_norm(mut self, max_norm: f32) -> Self {
+        assert!(max_norm > 0.0, "a norma máxima deve ser positiva");
+        self.max_norm = Some(max_norm);
+        self
+    }
+
+    /// Converte a entrada em índices, verificando que são inteiros dentro do vocabulário.
+    fn indices(&self, input: &ArrayD<f32>) -> Vec<usize> {
+        input
+            .iter()
+            .map(|&x| {
+                assert!(
+                    x >= 0.0 && x.fract() == 0.0 && (x as usize) < self.num_embeddings,
+                    "índice de embedding inválido: {} (vocabulário de {})",
+                    x,
+                    self.num_embeddings
+                );
+                x as usize
+            })
+            .collect()
+    }
+}
+
+impl Layer for Embedding {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let indices = self.indices(input);
+
+        // Reescala, na própria matriz, os vetores consultados que excedem a norma máxima
+        if let Some(max_norm) = self.max_norm {
+            for &index in &indices {
+                let mut row = self.embeddings.value.index_axis_mut(Axis(0), index);
+                let norm = row.iter().map(|x| x * x).sum::<f32>().sqrt();
+                if norm > max_norm {
+                    row *= max_norm / (norm + 1e-7);
+                }
+            }
+        }
+
+        let rows = self.embeddings.value.select(Axis(0), &indices);
+        self.cache = Some(EmbeddingCache {
+            indices,
+            input_shape: input.shape().to_vec(),
+        });
+        rows.into_shape(self.output_shape(input.shape())).unwrap()
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let input_error = self.compute_gradients(output_error, false);
+        self.apply_gradients(optimizer);
+        input_error
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        let mut shape = input_shape.to_vec();
+        shape.push(self.embedding_dim);
+        shape
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&[&self.embeddings])
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut [&mut self.embeddings], weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&[&self.embeddings])
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut [&mut self.embeddings], moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.embeddings.len()
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
+
+impl TiedWeights for Embedding {
+    fn tied_weights(&self) -> Result<ArrayView2<'_, f32>, String> {
+        if self.max_norm.is_some() {
+            return Err("embeddings com norma máxima não podem compartilhar pesos".into());
+        }
+        Ok(self
+            .embeddings
+            .value
+            .view()
+            .into_dimensionality::<Ix2>()
+            .unwrap())
+    }
+
+    fn set_tied_weights(&mut self, weights: Array2<f32>) {
+        self.embeddings.value = weights.into_dyn();
+    }
+
+    fn compute_gradients(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        shared_weights_trainable: bool,
+    ) -> ArrayD<f32> {
+        let cache = self
+            .cache
+            .take()
+            .expect("Embedding::backward chamado antes de forward");
+        if !self.trainable && !shared_weights_trainable {
+            return ArrayD::zeros(cache.input_shape);
+        }
+        let errors = output_error
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((cache.indices.len(), self.embedding_dim))
+            .unwrap();
+
+        // Acumula o gradiente das ocorrências repetidas de cada índice
+        let mut grads: BTreeMap<usize, Array1<f32>> = BTreeMap::new();
+        for (&index, error) in cache.indices.iter().zip(errors.outer_iter()) {
+            if Some(index) == self.padding_idx {
+                continue;
+            }
+            *grads
+                .entry(index)
+                .or_insert_with(|| Array1::zeros(self.embedding_dim)) += &error;
+        }
+        self.row_gradients = Some(grads);
+
+        ArrayD::zeros(cache.input_shape)
+    }
+
+    /// O gradiente é devolvido como uma matriz completa, com zeros nas linhas não usadas.
+    fn take_weight_gradients(&mut self) -> Option<Array2<f32>> {
+        let grads = self.row_gradients.take()?;
+        let mut full = Array2::zeros((self.num_embeddings, self.embedding_dim));
+        for (index, grad) in grads {
+            full.row_mut(index).assign(&grad);
+        }
+        Some(full)
+    }
+
+    /// As linhas de `gradients` passam a fazer parte da atualização esparsa, exceto a do
+    /// índice de preenchimento.
+    fn add_weight_gradients(&mut self, gradients: ArrayView2<'_, f32>) {
+        let Some(grads) = &mut self.row_gradients else {
+            return;
+        };
+        for (index, gradient) in gradients.outer_iter().enumerate() {
+            if Some(index) == self.padding_idx {
+                continue;
+            }
+            *grads
+                .entry(index)
+                .or_insert_with(|| Array1::zeros(self.embedding_dim)) += &gradient;
+        }
+    }
+
+    /// Atualiza apenas as linhas com gradiente (`Optimizer::update_rows`).
+    fn apply_gradients(&mut self, optimizer: &mut dyn Optimizer) {
+        let Some(grads) = self.row_gradients.take() else {
+            return;
+        };
+        if grads.is_empty() {
+            return;
+        }
+        let rows: Vec<usize> = grads.keys().copied().collect();
+        let mut grad_rows = Array2::zeros((rows.len(), self.embedding_dim));
+        for (mut row, grad) in grad_rows.outer_iter_mut().zip(grads.values()) {
+            row.assign(grad);
+        }
+        optimizer.update_rows(&mut self.embeddings, &rows, &grad_rows.into_dyn());
+    }
+}
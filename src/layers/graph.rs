@@ -0,0 +1,876 @@
+// bran/src/layers/graph.rs
+
+use super::{
+    any_trainable, collect_moments, collect_weights, distribute_moments, distribute_weights, Layer,
+    LayerType,
+};
+use crate::loss::MultiOutputLoss;
+use crate::metrics::{Evaluation, Metric, MetricType};
+use crate::model::{split_validation, NeuralNetwork, TrainConfig, TrainingData, Validation};
+use crate::optimizer::Optimizer;
+use crate::visualization::TrainingStats;
+use ndarray::{concatenate, ArrayD, ArrayViewD, Axis, Slice};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Identificador de um nó de um `GraphModel`, retornado pelos métodos que criam nós.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeId(usize);
+
+/// Forma de combinar as saídas de vários nós.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Merge {
+    /// Soma elemento a elemento; todas as entradas devem ter a mesma forma.
+    Add,
+    /// Concatena as entradas ao longo do eixo indicado (por exemplo, `1` para as features de
+    /// `(N, F)` ou os canais de `(N, C, H, W)`).
+    Concat(usize),
+    /// Produto elemento a elemento; todas as entradas devem ter a mesma forma.
+    Multiply,
+}
+
+impl Merge {
+    fn forward(&self, inputs: &[&ArrayD<f32>]) -> ArrayD<f32> {
+        let shapes: Vec<&[usize]> = inputs.iter().map(|input| input.shape()).collect();
+        self.output_shape(&shapes);
+        match self {
+            Merge::Add => inputs[1..]
+                .iter()
+                .fold(inputs[0].clone(), |sum, input| sum + *input),
+            Merge::Multiply => inputs[1..]
+                .iter()
+                .fold(inputs[0].clone(), |product, input| product * *input),
+            Merge::Concat(axis) => {
+                let views: Vec<_> = inputs.iter().map(|input| input.view()).collect();
+                concatenate(Axis(*axis), &views).unwrap()
+            }
+        }
+    }
+
+    /// Erros de cada entrada a partir do erro da saída.
+    fn backward(&self, output_error: &ArrayD<f32>, inputs: &[&ArrayD<f32>]) -> Vec<ArrayD<f32>> {
+        match self {
+            Merge::Add => vec![output_error.clone(); inputs.len()],
+            Merge::Multiply => (0..inputs.len())
+                .map(|i| {
+                    inputs
+                        .iter()
+                        .enumerate()
+                        .filter(|(j, _)| *j != i)
+                        .fold(output_error.clone(), |error, (_, input)| error * *input)
+                })
+                .collect(),
+            Merge::Concat(axis) => {
+                let mut start = 0;
+                inputs
+                    .iter()
+                    .map(|input| {
+                        let end = start + input.len_of(Axis(*axis));
+                        let error = output_error
+                            .slice_axis(Axis(*axis), Slice::from(start..end))
+                            .to_owned();
+                        start = end;
+                        error
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn output_shape(&self, shapes: &[&[usize]]) -> Vec<usize> {
+        assert!(
+            !shapes.is_empty(),
+            "{:?} precisa de ao menos uma entrada",
+            self
+        );
+        let mut shape = shapes[0].to_vec();
+        match self {
+            Merge::Add | Merge::Multiply => {
+                for other in &shapes[1..] {
+                    assert_eq!(
+                        &shape[..],
+                        *other,
+                        "{:?} exige entradas com a mesma forma",
+                        self
+                    );
+                }
+            }
+            Merge::Concat(axis) => {
+                assert!(*axis < shape.len(), "eixo {} fora da entrada", axis);
+                for other in &shapes[1..] {
+                    assert!(
+                        other.len() == shape.len()
+                            && (0..shape.len()).all(|d| d == *axis || other[d] == shape[d]),
+                        "Concat exige entradas com a mesma forma fora do eixo {}",
+                        axis
+                    );
+                    shape[*axis] += other[*axis];
+                }
+            }
+        }
+        shape
+    }
+}
+
+/// Operação realizada por um nó.
+#[derive(Serialize, Deserialize, Clone)]
+#[allow(clippy::large_enum_variant)]
+enum Operation {
+    Input,
+    Layer(LayerType),
+    Merge(Merge),
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Node {
+    operation: Operation,
+    inputs: Vec<NodeId>,
+}
+
+/// Modelo em grafo: as saídas das camadas podem ser reutilizadas por vários nós e
+/// combinadas com `add`, `concat` e `multiply`, permitindo conexões residuais (ResNet),
+/// atalhos entre codificador e decodificador (U-Net) e portas multiplicativas.
+///
+/// Cada nó só pode usar nós já criados, de modo que a ordem de criação é uma ordem
+/// topológica do grafo: a passagem forward percorre os nós nessa ordem e a backward na
+/// ordem inversa, somando os erros que chegam a um nó usado por vários outros antes de
+/// propagá-lo.
+///
+/// Com uma única entrada e uma única saída, o modelo é uma `Layer` e pode ser adicionado a
+/// uma `NeuralNetwork`, inclusive como bloco repetido.
+///
+/// ```text
+/// let mut block = GraphModel::new();
+/// let x = block.input();
+/// let h = block.layer(DenseLayer::new(16, 16, ActivationType::ReLU), x);
+/// let h = block.layer(DenseLayer::new(16, 16, ActivationType::Linear), h);
+/// let y = block.add(&[x, h]);
+/// block.output(y);
+/// ```
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct GraphModel {
+    nodes: Vec<Node>,
+    inputs: Vec<NodeId>,
+    outputs: Vec<NodeId>,
+    input_names: Vec<String>,
+    output_names: Vec<String>,
+
+    /// Saída de cada nó na última passagem forward.
+    #[serde(skip)]
+    values: Vec<ArrayD<f32>>,
+}
+
+impl GraphModel {
+    /// Cria um grafo vazio.
+    pub fn new() -> Self {
+        GraphModel::default()
+    }
+
+    fn push(&mut self, operation: Operation, inputs: Vec<NodeId>) -> NodeId {
+        for input in &inputs {
+            assert!(
+                input.0 < self.nodes.len(),
+                "nó {:?} não pertence ao grafo",
+                input
+            );
+        }
+        self.nodes.push(Node { operation, inputs });
+        NodeId(self.nodes.len() - 1)
+    }
+
+    /// Cria uma nova entrada do grafo, chamada `input_<i>`. As entradas são alimentadas na
+    /// ordem de criação.
+    pub fn input(&mut self) -> NodeId {
+        let name = format!("input_{}", self.inputs.len());
+        self.named_input(&name)
+    }
+
+    /// Cria uma nova entrada do grafo com nome, usado por `forward_named` e `fit`.
+    pub fn named_input(&mut self, name: &str) -> NodeId {
+        assert!(
+            !self.input_names.iter().any(|input| input == name),
+            "entrada '{}' já existe",
+            name
+        );
+        let node = self.push(Operation::Input, Vec::new());
+        self.inputs.push(node);
+        self.input_names.push(name.to_string());
+        node
+    }
+
+    /// Aplica uma camada à saída de um nó.
+    ///
+    /// # Argumentos
+    ///
+    /// * `layer` - Camada a ser aplicada.
+    /// * `input` - Nó cuja saída alimenta a camada.
+    ///
+    /// # Retorno
+    ///
+    /// Retorna o nó com a saída da camada.
+    pub fn layer(&mut self, layer: impl Into<LayerType>, input: NodeId) -> NodeId {
+        self.push(Operation::Layer(layer.into()), vec![input])
+    }
+
+    /// Combina as saídas de vários nós.
+    ///
+    /// # Argumentos
+    ///
+    /// * `merge` - Forma de combinar as saídas.
+    /// * `inputs` - Nós combinados; um mesmo nó pode aparecer mais de uma vez.
+    pub fn merge(&mut self, merge: Merge, inputs: &[NodeId]) -> NodeId {
+        assert!(
+            !inputs.is_empty(),
+            "{:?} precisa de ao menos uma entrada",
+            merge
+        );
+        self.push(Operation::Merge(merge), inputs.to_vec())
+    }
+
+    /// Soma as saídas de vários nós. Atalho para `merge(Merge::Add, inputs)`.
+    pub fn add(&mut self, inputs: &[NodeId]) -> NodeId {
+        self.merge(Merge::Add, inputs)
+    }
+
+    /// Concatena as saídas de vários nós ao longo de `axis`.
+    pub fn concat(&mut self, inputs: &[NodeId], axis: usize) -> NodeId {
+        self.merge(Merge::Concat(axis), inputs)
+    }
+
+    /// Multiplica elemento a elemento as saídas de vários nós.
+    pub fn multiply(&mut self, inputs: &[NodeId]) -> NodeId {
+        self.merge(Merge::Multiply, inputs)
+    }
+
+    /// Marca um nó como saída do grafo, chamada `output_<i>`. As saídas são retornadas na
+    /// ordem em que são marcadas.
+    pub fn output(&mut self, node: NodeId) {
+        let name = format!("output_{}", self.outputs.len());
+        self.named_output(&name, node);
+    }
+
+    /// Marca um nó como saída do grafo com nome, usado por `forward_named` e `fit`.
+    pub fn named_output(&mut self, name: &str, node: NodeId) {
+        assert!(
+            node.0 < self.nodes.len(),
+            "nó {:?} não pertence ao grafo",
+            node
+        );
+        assert!(
+            !self.output_names.iter().any(|output| output == name),
+            "saída '{}' já existe",
+            name
+        );
+        self.outputs.push(node);
+        self.output_names.push(name.to_string());
+    }
+
+    /// Nomes das entradas, na ordem de criação.
+    pub fn input_names(&self) -> &[String] {
+        &self.input_names
+    }
+
+    /// Nomes das saídas, na ordem em que foram marcadas.
+    pub fn output_names(&self) -> &[String] {
+        &self.output_names
+    }
+
+    /// Número de entradas do grafo.
+    pub fn num_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Número de saídas do grafo.
+    pub fn num_outputs(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Acessa a camada de um nó criado com `layer`.
+    pub fn node_layer(&self, node: NodeId) -> Option<&LayerType> {
+        match &self.nodes.get(node.0)?.operation {
+            Operation::Layer(layer) => Some(layer),
+            _ => None,
+        }
+    }
+
+    /// Acessa mutavelmente a camada de um nó criado com `layer`.
+    pub fn node_layer_mut(&mut self, node: NodeId) -> Option<&mut LayerType> {
+        match &mut self.nodes.get_mut(node.0)?.operation {
+            Operation::Layer(layer) => Some(layer),
+            _ => None,
+        }
+    }
+
+    fn layers(&self) -> Vec<&dyn Layer> {
+        self.nodes
+            .iter()
+            .filter_map(|node| match &node.operation {
+                Operation::Layer(layer) => Some(layer as &dyn Layer),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn layers_mut(&mut self) -> Vec<&mut dyn Layer> {
+        self.nodes
+            .iter_mut()
+            .filter_map(|node| match &mut node.operation {
+                Operation::Layer(layer) => Some(layer as &mut dyn Layer),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Passagem forward com várias entradas.
+    ///
+    /// # Argumentos
+    ///
+    /// * `inputs` - Um tensor por entrada, na ordem de criação das entradas.
+    ///
+    /// # Retorno
+    ///
+    /// Retorna um tensor por saída, na ordem em que as saídas foram marcadas.
+    pub fn forward_inputs(&mut self, inputs: &[ArrayD<f32>]) -> Vec<ArrayD<f32>> {
+        assert_eq!(
+            inputs.len(),
+            self.inputs.len(),
+            "o grafo tem {} entradas, recebeu {}",
+            self.inputs.len(),
+            inputs.len()
+        );
+        let mut values: Vec<ArrayD<f32>> = Vec::with_capacity(self.nodes.len());
+        let mut fed = inputs.iter();
+        for node in &mut self.nodes {
+            let value = match &mut node.operation {
+                Operation::Input => fed.next().unwrap().clone(),
+                Operation::Layer(layer) => layer.forward(&values[node.inputs[0].0]),
+                Operation::Merge(merge) => {
+                    let inputs: Vec<&ArrayD<f32>> =
+                        node.inputs.iter().map(|input| &values[input.0]).collect();
+                    merge.forward(&inputs)
+                }
+            };
+            values.push(value);
+        }
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|output| values[output.0].clone())
+            .collect();
+        self.values = values;
+        outputs
+    }
+
+    /// Passagem backward com vários erros de saída, atualizando as camadas.
+    ///
+    /// O erro de um nó é a soma dos erros vindos de todos os nós que usam sua saída; cada
+    /// camada recebe uma única chamada a `backward` com esse erro acumulado.
+    ///
+    /// # Argumentos
+    ///
+    /// * `output_errors` - Um erro por saída, na ordem das saídas.
+    /// * `optimizer` - Otimizador usado para atualizar os parâmetros.
+    ///
+    /// # Retorno
+    ///
+    /// Retorna o erro de cada entrada, na ordem das entradas (zeros para entradas que não
+    /// influenciam nenhuma saída).
+    pub fn backward_outputs(
+        &mut self,
+        output_errors: &[ArrayD<f32>],
+        optimizer: &mut dyn Optimizer,
+    ) -> Vec<ArrayD<f32>> {
+        assert_eq!(
+            output_errors.len(),
+            self.outputs.len(),
+            "o grafo tem {} saídas, recebeu {} erros",
+            self.outputs.len(),
+            output_errors.len()
+        );
+        assert_eq!(
+            self.values.len(),
+            self.nodes.len(),
+            "GraphModel::backward chamado antes de forward"
+        );
+        let mut errors: Vec<Option<ArrayD<f32>>> = vec![None; self.nodes.len()];
+        for (output, error) in self.outputs.iter().zip(output_errors) {
+            accumulate(&mut errors[output.0], error.clone());
+        }
+
+        for index in (0..self.nodes.len()).rev() {
+            let Some(error) = errors[index].take() else {
+                continue;
+            };
+            let node = &mut self.nodes[index];
+            match &mut node.operation {
+                Operation::Input => errors[index] = Some(error),
+                Operation::Layer(layer) => {
+                    let input_error = layer.backward(&error, optimizer);
+                    accumulate(&mut errors[node.inputs[0].0], input_error);
+                }
+                Operation::Merge(merge) => {
+                    let inputs: Vec<&ArrayD<f32>> = node
+                        .inputs
+                        .iter()
+                        .map(|input| &self.values[input.0])
+                        .collect();
+                    for (input, input_error) in
+                        node.inputs.iter().zip(merge.backward(&error, &inputs))
+                    {
+                        accumulate(&mut errors[input.0], input_error);
+                    }
+                }
+            }
+        }
+
+        let values = std::mem::take(&mut self.values);
+        self.inputs
+            .iter()
+            .map(|input| {
+                errors[input.0]
+                    .take()
+                    .unwrap_or_else(|| ArrayD::zeros(values[input.0].raw_dim()))
+            })
+            .collect()
+    }
+
+    /// Formas das saídas para entradas com as formas indicadas.
+    pub fn output_shapes(&self, input_shapes: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        assert_eq!(
+            input_shapes.len(),
+            self.inputs.len(),
+            "o grafo tem {} entradas, recebeu {} formas",
+            self.inputs.len(),
+            input_shapes.len()
+        );
+        let mut shapes: Vec<Vec<usize>> = Vec::with_capacity(self.nodes.len());
+        let mut fed = input_shapes.iter();
+        for node in &self.nodes {
+            let shape = match &node.operation {
+                Operation::Input => fed.next().unwrap().clone(),
+                Operation::Layer(layer) => layer.output_shape(&shapes[node.inputs[0].0]),
+                Operation::Merge(merge) => {
+                    let inputs: Vec<&[usize]> = node
+                        .inputs
+                        .iter()
+                        .map(|input| &shapes[input.0][..])
+                        .collect();
+                    merge.output_shape(&inputs)
+                }
+            };
+            shapes.push(shape);
+        }
+        self.outputs
+            .iter()
+            .map(|output| shapes[output.0].clone())
+            .collect()
+    }
+
+    /// Passagem forward com entradas nomeadas.
+    ///
+    /// # Argumentos
+    ///
+    /// * `inputs` - Um tensor para cada entrada do grafo, indexado pelo nome.
+    ///
+    /// # Retorno
+    ///
+    /// Retorna as saídas indexadas pelo nome.
+    pub fn forward_named(
+        &mut self,
+        inputs: &HashMap<String, ArrayD<f32>>,
+    ) -> HashMap<String, ArrayD<f32>> {
+        let ordered: Vec<ArrayD<f32>> = self
+            .input_names
+            .iter()
+            .map(|name| {
+                inputs
+                    .get(name)
+                    .unwrap_or_else(|| panic!("entrada '{}' não fornecida", name))
+                    .clone()
+            })
+            .collect();
+        let outputs = self.forward_inputs(&ordered);
+        self.output_names.iter().cloned().zip(outputs).collect()
+    }
+
+    /// Passagem backward com erros nomeados, atualizando as camadas.
+    ///
+    /// # Argumentos
+    ///
+    /// * `output_errors` - Erro de cada saída, indexado pelo nome; saídas ausentes recebem
+    ///   erro zero.
+    /// * `optimizer` - Otimizador usado para atualizar os parâmetros.
+    ///
+    /// # Retorno
+    ///
+    /// Retorna o erro de cada entrada, indexado pelo nome.
+    pub fn backward_named(
+        &mut self,
+        output_errors: &HashMap<String, ArrayD<f32>>,
+        optimizer: &mut dyn Optimizer,
+    ) -> HashMap<String, ArrayD<f32>> {
+        for name in output_errors.keys() {
+            assert!(
+                self.output_names.contains(name),
+                "saída '{}' não existe",
+                name
+            );
+        }
+        assert_eq!(
+            self.values.len(),
+            self.nodes.len(),
+            "GraphModel::backward chamado antes de forward"
+        );
+        let ordered: Vec<ArrayD<f32>> = self
+            .output_names
+            .iter()
+            .zip(&self.outputs)
+            .map(|(name, output)| match output_errors.get(name) {
+                Some(error) => error.clone(),
+                None => ArrayD::zeros(self.values[output.0].raw_dim()),
+            })
+            .collect();
+        let input_errors = self.backward_outputs(&ordered, optimizer);
+        self.input_names.iter().cloned().zip(input_errors).collect()
+    }
+
+    /// Avalia o grafo sobre um conjunto de dados, em modo de inferência e sem atualizar os pesos.
+    ///
+    /// # Argumentos
+    ///
+    /// * `inputs` - Entradas indexadas pelo nome.
+    /// * `targets` - Alvos de cada saída com perda, indexados pelo nome da saída.
+    /// * `losses` - Perdas e pesos de cada saída.
+    ///
+    /// # Retorno
+    ///
+    /// Retorna a perda total ponderada e, como métricas, a perda de cada saída.
+    pub fn evaluate_named(
+        &mut self,
+        inputs: &HashMap<String, ArrayD<f32>>,
+        targets: &HashMap<String, ArrayD<f32>>,
+        losses: &MultiOutputLoss,
+    ) -> Evaluation {
+        self.set_training(false);
+        let outputs = self.forward_named(inputs);
+        losses.evaluate(&outputs, targets)
+    }
+
+    /// Treina o grafo com entradas e saídas nomeadas, pelo mesmo laço de treinamento de
+    /// `NeuralNetwork::train`: embaralhamento, validação, callbacks, progresso e checkpoints
+    /// seguem a `TrainConfig`.
+    ///
+    /// Cada saída com perda em `losses` recebe como erro a derivada da sua perda multiplicada
+    /// pelo peso correspondente; a perda total é a soma ponderada das perdas das saídas. As
+    /// estatísticas registram a perda total e, como métricas, a perda de cada saída
+    /// (`<saida>_loss`) e as métricas configuradas para cada saída (`<saida>_<metrica>`). A
+    /// acurácia geral não se aplica a várias saídas e é registrada como `NaN`.
+    ///
+    /// Durante o treinamento, o grafo é a única camada de uma `NeuralNetwork`, que é a rede
+    /// vista pelos callbacks e gravada nos checkpoints.
+    ///
+    /// # Argumentos
+    ///
+    /// * `inputs` - Entradas indexadas pelo nome, com as amostras no primeiro eixo.
+    /// * `targets` - Alvos de cada saída com perda, indexados pelo nome da saída.
+    /// * `losses` - Perdas e pesos de cada saída.
+    /// * `optimizer` - Otimizador usado para atualizar os parâmetros.
+    /// * `stats` - Estatísticas do treinamento.
+    /// * `config` - Configuração do treinamento; a validação usa `with_named_validation_data`
+    ///   ou `with_validation_split`.
+    ///
+    /// # Retorno
+    ///
+    /// Retorna um erro se faltar alguma entrada ou alvo, se o número de amostras diferir entre
+    /// eles, se a configuração for inválida (como `batch_size` zero) ou se a gravação ou a
+    /// restauração de um checkpoint falhar.
+    pub fn fit(
+        &mut self,
+        inputs: &HashMap<String, ArrayD<f32>>,
+        targets: &HashMap<String, ArrayD<f32>>,
+        losses: &MultiOutputLoss,
+        optimizer: Arc<Mutex<dyn Optimizer + Send>>,
+        stats: Arc<Mutex<TrainingStats>>,
+        mut config: TrainConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let metrics = config.metrics.clone();
+        let data = NamedData::new(self, inputs, targets, losses, &metrics)?;
+        let validation = config.validation.take();
+        let (data, validation) =
+            match &validation {
+                None => (data, None),
+                Some(Validation::Named(inputs, targets)) => {
+                    let validation = NamedData::new(self, inputs, targets, losses, &metrics)?;
+                    (data, Some(validation))
+                }
+                Some(Validation::Split(fraction)) => split_validation(data, *fraction)?,
+                Some(Validation::Data(..)) => return Err(
+                    "GraphModel::fit usa dados de validação nomeados (with_named_validation_data)"
+                        .into(),
+                ),
+            };
+        if let Some(saved) = &config.resume {
+            if !matches!(saved.model.layers[..], [LayerType::Graph(_)]) {
+                return Err("o checkpoint não foi gravado por GraphModel::fit".into());
+            }
+        }
+
+        let mut neural_net = NeuralNetwork::new();
+        neural_net.add_layer(std::mem::take(self));
+        let neural_net = Arc::new(Mutex::new(neural_net));
+        let result = NeuralNetwork::fit_data(
+            neural_net.clone(),
+            data,
+            validation,
+            optimizer,
+            stats,
+            config,
+        );
+        if let Some(LayerType::Graph(graph)) = neural_net.lock().unwrap().layers.pop() {
+            *self = graph;
+        }
+        result
+    }
+
+    fn assert_single(&self) {
+        assert!(
+            self.inputs.len() == 1 && self.outputs.len() == 1,
+            "como Layer, o GraphModel deve ter uma entrada e uma saída (tem {} e {})",
+            self.inputs.len(),
+            self.outputs.len()
+        );
+    }
+}
+
+/// Entradas e alvos nomeados de `GraphModel::fit`, com as perdas e as métricas.
+#[derive(Clone)]
+struct NamedData<'a> {
+    inputs: HashMap<String, ArrayViewD<'a, f32>>,
+    targets: HashMap<String, ArrayViewD<'a, f32>>,
+    samples: usize,
+    losses: &'a MultiOutputLoss,
+    metrics: &'a [MetricType],
+}
+
+impl<'a> NamedData<'a> {
+    /// Verifica que há uma entrada para cada entrada do grafo e um alvo para cada saída com
+    /// perda, todos com o mesmo número de amostras.
+    fn new(
+        graph: &GraphModel,
+        inputs: &'a HashMap<String, ArrayD<f32>>,
+        targets: &'a HashMap<String, ArrayD<f32>>,
+        losses: &'a MultiOutputLoss,
+        metrics: &'a [MetricType],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut views = HashMap::new();
+        for name in &graph.input_names {
+            let input = inputs
+                .get(name)
+                .ok_or_else(|| format!("entrada '{}' não fornecida", name))?;
+            views.insert(name.clone(), input.view());
+        }
+        let mut target_views = HashMap::new();
+        for name in losses.output_names() {
+            if !graph.output_names.iter().any(|output| output == name) {
+                return Err(format!("saída '{}' não existe", name).into());
+            }
+            let target = targets
+                .get(name)
+                .ok_or_else(|| format!("alvo da saída '{}' não fornecido", name))?;
+            target_views.insert(name.to_string(), target.view());
+        }
+        let mut counts = views
+            .values()
+            .chain(target_views.values())
+            .map(|x| x.len_of(Axis(0)));
+        let samples = counts.next().ok_or("nenhuma entrada fornecida")?;
+        if counts.any(|n| n != samples) {
+            return Err("entradas e alvos devem ter o mesmo número de amostras".into());
+        }
+        Ok(NamedData {
+            inputs: views,
+            targets: target_views,
+            samples,
+            losses,
+            metrics,
+        })
+    }
+
+    /// Perda total, perda de cada saída e métricas configuradas de cada saída, na ordem de
+    /// `metric_names`.
+    fn evaluation(
+        &self,
+        outputs: &HashMap<String, ArrayD<f32>>,
+        targets: &HashMap<String, ArrayD<f32>>,
+    ) -> Evaluation {
+        let mut evaluation = self.losses.evaluate(outputs, targets);
+        for name in self.losses.output_names() {
+            for metric in self.metrics {
+                let value = metric.compute(&outputs[name], &targets[name]);
+                evaluation
+                    .metrics
+                    .push((format!("{}_{}", name, metric.name()), value));
+            }
+        }
+        evaluation
+    }
+
+    fn select(
+        tensors: &HashMap<String, ArrayViewD<'a, f32>>,
+        indices: &[usize],
+    ) -> HashMap<String, ArrayD<f32>> {
+        tensors
+            .iter()
+            .map(|(name, x)| (name.clone(), x.select(Axis(0), indices)))
+            .collect()
+    }
+}
+
+/// O grafo treinado por `GraphModel::fit`, única camada da rede.
+fn graph_mut(
+    neural_net: &mut NeuralNetwork,
+) -> Result<&mut GraphModel, Box<dyn std::error::Error>> {
+    match &mut neural_net.layers[..] {
+        [LayerType::Graph(graph)] => Ok(graph),
+        _ => Err("a rede treinada por GraphModel::fit deve conter apenas o grafo".into()),
+    }
+}
+
+impl TrainingData for NamedData<'_> {
+    fn samples(&self) -> usize {
+        self.samples
+    }
+
+    fn split_at(&self, at: usize) -> (Self, Self) {
+        let part = |range: Slice, samples: usize| {
+            let mut part = self.clone();
+            part.samples = samples;
+            for x in part.inputs.values_mut().chain(part.targets.values_mut()) {
+                x.slice_axis_inplace(Axis(0), range);
+            }
+            part
+        };
+        (
+            part(Slice::from(..at), at),
+            part(Slice::from(at..), self.samples - at),
+        )
+    }
+
+    fn metric_names(&self) -> Vec<String> {
+        let outputs: Vec<&str> = self.losses.output_names().collect();
+        let losses = outputs.iter().map(|name| format!("{}_loss", name));
+        let metrics = outputs.iter().flat_map(|name| {
+            self.metrics
+                .iter()
+                .map(move |metric| format!("{}_{}", name, metric.name()))
+        });
+        losses.chain(metrics).collect()
+    }
+
+    fn tracks_accuracy(&self) -> bool {
+        false
+    }
+
+    fn train_batch(
+        &self,
+        neural_net: &mut NeuralNetwork,
+        indices: &[usize],
+        optimizer: &mut dyn Optimizer,
+    ) -> Result<(Evaluation, f32), Box<dyn std::error::Error>> {
+        let graph = graph_mut(neural_net)?;
+        let x_batch = Self::select(&self.inputs, indices);
+        let y_batch = Self::select(&self.targets, indices);
+        let outputs = graph.forward_named(&x_batch);
+        let evaluation = self.evaluation(&outputs, &y_batch);
+        graph.backward_named(&self.losses.derivatives(&outputs, &y_batch), optimizer);
+        Ok((evaluation, f32::NAN))
+    }
+
+    fn evaluate(
+        &self,
+        neural_net: &mut NeuralNetwork,
+    ) -> Result<Evaluation, Box<dyn std::error::Error>> {
+        let training = neural_net.is_training();
+        neural_net.eval_mode();
+        let graph = graph_mut(neural_net)?;
+        let x = Self::select(&self.inputs, &(0..self.samples).collect::<Vec<_>>());
+        let outputs = graph.forward_named(&x);
+        let y = self
+            .targets
+            .iter()
+            .map(|(name, y)| (name.clone(), y.to_owned()))
+            .collect();
+        let evaluation = self.evaluation(&outputs, &y);
+        if training {
+            neural_net.train_mode();
+        }
+        Ok(evaluation)
+    }
+}
+
+/// Soma `error` ao erro acumulado de um nó.
+fn accumulate(total: &mut Option<ArrayD<f32>>, error: ArrayD<f32>) {
+    match total {
+        Some(total) => *total += &error,
+        None => *total = Some(error),
+    }
+}
+
+impl Layer for GraphModel {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        self.assert_single();
+        self.forward_inputs(std::slice::from_ref(input)).remove(0)
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        self.assert_single();
+        self.backward_outputs(std::slice::from_ref(output_error), optimizer)
+            .remove(0)
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        self.assert_single();
+        self.output_shapes(&[input_shape.to_vec()]).remove(0)
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        collect_weights(self.layers())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        distribute_weights(self.layers_mut(), weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        collect_moments(self.layers())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        distribute_moments(self.layers_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.layers().iter().map(|layer| layer.param_count()).sum()
+    }
+
+    fn set_training(&mut self, training: bool) {
+        for layer in self.layers_mut() {
+            layer.set_training(training);
+        }
+    }
+
+    fn trainable(&self) -> bool {
+        any_trainable(self.layers())
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        for layer in self.layers_mut() {
+            layer.set_trainable(trainable);
+        }
+    }
+}
@@ -0,0 +1,763 @@
+// bran/src/layers/normalization.rs
+
+use super::{
+    parameter_moments, parameter_values, set_parameter_moments, set_parameter_values, Layer,
+};
+use crate::optimizer::{Optimizer, Parameter};
+use ndarray::{Array1, Array2, ArrayD, Axis, Ix1};
+use serde::{Deserialize, Serialize};
+
+/// Reorganiza `(N, C, ...)` em uma matriz `(C, N * ...)`, com um canal por linha.
+fn channel_rows(x: &ArrayD<f32>) -> Array2<f32> {
+    let channels = x.shape()[1];
+    let mut axes: Vec<usize> = (0..x.ndim()).collect();
+    axes.swap(0, 1);
+    x.view()
+        .permuted_axes(axes)
+        .as_standard_layout()
+        .into_owned()
+        .into_shape((channels, x.len() / channels))
+        .unwrap()
+}
+
+/// Operação inversa de `channel_rows`, restaurando a forma `shape`.
+fn from_channel_rows(rows: Array2<f32>, shape: &[usize]) -> ArrayD<f32> {
+    let mut swapped = shape.to_vec();
+    swapped.swap(0, 1);
+    let mut axes: Vec<usize> = (0..shape.len()).collect();
+    axes.swap(0, 1);
+    rows.into_shape(swapped)
+        .unwrap()
+        .permuted_axes(axes)
+        .as_standard_layout()
+        .into_owned()
+}
+
+/// Passagem backward de uma normalização sobre as colunas de cada linha de `x_hat`.
+///
+/// Recebe o erro já multiplicado pelo ganho (`dx_hat`) e o inverso do desvio padrão de cada
+/// linha, e retorna o erro da entrada:
+/// `dx = inv_std * (dx_hat - mean(dx_hat) - x_hat * mean(dx_hat * x_hat))`.
+fn normalize_backward(
+    dx_hat: &Array2<f32>,
+    x_hat: &Array2<f32>,
+    inv_std: &Array1<f32>,
+) -> Array2<f32> {
+    let mean_dx_hat = dx_hat.mean_axis(Axis(1)).unwrap().insert_axis(Axis(1));
+    let mean_dx_hat_x_hat = (dx_hat * x_hat)
+        .mean_axis(Axis(1))
+        .unwrap()
+        .insert_axis(Axis(1));
+    (dx_hat - &mean_dx_hat - x_hat * &mean_dx_hat_x_hat) * inv_std.view().insert_axis(Axis(1))
+}
+
+/// Valores guardados na passagem forward da normalização em lote.
+#[derive(Clone)]
+struct BatchNormCache {
+    x_hat: Array2<f32>,
+    inv_std: Array1<f32>,
+    shape: Vec<usize>,
+    /// Se as estatísticas do lote foram usadas (modo de treinamento).
+    batch_statistics: bool,
+}
+
+/// Gera uma camada de normalização em lote sobre o eixo dos canais (eixo 1).
+macro_rules! batch_norm {
+    ($(#[$doc:meta])* $name:ident, [$($ndim:literal),+], $expected:literal) => {
+        $(#[$doc])*
+        #[derive(Serialize, Deserialize, Clone)]
+        pub struct $name {
+            /// Número de canais (ou features) normalizados.
+            pub num_features: usize,
+            /// Peso das estatísticas do lote na atualização das estatísticas acumuladas.
+            pub momentum: f32,
+            /// Constante somada à variância para estabilidade numérica.
+            pub epsilon: f32,
+            /// Ganho aprendido, um por canal.
+            pub gamma: Parameter,
+            /// Deslocamento aprendido, um por canal.
+            pub beta: Parameter,
+            /// Média acumulada, usada em modo de inferência.
+            pub running_mean: Array1<f32>,
+            /// Variância acumulada (não enviesada), usada em modo de inferência.
+            pub running_var: Array1<f32>,
+            trainable: bool,
+
+            #[serde(skip)]
+            training: bool,
+            #[serde(skip)]
+            cache: Option<BatchNormCache>,
+        }
+
+        impl $name {
+            /// Cria uma nova camada de normalização em lote com `momentum = 0.1` e `epsilon = 1e-5`.
+            ///
+            /// # Argumentos
+            ///
+            /// * `num_features` - Número de canais da entrada (tamanho do eixo 1).
+            pub fn new(num_features: usize) -> Self {
+                $name {
+                    num_features,
+                    momentum: 0.1,
+                    epsilon: 1e-5,
+                    gamma: Parameter::new(ArrayD::ones(vec![num_features]), false),
+                    beta: Parameter::new(ArrayD::zeros(vec![num_features]), false),
+                    running_mean: Array1::zeros(num_features),
+                    running_var: Array1::ones(num_features),
+                    trainable: true,
+                    training: false,
+                    cache: None,
+                }
+            }
+
+            /// Define o peso das estatísticas de cada lote nas estatísticas acumuladas.
+            pub fn with_momentum(mut self, momentum: f32) -> Self {
+                assert!(
+                    (0.0..=1.0).contains(&momentum),
+                    "o momentum deve estar no intervalo [0, 1]"
+                );
+                self.momentum = momentum;
+                self
+            }
+
+            /// Define a constante de estabilidade numérica.
+            pub fn with_epsilon(mut self, epsilon: f32) -> Self {
+                self.epsilon = epsilon;
+                self
+            }
+
+            fn parameters(&self) -> Vec<&Parameter> {
+                vec![&self.gamma, &self.beta]
+            }
+
+            fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+                vec![&mut self.gamma, &mut self.beta]
+            }
+        }
+
+        impl Layer for $name {
+            fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+                assert!(
+                    [$($ndim),+].contains(&input.ndim()),
+                    concat!(stringify!($name), " espera uma entrada ", $expected)
+                );
+                assert_eq!(
+                    input.shape()[1],
+                    self.num_features,
+                    concat!(stringify!($name), " espera {} canais, recebeu {}"),
+                    self.num_features,
+                    input.shape()[1]
+                );
+                let x = channel_rows(input);
+
+                let (mean, var) = if self.training {
+                    let mean = x.mean_axis(Axis(1)).unwrap();
+                    let var = x.var_axis(Axis(1), 0.0);
+                    let count = x.ncols() as f32;
+                    let unbiased = if count > 1.0 {
+                        &var * (count / (count - 1.0))
+                    } else {
+                        var.clone()
+                    };
+                    self.running_mean =
+                        &self.running_mean * (1.0 - self.momentum) + &mean * self.momentum;
+                    self.running_var =
+                        &self.running_var * (1.0 - self.momentum) + unbiased * self.momentum;
+                    (mean, var)
+                } else {
+                    (self.running_mean.clone(), self.running_var.clone())
+                };
+
+                let inv_std = var.mapv(|v| 1.0 / (v + self.epsilon).sqrt());
+                let x_hat = (x - &mean.insert_axis(Axis(1))) * &inv_std.view().insert_axis(Axis(1));
+                let gamma = self.gamma.value.view().into_dimensionality::<Ix1>().unwrap();
+                let beta = self.beta.value.view().into_dimensionality::<Ix1>().unwrap();
+                let y = &x_hat * &gamma.insert_axis(Axis(1)) + &beta.insert_axis(Axis(1));
+
+                self.cache = Some(BatchNormCache {
+                    x_hat,
+                    inv_std,
+                    shape: input.shape().to_vec(),
+                    batch_statistics: self.training,
+                });
+                from_channel_rows(y, input.shape())
+            }
+
+            fn backward(
+                &mut self,
+                output_error: &ArrayD<f32>,
+                optimizer: &mut dyn Optimizer,
+            ) -> ArrayD<f32> {
+                let cache = self.cache.take().expect(concat!(
+                    stringify!($name),
+                    "::backward chamado antes de forward"
+                ));
+                let dy = channel_rows(output_error);
+                if self.trainable {
+                    self.gamma.grad = (&dy * &cache.x_hat).sum_axis(Axis(1)).into_dyn();
+                    self.beta.grad = dy.sum_axis(Axis(1)).into_dyn();
+                }
+
+                let gamma = self.gamma.value.view().into_dimensionality::<Ix1>().unwrap();
+                let dx_hat = dy * &gamma.insert_axis(Axis(1));
+                let dx = if cache.batch_statistics {
+                    normalize_backward(&dx_hat, &cache.x_hat, &cache.inv_std)
+                } else {
+                    // Com as estatísticas acumuladas, a normalização é uma transformação afim
+                    dx_hat * &cache.inv_std.insert_axis(Axis(1))
+                };
+
+                if self.trainable {
+                    optimizer.update_parameters(&mut self.parameters_mut());
+                }
+                from_channel_rows(dx, &cache.shape)
+            }
+
+            fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+                input_shape.to_vec()
+            }
+
+            fn weights(&self) -> Vec<ArrayD<f32>> {
+                let mut weights = parameter_values(&self.parameters());
+                weights.push(self.running_mean.clone().into_dyn());
+                weights.push(self.running_var.clone().into_dyn());
+                weights
+            }
+
+            fn set_weights(&mut self, mut weights: Vec<ArrayD<f32>>) {
+                assert_eq!(
+                    weights.len(),
+                    4,
+                    concat!(stringify!($name), " espera gamma, beta, média e variância")
+                );
+                let running: Vec<Array1<f32>> = weights
+                    .split_off(2)
+                    .into_iter()
+                    .map(|stat| stat.into_dimensionality::<Ix1>().unwrap())
+                    .collect();
+                assert!(
+                    running.iter().all(|stat| stat.len() == self.num_features),
+                    "forma das estatísticas acumuladas incompatível"
+                );
+                set_parameter_values(&mut self.parameters_mut(), weights);
+                self.running_mean = running[0].clone();
+                self.running_var = running[1].clone();
+            }
+
+            fn moments(&self) -> Vec<ArrayD<f32>> {
+                parameter_moments(&self.parameters())
+            }
+
+            fn set_moments(
+                &mut self,
+                moments: Vec<ArrayD<f32>>,
+            ) -> Result<(), Box<dyn std::error::Error>> {
+                set_parameter_moments(&mut self.parameters_mut(), moments)
+            }
+
+            fn param_count(&self) -> usize {
+                2 * self.num_features
+            }
+
+            fn set_training(&mut self, training: bool) {
+                self.training = training;
+            }
+
+            fn trainable(&self) -> bool {
+                self.trainable
+            }
+
+            fn set_trainable(&mut self, trainable: bool) {
+                self.trainable = trainable;
+            }
+        }
+    };
+}
+
+batch_norm! {
+    /// Normalização em lote para entradas `(N, C)` ou `(N, C, T)`.
+    ///
+    /// Em modo de treinamento, cada canal é normalizado com a média e a variância do lote
+    /// (e das posições temporais), e as estatísticas acumuladas são atualizadas com
+    /// `momentum`. Em modo de inferência, usa as estatísticas acumuladas, que são salvas
+    /// junto com a rede.
+    BatchNorm1d, [2, 3], "(N, C) ou (N, C, T)"
+}
+
+batch_norm! {
+    /// Normalização em lote para mapas de características `(N, C, H, W)`.
+    ///
+    /// Em modo de treinamento, cada canal é normalizado com a média e a variância do lote
+    /// e das posições espaciais, e as estatísticas acumuladas são atualizadas com
+    /// `momentum`. Em modo de inferência, usa as estatísticas acumuladas, que são salvas
+    /// junto com a rede.
+    BatchNorm2d, [4], "(N, C, H, W)"
+}
+
+/// Valores guardados na passagem forward das normalizações por amostra.
+#[derive(Clone)]
+struct NormCache {
+    /// Entrada normalizada, uma linha por grupo normalizado.
+    x_hat: Array2<f32>,
+    inv_std: Array1<f32>,
+    shape: Vec<usize>,
+}
+
+/// Normaliza cada linha de `x` para média zero e variância um.
+fn normalize_rows(x: &Array2<f32>, epsilon: f32) -> (Array2<f32>, Array1<f32>) {
+    let mean = x.mean_axis(Axis(1)).unwrap();
+    let inv_std = x
+        .var_axis(Axis(1), 0.0)
+        .mapv(|v| 1.0 / (v + epsilon).sqrt());
+    let x_hat = (x - &mean.insert_axis(Axis(1))) * inv_std.view().insert_axis(Axis(1));
+    (x_hat, inv_std)
+}
+
+/// Reorganiza `x` em linhas de `row_len` elementos consecutivos.
+fn rows_of(x: &ArrayD<f32>, row_len: usize) -> Array2<f32> {
+    x.as_standard_layout()
+        .into_owned()
+        .into_shape((x.len() / row_len, row_len))
+        .unwrap()
+}
+
+/// Verifica que a entrada termina com `normalized_shape` e retorna o número de elementos normalizados.
+fn trailing_len(name: &str, input_shape: &[usize], normalized_shape: &[usize]) -> usize {
+    assert!(
+        input_shape.ends_with(normalized_shape),
+        "{} espera uma entrada terminando em {:?}, recebeu {:?}",
+        name,
+        normalized_shape,
+        input_shape
+    );
+    normalized_shape.iter().product()
+}
+
+/// Normalização de camada: cada amostra é normalizada sobre os últimos eixos
+/// (`normalized_shape`), independentemente do lote, e então recebe um ganho e um
+/// deslocamento aprendidos elemento a elemento. Adequada a modelos de sequência e a
+/// lotes pequenos.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LayerNorm {
+    /// Forma dos últimos eixos normalizados.
+    pub normalized_shape: Vec<usize>,
+    /// Constante somada à variância para estabilidade numérica.
+    pub epsilon: f32,
+    /// Ganho aprendido, com forma `normalized_shape`.
+    pub gamma: Parameter,
+    /// Deslocamento aprendido, com forma `normalized_shape`.
+    pub beta: Parameter,
+    trainable: bool,
+
+    #[serde(skip)]
+    cache: Option<NormCache>,
+}
+
+impl LayerNorm {
+    /// Cria uma nova normalização de camada com `epsilon = 1e-5`.
+    ///
+    /// # Argumentos
+    ///
+    /// * `normalized_shape` - Forma dos últimos eixos da entrada a normalizar.
+    pub fn new(normalized_shape: &[usize]) -> Self {
+        LayerNorm {
+            normalized_shape: normalized_shape.to_vec(),
+            epsilon: 1e-5,
+            gamma: Parameter::new(ArrayD::ones(normalized_shape), false),
+            beta: Parameter::new(ArrayD::zeros(normalized_shape), false),
+            trainable: true,
+            cache: None,
+        }
+    }
+
+    /// Define a constante de estabilidade numérica.
+    pub fn with_epsilon(mut self, epsilon: f32) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    fn parameters(&self) -> Vec<&Parameter> {
+        vec![&self.gamma, &self.beta]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        vec![&mut self.gamma, &mut self.beta]
+    }
+}
+
+impl Layer for LayerNorm {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let len = trailing_len("LayerNorm", input.shape(), &self.normalized_shape);
+        let (x_hat, inv_std) = normalize_rows(&rows_of(input, len), self.epsilon);
+        let gamma = self.gamma.value.view().into_shape(len).unwrap();
+        let beta = self.beta.value.view().into_shape(len).unwrap();
+        let y = &x_hat * &gamma + beta;
+
+        self.cache = Some(NormCache {
+            x_hat,
+            inv_std,
+            shape: input.shape().to_vec(),
+        });
+        y.into_shape(input.shape()).unwrap()
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let cache = self
+            .cache
+            .take()
+            .expect("LayerNorm::backward chamado antes de forward");
+        let len = cache.x_hat.ncols();
+        let dy = rows_of(output_error, len);
+        if self.trainable {
+            self.gamma.grad = (&dy * &cache.x_hat)
+                .sum_axis(Axis(0))
+                .into_shape(self.normalized_shape.clone())
+                .unwrap();
+            self.beta.grad = dy
+                .sum_axis(Axis(0))
+                .into_shape(self.normalized_shape.clone())
+                .unwrap();
+        }
+
+        let gamma = self.gamma.value.view().into_shape(len).unwrap();
+        let dx = normalize_backward(&(dy * gamma), &cache.x_hat, &cache.inv_std);
+
+        if self.trainable {
+            optimizer.update_parameters(&mut self.parameters_mut());
+        }
+        dx.into_shape(cache.shape).unwrap()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        trailing_len("LayerNorm", input_shape, &self.normalized_shape);
+        input_shape.to_vec()
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&self.parameters())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut self.parameters_mut(), weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&self.parameters())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut self.parameters_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.gamma.len() + self.beta.len()
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
+
+/// Normalização pela raiz da média quadrática (RMSNorm): divide cada amostra pela raiz da
+/// média dos quadrados dos últimos eixos, sem subtrair a média, e aplica um ganho aprendido.
+/// É mais barata que a `LayerNorm` e comum em transformers.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RMSNorm {
+    /// Forma dos últimos eixos normalizados.
+    pub normalized_shape: Vec<usize>,
+    /// Constante somada à média quadrática para estabilidade numérica.
+    pub epsilon: f32,
+    /// Ganho aprendido, com forma `normalized_shape`.
+    pub gamma: Parameter,
+    trainable: bool,
+
+    #[serde(skip)]
+    cache: Option<NormCache>,
+}
+
+impl RMSNorm {
+    /// Cria uma nova RMSNorm com `epsilon = 1e-6`.
+    ///
+    /// # Argumentos
+    ///
+    /// * `normalized_shape` - Forma dos últimos eixos da entrada a normalizar.
+    pub fn new(normalized_shape: &[usize]) -> Self {
+        RMSNorm {
+            normalized_shape: normalized_shape.to_vec(),
+            epsilon: 1e-6,
+            gamma: Parameter::new(ArrayD::ones(normalized_shape), false),
+            trainable: true,
+            cache: None,
+        }
+    }
+
+    /// Define a constante de estabilidade numérica.
+    pub fn with_epsilon(mut self, epsilon: f32) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+}
+
+impl Layer for RMSNorm {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let len = trailing_len("RMSNorm", input.shape(), &self.normalized_shape);
+        let x = rows_of(input, len);
+        let inv_rms = x
+            .mapv(|v| v * v)
+            .mean_axis(Axis(1))
+            .unwrap()
+            .mapv(|ms| 1.0 / (ms + self.epsilon).sqrt());
+        let x_hat = x * inv_rms.view().insert_axis(Axis(1));
+        let gamma = self.gamma.value.view().into_shape(len).unwrap();
+        let y = &x_hat * &gamma;
+
+        self.cache = Some(NormCache {
+            x_hat,
+            inv_std: inv_rms,
+            shape: input.shape().to_vec(),
+        });
+        y.into_shape(input.shape()).unwrap()
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let cache = self
+            .cache
+            .take()
+            .expect("RMSNorm::backward chamado antes de forward");
+        let len = cache.x_hat.ncols();
+        let dy = rows_of(output_error, len);
+        if self.trainable {
+            self.gamma.grad = (&dy * &cache.x_hat)
+                .sum_axis(Axis(0))
+                .into_shape(self.normalized_shape.clone())
+                .unwrap();
+        }
+
+        // dx = inv_rms * (dx_hat - x_hat * mean(dx_hat * x_hat))
+        let gamma = self.gamma.value.view().into_shape(len).unwrap();
+        let dx_hat = dy * gamma;
+        let mean_dx_hat_x_hat = (&dx_hat * &cache.x_hat)
+            .mean_axis(Axis(1))
+            .unwrap()
+            .insert_axis(Axis(1));
+        let dx = (dx_hat - &cache.x_hat * &mean_dx_hat_x_hat)
+            * cache.inv_std.view().insert_axis(Axis(1));
+
+        if self.trainable {
+            optimizer.update_parameters(&mut [&mut self.gamma]);
+        }
+        dx.into_shape(cache.shape).unwrap()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        trailing_len("RMSNorm", input_shape, &self.normalized_shape);
+        input_shape.to_vec()
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&[&self.gamma])
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut [&mut self.gamma], weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&[&self.gamma])
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut [&mut self.gamma], moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.gamma.len()
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
+
+/// Normalização em grupos: os canais de cada amostra `(N, C, ...)` são divididos em
+/// `num_groups` grupos, normalizados sobre os canais do grupo e as posições espaciais.
+/// Não depende do tamanho do lote; o ganho e o deslocamento são aprendidos por canal.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GroupNorm {
+    /// Número de grupos de canais.
+    pub num_groups: usize,
+    /// Número de canais da entrada.
+    pub num_channels: usize,
+    /// Constante somada à variância para estabilidade numérica.
+    pub epsilon: f32,
+    /// Ganho aprendido, um por canal.
+    pub gamma: Parameter,
+    /// Deslocamento aprendido, um por canal.
+    pub beta: Parameter,
+    trainable: bool,
+
+    #[serde(skip)]
+    cache: Option<NormCache>,
+}
+
+impl GroupNorm {
+    /// Cria uma nova normalização em grupos com `epsilon = 1e-5`.
+    ///
+    /// # Argumentos
+    ///
+    /// * `num_groups` - Número de grupos; deve dividir `num_channels`.
+    /// * `num_channels` - Número de canais da entrada (tamanho do eixo 1).
+    pub fn new(num_groups: usize, num_channels: usize) -> Self {
+        assert!(
+            num_groups > 0 && num_channels.is_multiple_of(num_groups),
+            "num_groups ({}) deve dividir num_channels ({})",
+            num_groups,
+            num_channels
+        );
+        GroupNorm {
+            num_groups,
+            num_channels,
+            epsilon: 1e-5,
+            gamma: Parameter::new(ArrayD::ones(vec![num_channels]), false),
+            beta: Parameter::new(ArrayD::zeros(vec![num_channels]), false),
+            trainable: true,
+            cache: None,
+        }
+    }
+
+    /// Define a constante de estabilidade numérica.
+    pub fn with_epsilon(mut self, epsilon: f32) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    fn parameters(&self) -> Vec<&Parameter> {
+        vec![&self.gamma, &self.beta]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        vec![&mut self.gamma, &mut self.beta]
+    }
+
+    /// Ganho ou deslocamento como coluna `(C, 1)`, para operar sobre `(N, C, posições)`.
+    fn per_channel(param: &Parameter) -> ndarray::ArrayView2<'_, f32> {
+        param.value.view().into_shape((param.len(), 1)).unwrap()
+    }
+}
+
+impl Layer for GroupNorm {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        assert!(
+            input.ndim() >= 2 && input.shape()[1] == self.num_channels,
+            "GroupNorm espera uma entrada (N, {}, ...)",
+            self.num_channels
+        );
+        let n = input.shape()[0];
+        let group_len = input.len() / (n * self.num_groups);
+        let (x_hat, inv_std) = normalize_rows(&rows_of(input, group_len), self.epsilon);
+
+        // Os canais de cada grupo são consecutivos, então `(N * G, tamanho do grupo)` é
+        // reorganizado diretamente em `(N, C, posições)` para aplicar o ganho por canal
+        let positions = input.len() / (n * self.num_channels);
+        let y = x_hat
+            .view()
+            .into_shape((n, self.num_channels, positions))
+            .unwrap()
+            .to_owned()
+            * Self::per_channel(&self.gamma)
+            + Self::per_channel(&self.beta);
+
+        self.cache = Some(NormCache {
+            x_hat,
+            inv_std,
+            shape: input.shape().to_vec(),
+        });
+        y.into_shape(input.shape()).unwrap()
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let cache = self
+            .cache
+            .take()
+            .expect("GroupNorm::backward chamado antes de forward");
+        let n = cache.shape[0];
+        let positions = cache.x_hat.len() / (n * self.num_channels);
+        let shape3 = (n, self.num_channels, positions);
+        let dy = output_error
+            .as_standard_layout()
+            .into_owned()
+            .into_shape(shape3)
+            .unwrap();
+        let x_hat = cache.x_hat.view().into_shape(shape3).unwrap();
+
+        if self.trainable {
+            self.gamma.grad = (&dy * &x_hat)
+                .sum_axis(Axis(2))
+                .sum_axis(Axis(0))
+                .into_dyn();
+            self.beta.grad = dy.sum_axis(Axis(2)).sum_axis(Axis(0)).into_dyn();
+        }
+
+        let dx_hat = (dy * Self::per_channel(&self.gamma))
+            .into_shape(cache.x_hat.dim())
+            .unwrap();
+        let dx = normalize_backward(&dx_hat, &cache.x_hat, &cache.inv_std);
+
+        if self.trainable {
+            optimizer.update_parameters(&mut self.parameters_mut());
+        }
+        dx.into_shape(cache.shape).unwrap()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        input_shape.to_vec()
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&self.parameters())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut self.parameters_mut(), weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&self.parameters())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut self.parameters_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        2 * self.num_channels
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
@@ -0,0 +1,542 @@
+// bran/src/layers/pool.rs
+
+use super::conv::{Geometry2D, Padding, Padding1D};
+use super::Layer;
+use crate::optimizer::Optimizer;
+use ndarray::{Array1, Array2, Array3, ArrayD, ArrayView3, Axis, Zip};
+use serde::{Deserialize, Serialize};
+
+/// Valores guardados na passagem forward de um pooling para uso na passagem backward.
+#[derive(Clone)]
+struct PoolCache {
+    geometry: Geometry2D,
+    input_shape: Vec<usize>,
+    /// Posição `y * W + x` do máximo de cada janela (apenas no max pooling).
+    argmax: Option<Array3<usize>>,
+}
+
+/// Marca janelas que caíram inteiramente no preenchimento.
+const NO_INDEX: usize = usize::MAX;
+
+/// Max pooling sobre imagens `(B, H, W)`, em paralelo.
+///
+/// # Retorno
+///
+/// A saída `(B, OH, OW)` e a posição do máximo de cada janela. Posições de preenchimento
+/// nunca são escolhidas.
+fn max_pool(images: ArrayView3<f32>, geometry: &Geometry2D) -> (Array3<f32>, Array3<usize>) {
+    let (oh, ow) = geometry.output;
+    let (kh, kw) = geometry.kernel;
+    let width = geometry.input.1;
+    let batch = images.len_of(Axis(0));
+    let mut output = Array3::zeros((batch, oh, ow));
+    let mut argmax = Array3::from_elem((batch, oh, ow), NO_INDEX);
+
+    Zip::from(output.outer_iter_mut())
+        .and(argmax.outer_iter_mut())
+        .and(images.outer_iter())
+        .par_for_each(|mut output, mut argmax, image| {
+            for oy in 0..oh {
+                for ox in 0..ow {
+                    let mut best = f32::NEG_INFINITY;
+                    for ky in 0..kh {
+                        for kx in 0..kw {
+                            if let Some((y, x)) = geometry.source(oy, ox, ky, kx) {
+                                if image[[y, x]] > best || argmax[[oy, ox]] == NO_INDEX {
+                                    best = image[[y, x]];
+                                    argmax[[oy, ox]] = y * width + x;
+                                }
+                            }
+                        }
+                    }
+                    if argmax[[oy, ox]] != NO_INDEX {
+                        output[[oy, ox]] = best;
+                    }
+                }
+            }
+        });
+
+    (output, argmax)
+}
+
+/// Average pooling sobre imagens `(B, H, W)`, em paralelo. A média considera apenas as
+/// posições da janela dentro da entrada, ignorando o preenchimento.
+fn avg_pool(images: ArrayView3<f32>, geometry: &Geometry2D) -> Array3<f32> {
+    let (oh, ow) = geometry.output;
+    let (kh, kw) = geometry.kernel;
+    let batch = images.len_of(Axis(0));
+    let mut output = Array3::zeros((batch, oh, ow));
+
+    Zip::from(output.outer_iter_mut())
+        .and(images.outer_iter())
+        .par_for_each(|mut output, image| {
+            for oy in 0..oh {
+                for ox in 0..ow {
+                    let (mut sum, mut count) = (0.0, 0);
+                    for ky in 0..kh {
+                        for kx in 0..kw {
+                            if let Some((y, x)) = geometry.source(oy, ox, ky, kx) {
+                                sum += image[[y, x]];
+                                count += 1;
+                            }
+                        }
+                    }
+                    if count > 0 {
+                        output[[oy, ox]] = sum / count as f32;
+                    }
+                }
+            }
+        });
+
+    output
+}
+
+/// Passagem backward de um pooling: devolve o erro `(B, OH, OW)` às posições da entrada.
+/// No max pooling, cada erro vai apenas para a posição do máximo; no average pooling, é
+/// dividido igualmente entre as posições da janela.
+fn pool_backward(errors: ArrayView3<f32>, cache: &PoolCache) -> Array3<f32> {
+    let geometry = &cache.geometry;
+    let (h, w) = geometry.input;
+    let (oh, ow) = geometry.output;
+    let (kh, kw) = geometry.kernel;
+    let batch = errors.len_of(Axis(0));
+    let mut input_error = Array3::<f32>::zeros((batch, h, w));
+
+    match &cache.argmax {
+        Some(argmax) => Zip::from(input_error.outer_iter_mut())
+            .and(errors.outer_iter())
+            .and(argmax.outer_iter())
+            .par_for_each(|mut input_error, errors, argmax| {
+                Zip::from(&errors).and(&argmax).for_each(|&e, &index| {
+                    if index != NO_INDEX {
+                        input_error[[index / w, index % w]] += e;
+                    }
+                });
+            }),
+        None => Zip::from(input_error.outer_iter_mut())
+            .and(errors.outer_iter())
+            .par_for_each(|mut input_error, errors| {
+                for oy in 0..oh {
+                    for ox in 0..ow {
+                        let positions: Vec<(usize, usize)> = (0..kh)
+                            .flat_map(|ky| (0..kw).map(move |kx| (ky, kx)))
+                            .filter_map(|(ky, kx)| geometry.source(oy, ox, ky, kx))
+                            .collect();
+                        for &(y, x) in &positions {
+                            input_error[[y, x]] += errors[[oy, ox]] / positions.len() as f32;
+                        }
+                    }
+                }
+            }),
+    }
+
+    input_error
+}
+
+/// Aplica um pooling a cada canal de um tensor `(N, C, H, W)` ou `(N, C, T)`, tratando
+/// sequências como imagens de altura 1.
+fn pool_forward(input: &ArrayD<f32>, geometry: Geometry2D, max: bool) -> (ArrayD<f32>, PoolCache) {
+    let shape = input.shape().to_vec();
+    let (h, w) = geometry.input;
+    let input = input.as_standard_layout();
+    let images = input
+        .view()
+        .into_shape((shape[0] * shape[1], h, w))
+        .unwrap();
+
+    let (output, argmax) = if max {
+        let (output, argmax) = max_pool(images, &geometry);
+        (output, Some(argmax))
+    } else {
+        (avg_pool(images, &geometry), None)
+    };
+
+    let mut output_shape = shape[..2].to_vec();
+    if shape.len() == 4 {
+        output_shape.push(geometry.output.0);
+    }
+    output_shape.push(geometry.output.1);
+    let cache = PoolCache {
+        geometry,
+        input_shape: shape,
+        argmax,
+    };
+    (output.into_shape(output_shape).unwrap(), cache)
+}
+
+/// Passagem backward de `pool_forward`.
+fn pool_backward_dyn(output_error: &ArrayD<f32>, cache: &PoolCache) -> ArrayD<f32> {
+    let (oh, ow) = cache.geometry.output;
+    let shape = &cache.input_shape;
+    let output_error = output_error.as_standard_layout();
+    let errors = output_error
+        .view()
+        .into_shape((shape[0] * shape[1], oh, ow))
+        .unwrap();
+    pool_backward(errors, cache)
+        .into_shape(shape.clone())
+        .unwrap()
+}
+
+/// Gera uma camada de pooling 2D sobre entradas NCHW.
+macro_rules! pool2d {
+    ($(#[$doc:meta])* $name:ident, $max:expr) => {
+        $(#[$doc])*
+        #[derive(Serialize, Deserialize, Clone)]
+        pub struct $name {
+            /// Tamanho da janela `(altura, largura)`.
+            pub kernel_size: (usize, usize),
+            /// Passo da janela `(vertical, horizontal)`.
+            pub stride: (usize, usize),
+            /// Modo de preenchimento das bordas.
+            pub padding: Padding,
+
+            #[serde(skip)]
+            cache: Option<PoolCache>,
+        }
+
+        impl $name {
+            /// Cria uma nova camada de pooling com stride igual ao tamanho da janela e sem preenchimento.
+            ///
+            /// # Argumentos
+            ///
+            /// * `kernel_size` - Tamanho da janela `(altura, largura)`.
+            pub fn new(kernel_size: (usize, usize)) -> Self {
+                $name {
+                    kernel_size,
+                    stride: kernel_size,
+                    padding: Padding::Valid,
+                    cache: None,
+                }
+            }
+
+            /// Define o passo da janela.
+            pub fn with_stride(mut self, stride: (usize, usize)) -> Self {
+                assert!(stride.0 > 0 && stride.1 > 0, "o stride deve ser positivo");
+                self.stride = stride;
+                self
+            }
+
+            /// Define o modo de preenchimento das bordas.
+            pub fn with_padding(mut self, padding: Padding) -> Self {
+                self.padding = padding;
+                self
+            }
+
+            fn geometry(&self, input: (usize, usize)) -> Geometry2D {
+                Geometry2D::new(input, self.kernel_size, self.stride, (1, 1), self.padding)
+            }
+        }
+
+        impl Layer for $name {
+            fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+                assert_eq!(
+                    input.ndim(),
+                    4,
+                    concat!(stringify!($name), " espera uma entrada (N, C, H, W)")
+                );
+                let geometry = self.geometry((input.shape()[2], input.shape()[3]));
+                let (output, cache) = pool_forward(input, geometry, $max);
+                self.cache = Some(cache);
+                output
+            }
+
+            fn backward(
+                &mut self,
+                output_error: &ArrayD<f32>,
+                _optimizer: &mut dyn Optimizer,
+            ) -> ArrayD<f32> {
+                let cache = self.cache.take().expect(concat!(
+                    stringify!($name),
+                    "::backward chamado antes de forward"
+                ));
+                pool_backward_dyn(output_error, &cache)
+            }
+
+            fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+                assert_eq!(
+                    input_shape.len(),
+                    4,
+                    concat!(stringify!($name), " espera uma entrada (N, C, H, W)")
+                );
+                let geometry = self.geometry((input_shape[2], input_shape[3]));
+                vec![
+                    input_shape[0],
+                    input_shape[1],
+                    geometry.output.0,
+                    geometry.output.1,
+                ]
+            }
+        }
+    };
+}
+
+/// Gera uma camada de pooling 1D sobre sequências `(lote, canais, tempo)`.
+macro_rules! pool1d {
+    ($(#[$doc:meta])* $name:ident, $max:expr) => {
+        $(#[$doc])*
+        #[derive(Serialize, Deserialize, Clone)]
+        pub struct $name {
+            /// Tamanho da janela.
+            pub kernel_size: usize,
+            /// Passo da janela.
+            pub stride: usize,
+            /// Modo de preenchimento das bordas.
+            pub padding: Padding1D,
+
+            #[serde(skip)]
+            cache: Option<PoolCache>,
+        }
+
+        impl $name {
+            /// Cria uma nova camada de pooling com stride igual ao tamanho da janela e sem preenchimento.
+            ///
+            /// # Argumentos
+            ///
+            /// * `kernel_size` - Tamanho da janela.
+            pub fn new(kernel_size: usize) -> Self {
+                $name {
+                    kernel_size,
+                    stride: kernel_size,
+                    padding: Padding1D::Valid,
+                    cache: None,
+                }
+            }
+
+            /// Define o passo da janela.
+            pub fn with_stride(mut self, stride: usize) -> Self {
+                assert!(stride > 0, "o stride deve ser positivo");
+                self.stride = stride;
+                self
+            }
+
+            /// Define o modo de preenchimento das bordas.
+            pub fn with_padding(mut self, padding: Padding1D) -> Self {
+                self.padding = padding;
+                self
+            }
+
+            fn geometry(&self, len: usize) -> Geometry2D {
+                Geometry2D::sequence(len, self.kernel_size, self.stride, 1, self.padding)
+            }
+        }
+
+        impl Layer for $name {
+            fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+                assert_eq!(
+                    input.ndim(),
+                    3,
+                    concat!(stringify!($name), " espera uma entrada (N, C, T)")
+                );
+                let geometry = self.geometry(input.shape()[2]);
+                let (output, cache) = pool_forward(input, geometry, $max);
+                self.cache = Some(cache);
+                output
+            }
+
+            fn backward(
+                &mut self,
+                output_error: &ArrayD<f32>,
+                _optimizer: &mut dyn Optimizer,
+            ) -> ArrayD<f32> {
+                let cache = self.cache.take().expect(concat!(
+                    stringify!($name),
+                    "::backward chamado antes de forward"
+                ));
+                pool_backward_dyn(output_error, &cache)
+            }
+
+            fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+                assert_eq!(
+                    input_shape.len(),
+                    3,
+                    concat!(stringify!($name), " espera uma entrada (N, C, T)")
+                );
+                let geometry = self.geometry(input_shape[2]);
+                vec![input_shape[0], input_shape[1], geometry.output.1]
+            }
+        }
+    };
+}
+
+pool2d! {
+    /// Max pooling 2D: cada saída é o maior valor da janela correspondente.
+    /// A posição do máximo é guardada para a passagem backward.
+    MaxPool2D, true
+}
+
+pool2d! {
+    /// Average pooling 2D: cada saída é a média da janela correspondente.
+    AvgPool2D, false
+}
+
+pool1d! {
+    /// Max pooling 1D: cada saída é o maior valor da janela correspondente.
+    /// A posição do máximo é guardada para a passagem backward.
+    MaxPool1D, true
+}
+
+pool1d! {
+    /// Average pooling 1D: cada saída é a média da janela correspondente.
+    AvgPool1D, false
+}
+
+/// Valores guardados na passagem forward de um pooling global.
+#[derive(Clone)]
+struct GlobalPoolCache {
+    input_shape: Vec<usize>,
+    /// Posição do máximo de cada canal (apenas no max pooling global).
+    argmax: Option<Array1<usize>>,
+}
+
+/// Reorganiza `(N, C, ...)` em uma matriz `(N * C, posições)`.
+fn channel_rows(input: &ArrayD<f32>) -> Array2<f32> {
+    assert!(
+        input.ndim() >= 3,
+        "pooling global espera uma entrada (N, C, ...) com ao menos um eixo espacial"
+    );
+    let rows = input.shape()[0] * input.shape()[1];
+    let positions = input.len() / rows.max(1);
+    input
+        .as_standard_layout()
+        .into_owned()
+        .into_shape((rows, positions))
+        .unwrap()
+}
+
+/// Forma `(N, C)` da saída de um pooling global.
+fn global_output_shape(input_shape: &[usize]) -> Vec<usize> {
+    assert!(
+        input_shape.len() >= 3,
+        "pooling global espera uma entrada (N, C, ...) com ao menos um eixo espacial"
+    );
+    input_shape[..2].to_vec()
+}
+
+/// Average pooling global: a média de todos os eixos espaciais de cada canal,
+/// produzindo `(lote, canais)`. Aceita entradas 1D `(N, C, T)` e 2D `(N, C, H, W)`.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct GlobalAvgPool {
+    #[serde(skip)]
+    cache: Option<GlobalPoolCache>,
+}
+
+impl GlobalAvgPool {
+    /// Cria uma nova camada de average pooling global.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Layer for GlobalAvgPool {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let rows = channel_rows(input);
+        let output = rows.mean_axis(Axis(1)).unwrap();
+        self.cache = Some(GlobalPoolCache {
+            input_shape: input.shape().to_vec(),
+            argmax: None,
+        });
+        output
+            .into_shape(global_output_shape(input.shape()))
+            .unwrap()
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        _optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let cache = self
+            .cache
+            .take()
+            .expect("GlobalAvgPool::backward chamado antes de forward");
+        let positions: usize = cache.input_shape[2..].iter().product();
+        let mut expanded = output_error.clone();
+        for _ in 2..cache.input_shape.len() {
+            let axis = Axis(expanded.ndim());
+            expanded = expanded.insert_axis(axis);
+        }
+        (expanded / positions as f32)
+            .broadcast(cache.input_shape.clone())
+            .unwrap()
+            .to_owned()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        global_output_shape(input_shape)
+    }
+}
+
+/// Max pooling global: o maior valor de cada canal sobre todos os eixos espaciais,
+/// produzindo `(lote, canais)`. Aceita entradas 1D `(N, C, T)` e 2D `(N, C, H, W)`.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct GlobalMaxPool {
+    #[serde(skip)]
+    cache: Option<GlobalPoolCache>,
+}
+
+impl GlobalMaxPool {
+    /// Cria uma nova camada de max pooling global.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Layer for GlobalMaxPool {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let rows = channel_rows(input);
+        let mut argmax = Array1::zeros(rows.nrows());
+        let mut output = Array1::zeros(rows.nrows());
+        Zip::from(&mut output)
+            .and(&mut argmax)
+            .and(rows.rows())
+            .par_for_each(|output, argmax, row| {
+                let (index, &value) =
+                    row.iter()
+                        .enumerate()
+                        .fold((0, &f32::NEG_INFINITY), |best, current| {
+                            if current.1 > best.1 {
+                                current
+                            } else {
+                                best
+                            }
+                        });
+                *output = value;
+                *argmax = index;
+            });
+        self.cache = Some(GlobalPoolCache {
+            input_shape: input.shape().to_vec(),
+            argmax: Some(argmax),
+        });
+        output
+            .into_shape(global_output_shape(input.shape()))
+            .unwrap()
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        _optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let cache = self
+            .cache
+            .take()
+            .expect("GlobalMaxPool::backward chamado antes de forward");
+        let argmax = cache.argmax.as_ref().unwrap();
+        let positions: usize = cache.input_shape[2..].iter().product();
+        let mut input_error = Array2::zeros((argmax.len(), positions));
+        for ((mut row, &index), &e) in input_error
+            .rows_mut()
+            .into_iter()
+            .zip(argmax)
+            .zip(output_error.iter())
+        {
+            row[index] = e;
+        }
+        input_error.into_shape(cache.input_shape).unwrap()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        global_output_shape(input_shape)
+    }
+}
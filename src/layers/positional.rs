@@ -0,0 +1,216 @@
+// bran/src/layers/positional.rs
+
+use super::{
+    parameter_moments, parameter_values, set_parameter_moments, set_parameter_values, Layer,
+};
+use crate::optimizer::{Optimizer, Parameter};
+use ndarray::{s, Array2, ArrayD, Axis};
+use rand::distributions::{Distribution, Uniform};
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+
+/// Verifica que a entrada é uma sequência `(N, T, embed_dim)` com `T <= max_len`.
+fn check_sequence(input_shape: &[usize], max_len: usize, embed_dim: usize, name: &str) {
+    assert!(
+        input_shape.len() == 3 && input_shape[2] == embed_dim,
+        "{} espera uma entrada (N, T, {})",
+        name,
+        embed_dim
+    );
+    assert!(
+        input_shape[1] <= max_len,
+        "{} suporta sequências de até {} posições, recebeu {}",
+        name,
+        max_len,
+        input_shape[1]
+    );
+}
+
+/// Codificação posicional senoidal fixa, somada à entrada `(N, T, embed_dim)`:
+///
+/// ```text
+/// PE[t, 2i]     = sin(t / 10000^(2i / embed_dim))
+/// PE[t, 2i + 1] = cos(t / 10000^(2i / embed_dim))
+/// ```
+///
+/// Não tem parâmetros; o erro passa inalterado na passagem backward.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SinusoidalPositionalEncoding {
+    /// Comprimento máximo das sequências.
+    pub max_len: usize,
+    /// Dimensão das entradas.
+    pub embed_dim: usize,
+
+    #[serde(skip)]
+    table: Option<Array2<f32>>,
+}
+
+impl SinusoidalPositionalEncoding {
+    /// Cria uma nova codificação posicional senoidal.
+    ///
+    /// # Argumentos
+    ///
+    /// * `max_len` - Comprimento máximo das sequências.
+    /// * `embed_dim` - Dimensão das entradas.
+    pub fn new(max_len: usize, embed_dim: usize) -> Self {
+        SinusoidalPositionalEncoding {
+            max_len,
+            embed_dim,
+            table: None,
+        }
+    }
+
+    /// Tabela `(max_len, embed_dim)` das codificações, calculada uma única vez.
+    pub fn table(&mut self) -> &Array2<f32> {
+        let embed_dim = self.embed_dim;
+        self.table.get_or_insert_with(|| {
+            Array2::from_shape_fn((self.max_len, embed_dim), |(t, j)| {
+                let frequency = 10000f32.powf(-((j - j % 2) as f32) / embed_dim as f32);
+                let angle = t as f32 * frequency;
+                if j % 2 == 0 {
+                    angle.sin()
+                } else {
+                    angle.cos()
+                }
+            })
+        })
+    }
+}
+
+impl Layer for SinusoidalPositionalEncoding {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        check_sequence(
+            input.shape(),
+            self.max_len,
+            self.embed_dim,
+            "SinusoidalPositionalEncoding",
+        );
+        let steps = input.shape()[1];
+        input + &self.table().slice(s![..steps, ..])
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        _optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        output_error.clone()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        check_sequence(
+            input_shape,
+            self.max_len,
+            self.embed_dim,
+            "SinusoidalPositionalEncoding",
+        );
+        input_shape.to_vec()
+    }
+}
+
+/// Codificação posicional aprendida: soma à entrada `(N, T, embed_dim)` um vetor treinável
+/// por posição, inicializado com valores uniformes em `[-0.05, 0.05]`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LearnedPositionalEncoding {
+    /// Comprimento máximo das sequências.
+    pub max_len: usize,
+    /// Dimensão das entradas.
+    pub embed_dim: usize,
+    /// Vetores de cada posição, de forma `(max_len, embed_dim)`.
+    pub positions: Parameter,
+    trainable: bool,
+
+    #[serde(skip)]
+    steps: Option<usize>,
+}
+
+impl LearnedPositionalEncoding {
+    /// Cria uma nova codificação posicional aprendida.
+    ///
+    /// # Argumentos
+    ///
+    /// * `max_len` - Comprimento máximo das sequências.
+    /// * `embed_dim` - Dimensão das entradas.
+    pub fn new(max_len: usize, embed_dim: usize) -> Self {
+        let dist = Uniform::new_inclusive(-0.05, 0.05);
+        let mut rng = thread_rng();
+        let positions =
+            ArrayD::from_shape_simple_fn(vec![max_len, embed_dim], || dist.sample(&mut rng));
+        LearnedPositionalEncoding {
+            max_len,
+            embed_dim,
+            positions: Parameter::new(positions, false),
+            trainable: true,
+            steps: None,
+        }
+    }
+}
+
+impl Layer for LearnedPositionalEncoding {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        check_sequence(
+            input.shape(),
+            self.max_len,
+            self.embed_dim,
+            "LearnedPositionalEncoding",
+        );
+        let steps = input.shape()[1];
+        self.steps = Some(steps);
+        input + &self.positions.value.slice(s![..steps, ..])
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let steps = self
+            .steps
+            .take()
+            .expect("LearnedPositionalEncoding::backward chamado antes de forward");
+        if self.trainable {
+            let grads = output_error.sum_axis(Axis(0));
+            let rows: Vec<usize> = (0..steps).collect();
+            optimizer.update_rows(&mut self.positions, &rows, &grads);
+        }
+        output_error.clone()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        check_sequence(
+            input_shape,
+            self.max_len,
+            self.embed_dim,
+            "LearnedPositionalEncoding",
+        );
+        input_shape.to_vec()
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&[&self.positions])
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut [&mut self.positions], weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&[&self.positions])
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut [&mut self.positions], moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
@@ -0,0 +1,866 @@
+// bran/src/layers/recurrent.rs
+
+use super::{
+    glorot_uniform, matrix, parameter_moments, parameter_values, project, project_backward,
+    set_parameter_moments, set_parameter_values, Layer,
+};
+use crate::activations::{Activation, ActivationType, Sigmoid};
+use crate::optimizer::{Optimizer, Parameter};
+use ndarray::{s, stack, Array1, Array2, Array3, ArrayD, Axis, Ix1, Ix2, Ix3};
+use serde::{Deserialize, Serialize};
+
+/// Interface comum às camadas recorrentes, que processam sequências `(N, T, features)`.
+///
+/// O estado inicial e o estado final são tensores `(N, hidden_size)`: um para `SimpleRNN` e
+/// `GRU` (`[h]`) e dois para `LSTM` (`[h, c]`).
+pub trait Recurrent: Layer {
+    /// Dimensão do estado oculto.
+    fn hidden_size(&self) -> usize;
+
+    /// Indica se a saída contém todos os instantes `(N, T, H)` ou apenas o último `(N, H)`.
+    fn return_sequences(&self) -> bool;
+
+    /// Define o estado inicial usado pelas próximas passagens forward, no lugar de zeros.
+    fn set_initial_state(&mut self, state: Vec<Array2<f32>>);
+
+    /// Volta a usar o estado inicial zero.
+    fn clear_initial_state(&mut self);
+
+    /// Estado após o último instante da última passagem forward.
+    fn final_state(&self) -> Option<Vec<Array2<f32>>>;
+
+    /// Gradiente da perda em relação ao estado inicial, calculado na última passagem backward.
+    fn initial_state_grad(&self) -> Option<Vec<Array2<f32>>>;
+}
+
+/// Estados de entrada e saída de uma camada recorrente (não serializados).
+#[derive(Clone, Default)]
+struct SequenceState {
+    initial: Option<Vec<Array2<f32>>>,
+    last: Option<Vec<Array2<f32>>>,
+    initial_grad: Option<Vec<Array2<f32>>>,
+}
+
+impl SequenceState {
+    /// Componente `index` do estado inicial, ou zeros se nenhum foi definido.
+    fn initial(&self, index: usize, batch: usize, hidden_size: usize) -> Array2<f32> {
+        match &self.initial {
+            Some(state) => {
+                assert_eq!(
+                    state[index].dim(),
+                    (batch, hidden_size),
+                    "estado inicial incompatível com o lote"
+                );
+                state[index].clone()
+            }
+            None => Array2::zeros((batch, hidden_size)),
+        }
+    }
+}
+
+/// Gera os construtores de configuração e a implementação de `Recurrent` de uma camada recorrente.
+macro_rules! recurrent_common {
+    ($name:ident, $num_states:expr) => {
+        impl $name {
+            /// Define se a saída contém todos os instantes `(N, T, H)` (`true`) ou apenas o
+            /// estado do último instante `(N, H)` (`false`, padrão).
+            pub fn with_return_sequences(mut self, return_sequences: bool) -> Self {
+                self.return_sequences = return_sequences;
+                self
+            }
+
+            /// Ativa o BPTT truncado: a sequência é dividida em janelas de `steps` instantes,
+            /// a partir do início, e o erro não se propaga de uma janela para a anterior.
+            pub fn with_truncation(mut self, steps: usize) -> Self {
+                assert!(steps > 0, "a janela do BPTT truncado deve ser positiva");
+                self.truncate = Some(steps);
+                self
+            }
+        }
+
+        impl Recurrent for $name {
+            fn hidden_size(&self) -> usize {
+                self.hidden_size
+            }
+
+            fn return_sequences(&self) -> bool {
+                self.return_sequences
+            }
+
+            fn set_initial_state(&mut self, state: Vec<Array2<f32>>) {
+                assert_eq!(
+                    state.len(),
+                    $num_states,
+                    concat!(stringify!($name), " espera {} tensores de estado"),
+                    $num_states
+                );
+                self.state.initial = Some(state);
+            }
+
+            fn clear_initial_state(&mut self) {
+                self.state.initial = None;
+            }
+
+            fn final_state(&self) -> Option<Vec<Array2<f32>>> {
+                self.state.last.clone()
+            }
+
+            fn initial_state_grad(&self) -> Option<Vec<Array2<f32>>> {
+                self.state.initial_grad.clone()
+            }
+        }
+    };
+}
+
+/// Converte a entrada em uma sequência `(N, T, input_size)`, verificando a forma.
+fn sequence_input(input: &ArrayD<f32>, input_size: usize, name: &str) -> Array3<f32> {
+    let input = input
+        .view()
+        .into_dimensionality::<Ix3>()
+        .unwrap_or_else(|_| panic!("{} espera uma entrada (N, T, features)", name));
+    assert_eq!(
+        input.len_of(Axis(2)),
+        input_size,
+        "{} espera {} features por instante",
+        name,
+        input_size
+    );
+    input.as_standard_layout().into_owned()
+}
+
+/// Forma da saída de uma camada recorrente para uma entrada `(N, T, F)`.
+fn sequence_output_shape(
+    input_shape: &[usize],
+    hidden_size: usize,
+    return_sequences: bool,
+    name: &str,
+) -> Vec<usize> {
+    assert_eq!(
+        input_shape.len(),
+        3,
+        "{} espera uma entrada (N, T, features)",
+        name
+    );
+    if return_sequences {
+        vec![input_shape[0], input_shape[1], hidden_size]
+    } else {
+        vec![input_shape[0], hidden_size]
+    }
+}
+
+/// Saída da camada a partir dos estados ocultos `h_1..h_T`.
+fn sequence_output(hidden: &[Array2<f32>], return_sequences: bool) -> ArrayD<f32> {
+    if return_sequences {
+        let views: Vec<_> = hidden.iter().map(|h| h.view()).collect();
+        stack(Axis(1), &views).unwrap().into_dyn()
+    } else {
+        hidden.last().unwrap().clone().into_dyn()
+    }
+}
+
+/// Erro de saída de cada instante `(N, T, H)`. No modo de último estado, só o último instante
+/// recebe erro.
+fn step_errors(
+    output_error: &ArrayD<f32>,
+    (batch, steps, hidden_size): (usize, usize, usize),
+    return_sequences: bool,
+) -> Array3<f32> {
+    if return_sequences {
+        return output_error
+            .view()
+            .into_dimensionality::<Ix3>()
+            .expect("erro de saída deve ter forma (N, T, H)")
+            .to_owned();
+    }
+    let mut errors = Array3::zeros((batch, steps, hidden_size));
+    errors.slice_mut(s![.., steps - 1, ..]).assign(
+        &output_error
+            .view()
+            .into_dimensionality::<Ix2>()
+            .expect("erro de saída deve ter forma (N, H)"),
+    );
+    errors
+}
+
+/// Indica se o erro deixa de ser propagado do instante `t` para o anterior.
+fn truncated(truncate: Option<usize>, t: usize) -> bool {
+    truncate.is_some_and(|steps| t > 0 && t.is_multiple_of(steps))
+}
+
+/// Valores guardados na passagem forward da `SimpleRNN`.
+#[derive(Clone)]
+struct SimpleRnnCache {
+    input: Array3<f32>,
+    /// Estados `h_0..h_T`.
+    hidden: Vec<Array2<f32>>,
+    /// Pré-ativações de cada instante.
+    z: Vec<Array2<f32>>,
+}
+
+/// Camada recorrente simples (Elman): `h_t = f(W x_t + U h_{t-1} + b)`.
+///
+/// Recebe sequências `(N, T, input_size)` e é treinada com retropropagação no tempo (BPTT).
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SimpleRNN {
+    /// Número de features de cada instante.
+    pub input_size: usize,
+    /// Dimensão do estado oculto.
+    pub hidden_size: usize,
+    /// Pesos da entrada, de forma `(hidden_size, input_size)`.
+    pub kernel: Parameter,
+    /// Pesos recorrentes, de forma `(hidden_size, hidden_size)`.
+    pub recurrent_kernel: Parameter,
+    /// Vieses, de forma `(hidden_size,)`.
+    pub bias: Parameter,
+    /// Função de ativação do estado.
+    pub activation_type: ActivationType,
+    /// Se a saída contém todos os instantes ou apenas o último estado.
+    pub return_sequences: bool,
+    /// Janela do BPTT truncado, se ativado.
+    pub truncate: Option<usize>,
+    trainable: bool,
+
+    #[serde(skip)]
+    state: SequenceState,
+    #[serde(skip)]
+    cache: Option<SimpleRnnCache>,
+}
+
+impl SimpleRNN {
+    /// Cria uma nova camada recorrente simples.
+    ///
+    /// # Argumentos
+    ///
+    /// * `input_size` - Número de features de cada instante.
+    /// * `hidden_size` - Dimensão do estado oculto.
+    /// * `activation_type` - Função de ativação do estado (normalmente `Tanh`).
+    pub fn new(input_size: usize, hidden_size: usize, activation_type: ActivationType) -> Self {
+        SimpleRNN {
+            input_size,
+            hidden_size,
+            kernel: Parameter::new(
+                glorot_uniform(&[hidden_size, input_size], input_size, hidden_size),
+                true,
+            ),
+            recurrent_kernel: Parameter::new(
+                glorot_uniform(&[hidden_size, hidden_size], hidden_size, hidden_size),
+                true,
+            ),
+            bias: Parameter::new(ArrayD::zeros(vec![hidden_size]), false),
+            activation_type,
+            return_sequences: false,
+            truncate: None,
+            trainable: true,
+            state: SequenceState::default(),
+            cache: None,
+        }
+    }
+
+    fn parameters(&self) -> Vec<&Parameter> {
+        vec![&self.kernel, &self.recurrent_kernel, &self.bias]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        vec![&mut self.kernel, &mut self.recurrent_kernel, &mut self.bias]
+    }
+}
+
+recurrent_common!(SimpleRNN, 1);
+
+impl Layer for SimpleRNN {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let input = sequence_input(input, self.input_size, "SimpleRNN");
+        let (batch, steps, _) = input.dim();
+        let projection = project(&input, &self.kernel, &self.bias);
+        let recurrent = matrix(&self.recurrent_kernel);
+
+        let mut hidden = vec![self.state.initial(0, batch, self.hidden_size)];
+        let mut zs = Vec::with_capacity(steps);
+        for t in 0..steps {
+            let z = &projection.slice(s![.., t, ..]) + &hidden[t].dot(&recurrent.t());
+            hidden.push(z.mapv(|x| self.activation_type.activate(x)));
+            zs.push(z);
+        }
+
+        let output = sequence_output(&hidden[1..], self.return_sequences);
+        self.state.last = Some(vec![hidden[steps].clone()]);
+        self.cache = Some(SimpleRnnCache {
+            input,
+            hidden,
+            z: zs,
+        });
+        output
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let cache = self
+            .cache
+            .take()
+            .expect("SimpleRNN::backward chamado antes de forward");
+        let (batch, steps, _) = cache.input.dim();
+        let errors = step_errors(
+            output_error,
+            (batch, steps, self.hidden_size),
+            self.return_sequences,
+        );
+        let recurrent = matrix(&self.recurrent_kernel).to_owned();
+
+        let mut d_projection = Array3::zeros((batch, steps, self.hidden_size));
+        let mut d_recurrent = Array2::zeros(recurrent.raw_dim());
+        let mut carry = Array2::zeros((batch, self.hidden_size));
+        for t in (0..steps).rev() {
+            let dh = carry + errors.slice(s![.., t, ..]);
+            let dz = dh * cache.z[t].mapv(|x| self.activation_type.derivative(x));
+            if self.trainable {
+                d_recurrent += &dz.t().dot(&cache.hidden[t]);
+            }
+            carry = dz.dot(&recurrent);
+            d_projection.slice_mut(s![.., t, ..]).assign(&dz);
+            if truncated(self.truncate, t) {
+                carry.fill(0.0);
+            }
+        }
+        self.state.initial_grad = Some(vec![carry]);
+
+        if self.trainable {
+            self.recurrent_kernel.grad = d_recurrent.into_dyn();
+        }
+        let input_error = project_backward(
+            &d_projection,
+            &cache.input,
+            &mut self.kernel,
+            &mut self.bias,
+            self.trainable,
+        );
+        if self.trainable {
+            optimizer.update_parameters(&mut self.parameters_mut());
+        }
+        input_error.into_dyn()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        sequence_output_shape(
+            input_shape,
+            self.hidden_size,
+            self.return_sequences,
+            "SimpleRNN",
+        )
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&self.parameters())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut self.parameters_mut(), weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&self.parameters())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut self.parameters_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.parameters().iter().map(|param| param.len()).sum()
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
+
+/// Valores guardados na passagem forward da `LSTM`.
+#[derive(Clone)]
+struct LstmCache {
+    input: Array3<f32>,
+    /// Estados ocultos `h_0..h_T`.
+    hidden: Vec<Array2<f32>>,
+    /// Estados da célula `c_0..c_T`.
+    cell: Vec<Array2<f32>>,
+    /// Portas ativadas `[i, f, g, o]` de cada instante, de forma `(N, 4H)`.
+    gates: Vec<Array2<f32>>,
+}
+
+/// Camada LSTM (long short-term memory), com portas de entrada, esquecimento, candidata e
+/// saída:
+///
+/// ```text
+/// [i, f, g, o] = [σ, σ, tanh, σ](W x_t + U h_{t-1} + b)
+/// c_t = f * c_{t-1} + i * g
+/// h_t = o * tanh(c_t)
+/// ```
+///
+/// Os pesos das quatro portas são concatenados no primeiro eixo, nessa ordem. O viés da porta
+/// de esquecimento começa em 1.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LSTM {
+    /// Número de features de cada instante.
+    pub input_size: usize,
+    /// Dimensão do estado oculto e da célula.
+    pub hidden_size: usize,
+    /// Pesos da entrada, de forma `(4 * hidden_size, input_size)`.
+    pub kernel: Parameter,
+    /// Pesos recorrentes, de forma `(4 * hidden_size, hidden_size)`.
+    pub recurrent_kernel: Parameter,
+    /// Vieses, de forma `(4 * hidden_size,)`.
+    pub bias: Parameter,
+    /// Se a saída contém todos os instantes ou apenas o último estado.
+    pub return_sequences: bool,
+    /// Janela do BPTT truncado, se ativado.
+    pub truncate: Option<usize>,
+    trainable: bool,
+
+    #[serde(skip)]
+    state: SequenceState,
+    #[serde(skip)]
+    cache: Option<LstmCache>,
+}
+
+impl LSTM {
+    /// Cria uma nova camada LSTM.
+    ///
+    /// # Argumentos
+    ///
+    /// * `input_size` - Número de features de cada instante.
+    /// * `hidden_size` - Dimensão do estado oculto e da célula.
+    pub fn new(input_size: usize, hidden_size: usize) -> Self {
+        let gates = 4 * hidden_size;
+        let mut bias = Array1::zeros(gates);
+        bias.slice_mut(s![hidden_size..2 * hidden_size]).fill(1.0);
+        LSTM {
+            input_size,
+            hidden_size,
+            kernel: Parameter::new(
+                glorot_uniform(&[gates, input_size], input_size, gates),
+                true,
+            ),
+            recurrent_kernel: Parameter::new(
+                glorot_uniform(&[gates, hidden_size], hidden_size, gates),
+                true,
+            ),
+            bias: Parameter::new(bias.into_dyn(), false),
+            return_sequences: false,
+            truncate: None,
+            trainable: true,
+            state: SequenceState::default(),
+            cache: None,
+        }
+    }
+
+    fn parameters(&self) -> Vec<&Parameter> {
+        vec![&self.kernel, &self.recurrent_kernel, &self.bias]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        vec![&mut self.kernel, &mut self.recurrent_kernel, &mut self.bias]
+    }
+}
+
+recurrent_common!(LSTM, 2);
+
+impl Layer for LSTM {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let input = sequence_input(input, self.input_size, "LSTM");
+        let (batch, steps, _) = input.dim();
+        let h = self.hidden_size;
+        let projection = project(&input, &self.kernel, &self.bias);
+        let recurrent = matrix(&self.recurrent_kernel);
+
+        let mut hidden = vec![self.state.initial(0, batch, h)];
+        let mut cell = vec![self.state.initial(1, batch, h)];
+        let mut all_gates = Vec::with_capacity(steps);
+        for t in 0..steps {
+            let mut gates = &projection.slice(s![.., t, ..]) + &hidden[t].dot(&recurrent.t());
+            gates
+                .slice_mut(s![.., ..2 * h])
+                .mapv_inplace(|x| Sigmoid.activate(x));
+            gates
+                .slice_mut(s![.., 2 * h..3 * h])
+                .mapv_inplace(f32::tanh);
+            gates
+                .slice_mut(s![.., 3 * h..])
+                .mapv_inplace(|x| Sigmoid.activate(x));
+
+            let (i, f) = (gates.slice(s![.., ..h]), gates.slice(s![.., h..2 * h]));
+            let (g, o) = (
+                gates.slice(s![.., 2 * h..3 * h]),
+                gates.slice(s![.., 3 * h..]),
+            );
+            let c = &f * &cell[t] + &i * &g;
+            hidden.push(&o * &c.mapv(f32::tanh));
+            cell.push(c);
+            all_gates.push(gates);
+        }
+
+        let output = sequence_output(&hidden[1..], self.return_sequences);
+        self.state.last = Some(vec![hidden[steps].clone(), cell[steps].clone()]);
+        self.cache = Some(LstmCache {
+            input,
+            hidden,
+            cell,
+            gates: all_gates,
+        });
+        output
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let cache = self
+            .cache
+            .take()
+            .expect("LSTM::backward chamado antes de forward");
+        let (batch, steps, _) = cache.input.dim();
+        let h = self.hidden_size;
+        let errors = step_errors(output_error, (batch, steps, h), self.return_sequences);
+        let recurrent = matrix(&self.recurrent_kernel).to_owned();
+
+        let mut d_projection = Array3::zeros((batch, steps, 4 * h));
+        let mut d_recurrent = Array2::zeros(recurrent.raw_dim());
+        let mut carry_h = Array2::zeros((batch, h));
+        let mut carry_c = Array2::zeros((batch, h));
+        for t in (0..steps).rev() {
+            let gates = &cache.gates[t];
+            let (i, f) = (gates.slice(s![.., ..h]), gates.slice(s![.., h..2 * h]));
+            let (g, o) = (
+                gates.slice(s![.., 2 * h..3 * h]),
+                gates.slice(s![.., 3 * h..]),
+            );
+            let tanh_c = cache.cell[t + 1].mapv(f32::tanh);
+
+            let dh = carry_h + errors.slice(s![.., t, ..]);
+            let dc = carry_c + &dh * &o * &tanh_c.mapv(|x| 1.0 - x * x);
+
+            let mut da = Array2::zeros((batch, 4 * h));
+            da.slice_mut(s![.., ..h])
+                .assign(&(&dc * &g * &i.mapv(|x| x * (1.0 - x))));
+            da.slice_mut(s![.., h..2 * h])
+                .assign(&(&dc * &cache.cell[t] * f.mapv(|x| x * (1.0 - x))));
+            da.slice_mut(s![.., 2 * h..3 * h])
+                .assign(&(&dc * &i * g.mapv(|x| 1.0 - x * x)));
+            da.slice_mut(s![.., 3 * h..])
+                .assign(&(&dh * &tanh_c * o.mapv(|x| x * (1.0 - x))));
+
+            if self.trainable {
+                d_recurrent += &da.t().dot(&cache.hidden[t]);
+            }
+            carry_h = da.dot(&recurrent);
+            carry_c = dc * f;
+            d_projection.slice_mut(s![.., t, ..]).assign(&da);
+            if truncated(self.truncate, t) {
+                carry_h.fill(0.0);
+                carry_c.fill(0.0);
+            }
+        }
+        self.state.initial_grad = Some(vec![carry_h, carry_c]);
+
+        if self.trainable {
+            self.recurrent_kernel.grad = d_recurrent.into_dyn();
+        }
+        let input_error = project_backward(
+            &d_projection,
+            &cache.input,
+            &mut self.kernel,
+            &mut self.bias,
+            self.trainable,
+        );
+        if self.trainable {
+            optimizer.update_parameters(&mut self.parameters_mut());
+        }
+        input_error.into_dyn()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        sequence_output_shape(input_shape, self.hidden_size, self.return_sequences, "LSTM")
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&self.parameters())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut self.parameters_mut(), weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&self.parameters())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut self.parameters_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.parameters().iter().map(|param| param.len()).sum()
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
+
+/// Valores guardados na passagem forward da `GRU`.
+#[derive(Clone)]
+struct GruCache {
+    input: Array3<f32>,
+    /// Estados `h_0..h_T`.
+    hidden: Vec<Array2<f32>>,
+    /// Portas ativadas `[r, z, n]` de cada instante, de forma `(N, 3H)`.
+    gates: Vec<Array2<f32>>,
+    /// Projeções recorrentes `U h_{t-1} + b_h` de cada instante, de forma `(N, 3H)`.
+    recurrent: Vec<Array2<f32>>,
+}
+
+/// Camada GRU (gated recurrent unit), com portas de reinício e de atualização:
+///
+/// ```text
+/// r = σ(W_r x_t + b_r + U_r h_{t-1} + c_r)
+/// z = σ(W_z x_t + b_z + U_z h_{t-1} + c_z)
+/// n = tanh(W_n x_t + b_n + r * (U_n h_{t-1} + c_n))
+/// h_t = (1 - z) * n + z * h_{t-1}
+/// ```
+///
+/// Os pesos das três portas são concatenados no primeiro eixo, nessa ordem. A porta de
+/// reinício é aplicada após a projeção recorrente, por isso há vieses separados para a
+/// entrada (`b`) e para o estado (`c`).
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GRU {
+    /// Número de features de cada instante.
+    pub input_size: usize,
+    /// Dimensão do estado oculto.
+    pub hidden_size: usize,
+    /// Pesos da entrada, de forma `(3 * hidden_size, input_size)`.
+    pub kernel: Parameter,
+    /// Pesos recorrentes, de forma `(3 * hidden_size, hidden_size)`.
+    pub recurrent_kernel: Parameter,
+    /// Vieses da entrada, de forma `(3 * hidden_size,)`.
+    pub bias: Parameter,
+    /// Vieses do estado, de forma `(3 * hidden_size,)`.
+    pub recurrent_bias: Parameter,
+    /// Se a saída contém todos os instantes ou apenas o último estado.
+    pub return_sequences: bool,
+    /// Janela do BPTT truncado, se ativado.
+    pub truncate: Option<usize>,
+    trainable: bool,
+
+    #[serde(skip)]
+    state: SequenceState,
+    #[serde(skip)]
+    cache: Option<GruCache>,
+}
+
+impl GRU {
+    /// Cria uma nova camada GRU.
+    ///
+    /// # Argumentos
+    ///
+    /// * `input_size` - Número de features de cada instante.
+    /// * `hidden_size` - Dimensão do estado oculto.
+    pub fn new(input_size: usize, hidden_size: usize) -> Self {
+        let gates = 3 * hidden_size;
+        GRU {
+            input_size,
+            hidden_size,
+            kernel: Parameter::new(
+                glorot_uniform(&[gates, input_size], input_size, gates),
+                true,
+            ),
+            recurrent_kernel: Parameter::new(
+                glorot_uniform(&[gates, hidden_size], hidden_size, gates),
+                true,
+            ),
+            bias: Parameter::new(ArrayD::zeros(vec![gates]), false),
+            recurrent_bias: Parameter::new(ArrayD::zeros(vec![gates]), false),
+            return_sequences: false,
+            truncate: None,
+            trainable: true,
+            state: SequenceState::default(),
+            cache: None,
+        }
+    }
+
+    fn parameters(&self) -> Vec<&Parameter> {
+        vec![
+            &self.kernel,
+            &self.recurrent_kernel,
+            &self.bias,
+            &self.recurrent_bias,
+        ]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        vec![
+            &mut self.kernel,
+            &mut self.recurrent_kernel,
+            &mut self.bias,
+            &mut self.recurrent_bias,
+        ]
+    }
+}
+
+recurrent_common!(GRU, 1);
+
+impl Layer for GRU {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let input = sequence_input(input, self.input_size, "GRU");
+        let (batch, steps, _) = input.dim();
+        let h = self.hidden_size;
+        let projection = project(&input, &self.kernel, &self.bias);
+        let recurrent = matrix(&self.recurrent_kernel);
+        let recurrent_bias = self
+            .recurrent_bias
+            .value
+            .view()
+            .into_dimensionality::<Ix1>()
+            .unwrap();
+
+        let mut hidden = vec![self.state.initial(0, batch, h)];
+        let mut all_gates = Vec::with_capacity(steps);
+        let mut all_recurrent = Vec::with_capacity(steps);
+        for t in 0..steps {
+            let x = projection.slice(s![.., t, ..]);
+            let hp = hidden[t].dot(&recurrent.t()) + recurrent_bias;
+
+            let mut gates = &x + &hp;
+            gates
+                .slice_mut(s![.., ..2 * h])
+                .mapv_inplace(|x| Sigmoid.activate(x));
+            let n = (&x.slice(s![.., 2 * h..])
+                + &gates.slice(s![.., ..h]) * &hp.slice(s![.., 2 * h..]))
+                .mapv(f32::tanh);
+            gates.slice_mut(s![.., 2 * h..]).assign(&n);
+
+            let z = gates.slice(s![.., h..2 * h]);
+            hidden.push(z.mapv(|z| 1.0 - z) * &n + &z * &hidden[t]);
+            all_gates.push(gates);
+            all_recurrent.push(hp);
+        }
+
+        let output = sequence_output(&hidden[1..], self.return_sequences);
+        self.state.last = Some(vec![hidden[steps].clone()]);
+        self.cache = Some(GruCache {
+            input,
+            hidden,
+            gates: all_gates,
+            recurrent: all_recurrent,
+        });
+        output
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let cache = self
+            .cache
+            .take()
+            .expect("GRU::backward chamado antes de forward");
+        let (batch, steps, _) = cache.input.dim();
+        let h = self.hidden_size;
+        let errors = step_errors(output_error, (batch, steps, h), self.return_sequences);
+        let recurrent = matrix(&self.recurrent_kernel).to_owned();
+
+        let mut d_projection = Array3::zeros((batch, steps, 3 * h));
+        let mut d_recurrent = Array2::zeros(recurrent.raw_dim());
+        let mut d_recurrent_bias = Array1::zeros(3 * h);
+        let mut carry = Array2::zeros((batch, h));
+        for t in (0..steps).rev() {
+            let gates = &cache.gates[t];
+            let (r, z, n) = (
+                gates.slice(s![.., ..h]),
+                gates.slice(s![.., h..2 * h]),
+                gates.slice(s![.., 2 * h..]),
+            );
+            let hp_n = cache.recurrent[t].slice(s![.., 2 * h..]);
+
+            let dh = carry + errors.slice(s![.., t, ..]);
+            let da_n = &dh * &z.mapv(|z| 1.0 - z) * n.mapv(|n| 1.0 - n * n);
+            let da_z = &dh * &(&cache.hidden[t] - &n) * z.mapv(|z| z * (1.0 - z));
+            let da_r = &da_n * &hp_n * r.mapv(|r| r * (1.0 - r));
+
+            let mut dx = Array2::zeros((batch, 3 * h));
+            dx.slice_mut(s![.., ..h]).assign(&da_r);
+            dx.slice_mut(s![.., h..2 * h]).assign(&da_z);
+            dx.slice_mut(s![.., 2 * h..]).assign(&da_n);
+            let mut dhp = dx.clone();
+            dhp.slice_mut(s![.., 2 * h..]).assign(&(&da_n * &r));
+
+            if self.trainable {
+                d_recurrent += &dhp.t().dot(&cache.hidden[t]);
+                d_recurrent_bias += &dhp.sum_axis(Axis(0));
+            }
+            carry = dh * z + dhp.dot(&recurrent);
+            d_projection.slice_mut(s![.., t, ..]).assign(&dx);
+            if truncated(self.truncate, t) {
+                carry.fill(0.0);
+            }
+        }
+        self.state.initial_grad = Some(vec![carry]);
+
+        if self.trainable {
+            self.recurrent_kernel.grad = d_recurrent.into_dyn();
+            self.recurrent_bias.grad = d_recurrent_bias.into_dyn();
+        }
+        let input_error = project_backward(
+            &d_projection,
+            &cache.input,
+            &mut self.kernel,
+            &mut self.bias,
+            self.trainable,
+        );
+        if self.trainable {
+            optimizer.update_parameters(&mut self.parameters_mut());
+        }
+        input_error.into_dyn()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        sequence_output_shape(input_shape, self.hidden_size, self.return_sequences, "GRU")
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&self.parameters())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut self.parameters_mut(), weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&self.parameters())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut self.parameters_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.parameters().iter().map(|param| param.len()).sum()
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
@@ -0,0 +1,124 @@
+// bran/src/layers/reshape.rs
+
+use super::Layer;
+use crate::optimizer::Optimizer;
+use ndarray::ArrayD;
+use serde::{Deserialize, Serialize};
+
+/// Reorganiza um tensor contíguo para a forma indicada, sem copiar quando possível.
+fn reshape(input: &ArrayD<f32>, shape: Vec<usize>) -> ArrayD<f32> {
+    input
+        .as_standard_layout()
+        .into_owned()
+        .into_shape(shape)
+        .expect("o número de elementos não corresponde à nova forma")
+}
+
+/// Achata todos os eixos após o lote: `(N, d1, d2, ...)` → `(N, d1 * d2 * ...)`.
+///
+/// Usada para ligar uma sequência de camadas convolucionais a camadas densas.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Flatten {
+    #[serde(skip)]
+    input_shape: Option<Vec<usize>>,
+}
+
+impl Flatten {
+    /// Cria uma nova camada de achatamento.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Layer for Flatten {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let output_shape = self.output_shape(input.shape());
+        self.input_shape = Some(input.shape().to_vec());
+        reshape(input, output_shape)
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        _optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let input_shape = self
+            .input_shape
+            .take()
+            .expect("Flatten::backward chamado antes de forward");
+        reshape(output_error, input_shape)
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        assert!(
+            !input_shape.is_empty(),
+            "Flatten espera ao menos o eixo do lote"
+        );
+        vec![input_shape[0], input_shape[1..].iter().product()]
+    }
+}
+
+/// Reorganiza os eixos após o lote para a forma `target_shape`: `(N, ...)` → `(N, target_shape...)`.
+///
+/// O número de elementos de cada amostra deve ser preservado.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Reshape {
+    /// Forma de cada amostra na saída (sem o eixo do lote).
+    pub target_shape: Vec<usize>,
+
+    #[serde(skip)]
+    input_shape: Option<Vec<usize>>,
+}
+
+impl Reshape {
+    /// Cria uma nova camada de reorganização.
+    ///
+    /// # Argumentos
+    ///
+    /// * `target_shape` - Forma de cada amostra na saída, sem o eixo do lote.
+    pub fn new(target_shape: &[usize]) -> Self {
+        Reshape {
+            target_shape: target_shape.to_vec(),
+            input_shape: None,
+        }
+    }
+}
+
+impl Layer for Reshape {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let output_shape = self.output_shape(input.shape());
+        self.input_shape = Some(input.shape().to_vec());
+        reshape(input, output_shape)
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        _optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let input_shape = self
+            .input_shape
+            .take()
+            .expect("Reshape::backward chamado antes de forward");
+        reshape(output_error, input_shape)
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        assert!(
+            !input_shape.is_empty(),
+            "Reshape espera ao menos o eixo do lote"
+        );
+        let features: usize = input_shape[1..].iter().product();
+        let target: usize = self.target_shape.iter().product();
+        assert_eq!(
+            features,
+            target,
+            "Reshape não pode converter {:?} em {:?}",
+            &input_shape[1..],
+            self.target_shape
+        );
+        std::iter::once(input_shape[0])
+            .chain(self.target_shape.iter().copied())
+            .collect()
+    }
+}
@@ -0,0 +1,529 @@
+// bran/src/layers/transformer.rs
+
+use super::{
+    any_trainable, collect_moments, collect_weights, distribute_moments, distribute_weights,
+    glorot_uniform, parameter_moments, parameter_values, project, project_backward,
+    set_parameter_moments, set_parameter_values, Dropout, Layer, LayerNorm, MultiHeadAttention,
+};
+use crate::activations::{Activation, ActivationType};
+use crate::optimizer::{Optimizer, Parameter};
+use ndarray::{Array3, ArrayD, Ix3};
+use serde::{Deserialize, Serialize};
+
+/// Converte um tensor em uma sequência `(N, T, E)`.
+fn sequence(x: &ArrayD<f32>, name: &str) -> Array3<f32> {
+    x.view()
+        .into_dimensionality::<Ix3>()
+        .unwrap_or_else(|_| panic!("{} espera uma entrada (N, T, embed_dim)", name))
+        .as_standard_layout()
+        .into_owned()
+}
+
+/// Rede feed-forward aplicada a cada posição: `W2 f(W1 x + b1) + b2`.
+#[derive(Serialize, Deserialize, Clone)]
+struct FeedForward {
+    w1: Parameter,
+    b1: Parameter,
+    w2: Parameter,
+    b2: Parameter,
+    activation_type: ActivationType,
+    trainable: bool,
+
+    /// Entrada, pré-ativação e ativação da camada oculta.
+    #[serde(skip)]
+    cache: Option<(Array3<f32>, Array3<f32>, Array3<f32>)>,
+}
+
+impl FeedForward {
+    fn new(embed_dim: usize, ff_dim: usize, activation_type: ActivationType) -> Self {
+        FeedForward {
+            w1: Parameter::new(
+                glorot_uniform(&[ff_dim, embed_dim], embed_dim, ff_dim),
+                true,
+            ),
+            b1: Parameter::new(ArrayD::zeros(vec![ff_dim]), false),
+            w2: Parameter::new(
+                glorot_uniform(&[embed_dim, ff_dim], ff_dim, embed_dim),
+                true,
+            ),
+            b2: Parameter::new(ArrayD::zeros(vec![embed_dim]), false),
+            activation_type,
+            trainable: true,
+            cache: None,
+        }
+    }
+
+    fn parameters(&self) -> Vec<&Parameter> {
+        vec![&self.w1, &self.b1, &self.w2, &self.b2]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<&mut Parameter> {
+        vec![&mut self.w1, &mut self.b1, &mut self.w2, &mut self.b2]
+    }
+}
+
+impl Layer for FeedForward {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let input = sequence(input, "FeedForward");
+        let z = project(&input, &self.w1, &self.b1);
+        let hidden = z.mapv(|x| self.activation_type.activate(x));
+        let output = project(&hidden, &self.w2, &self.b2);
+        self.cache = Some((input, z, hidden));
+        output.into_dyn()
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let (input, z, hidden) = self
+            .cache
+            .take()
+            .expect("FeedForward::backward chamado antes de forward");
+        let output_error = sequence(output_error, "FeedForward");
+        let d_hidden = project_backward(
+            &output_error,
+            &hidden,
+            &mut self.w2,
+            &mut self.b2,
+            self.trainable,
+        );
+        let dz = d_hidden * z.mapv(|x| self.activation_type.derivative(x));
+        let input_error = project_backward(&dz, &input, &mut self.w1, &mut self.b1, self.trainable);
+        if self.trainable {
+            optimizer.update_parameters(&mut self.parameters_mut());
+        }
+        input_error.into_dyn()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        input_shape.to_vec()
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        parameter_values(&self.parameters())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        set_parameter_values(&mut self.parameters_mut(), weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        parameter_moments(&self.parameters())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        set_parameter_moments(&mut self.parameters_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.parameters().iter().map(|param| param.len()).sum()
+    }
+
+    fn trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
+
+/// Conexão residual com normalização de camada e dropout em torno de uma subcamada.
+///
+/// Pós-normalização: `norm(x + dropout(f(x)))`. Pré-normalização: `x + dropout(f(norm(x)))`.
+#[derive(Serialize, Deserialize, Clone)]
+struct Residual {
+    norm: LayerNorm,
+    dropout: Dropout,
+}
+
+impl Residual {
+    fn new(embed_dim: usize, dropout: f32) -> Self {
+        Residual {
+            norm: LayerNorm::new(&[embed_dim]),
+            dropout: Dropout::new(dropout),
+        }
+    }
+
+    fn forward(
+        &mut self,
+        x: &ArrayD<f32>,
+        norm_first: bool,
+        sublayer: impl FnOnce(&ArrayD<f32>) -> ArrayD<f32>,
+    ) -> ArrayD<f32> {
+        if norm_first {
+            x + &self.dropout.forward(&sublayer(&self.norm.forward(x)))
+        } else {
+            self.norm
+                .forward(&(x + &self.dropout.forward(&sublayer(x))))
+        }
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        norm_first: bool,
+        optimizer: &mut dyn Optimizer,
+        sublayer: impl FnOnce(&ArrayD<f32>, &mut dyn Optimizer) -> ArrayD<f32>,
+    ) -> ArrayD<f32> {
+        if norm_first {
+            let branch_error = sublayer(&self.dropout.backward(output_error, optimizer), optimizer);
+            output_error + &self.norm.backward(&branch_error, optimizer)
+        } else {
+            let sum_error = self.norm.backward(output_error, optimizer);
+            let branch_error = sublayer(&self.dropout.backward(&sum_error, optimizer), optimizer);
+            sum_error + branch_error
+        }
+    }
+
+    fn layers(&self) -> [&dyn Layer; 2] {
+        [&self.norm, &self.dropout]
+    }
+
+    fn layers_mut(&mut self) -> [&mut dyn Layer; 2] {
+        [&mut self.norm, &mut self.dropout]
+    }
+}
+
+/// Bloco codificador do Transformer: autoatenção multi-cabeça seguida de uma rede
+/// feed-forward por posição, cada uma com conexão residual, dropout e normalização de camada.
+///
+/// Opera sobre sequências `(N, T, embed_dim)` e preserva a forma da entrada.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TransformerEncoderLayer {
+    /// Autoatenção; a máscara de preenchimento pode ser definida diretamente nela.
+    pub self_attention: MultiHeadAttention,
+    /// Se a normalização vem antes de cada subcamada (pré-norm) ou após a soma residual.
+    pub norm_first: bool,
+    attention_block: Residual,
+    feed_forward: FeedForward,
+    feed_forward_block: Residual,
+}
+
+impl TransformerEncoderLayer {
+    /// Cria um novo bloco codificador com ativação ReLU, sem dropout e com pós-normalização.
+    ///
+    /// # Argumentos
+    ///
+    /// * `embed_dim` - Dimensão das entradas e da saída.
+    /// * `num_heads` - Número de cabeças da atenção.
+    /// * `ff_dim` - Dimensão oculta da rede feed-forward.
+    pub fn new(embed_dim: usize, num_heads: usize, ff_dim: usize) -> Self {
+        TransformerEncoderLayer {
+            self_attention: MultiHeadAttention::new(embed_dim, num_heads),
+            norm_first: false,
+            attention_block: Residual::new(embed_dim, 0.0),
+            feed_forward: FeedForward::new(embed_dim, ff_dim, ActivationType::ReLU),
+            feed_forward_block: Residual::new(embed_dim, 0.0),
+        }
+    }
+
+    /// Define a ativação da rede feed-forward.
+    pub fn with_activation(mut self, activation_type: ActivationType) -> Self {
+        self.feed_forward.activation_type = activation_type;
+        self
+    }
+
+    /// Define a taxa de dropout dos pesos de atenção e dos ramos residuais.
+    pub fn with_dropout(mut self, rate: f32) -> Self {
+        self.self_attention = self.self_attention.with_dropout(rate);
+        self.attention_block.dropout = Dropout::new(rate);
+        self.feed_forward_block.dropout = Dropout::new(rate);
+        self
+    }
+
+    /// Escolhe entre pré-normalização (`true`) e pós-normalização (`false`, padrão).
+    pub fn with_norm_first(mut self, norm_first: bool) -> Self {
+        self.norm_first = norm_first;
+        self
+    }
+
+    fn layers(&self) -> Vec<&dyn Layer> {
+        let mut layers: Vec<&dyn Layer> = vec![&self.self_attention];
+        layers.extend(self.attention_block.layers());
+        layers.push(&self.feed_forward);
+        layers.extend(self.feed_forward_block.layers());
+        layers
+    }
+
+    fn layers_mut(&mut self) -> Vec<&mut dyn Layer> {
+        let mut layers: Vec<&mut dyn Layer> = vec![&mut self.self_attention];
+        layers.extend(self.attention_block.layers_mut());
+        layers.push(&mut self.feed_forward);
+        layers.extend(self.feed_forward_block.layers_mut());
+        layers
+    }
+}
+
+impl Layer for TransformerEncoderLayer {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let norm_first = self.norm_first;
+        let attention = &mut self.self_attention;
+        let x = self
+            .attention_block
+            .forward(input, norm_first, |x| attention.forward(x));
+        let feed_forward = &mut self.feed_forward;
+        self.feed_forward_block
+            .forward(&x, norm_first, |x| feed_forward.forward(x))
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let norm_first = self.norm_first;
+        let feed_forward = &mut self.feed_forward;
+        let error = self.feed_forward_block.backward(
+            output_error,
+            norm_first,
+            optimizer,
+            |error, optimizer| feed_forward.backward(error, optimizer),
+        );
+        let attention = &mut self.self_attention;
+        self.attention_block
+            .backward(&error, norm_first, optimizer, |error, optimizer| {
+                attention.backward(error, optimizer)
+            })
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        self.self_attention.output_shape(input_shape)
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        collect_weights(self.layers())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        distribute_weights(self.layers_mut(), weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        collect_moments(self.layers())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        distribute_moments(self.layers_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.layers().iter().map(|layer| layer.param_count()).sum()
+    }
+
+    fn set_training(&mut self, training: bool) {
+        for layer in self.layers_mut() {
+            layer.set_training(training);
+        }
+    }
+
+    fn trainable(&self) -> bool {
+        any_trainable(self.layers())
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        for layer in self.layers_mut() {
+            layer.set_trainable(trainable);
+        }
+    }
+}
+
+/// Bloco decodificador do Transformer: autoatenção causal, atenção cruzada sobre a saída do
+/// codificador (a memória) e rede feed-forward por posição, cada uma com conexão residual,
+/// dropout e normalização de camada.
+///
+/// A memória `(N, Tm, embed_dim)` é definida com `set_memory` antes da passagem forward; após a
+/// passagem backward, `memory_grad` retorna o erro a propagar para o codificador.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TransformerDecoderLayer {
+    /// Autoatenção causal sobre a sequência do decodificador.
+    pub self_attention: MultiHeadAttention,
+    /// Atenção cruzada das posições do decodificador sobre a memória.
+    pub cross_attention: MultiHeadAttention,
+    /// Se a normalização vem antes de cada subcamada (pré-norm) ou após a soma residual.
+    pub norm_first: bool,
+    self_attention_block: Residual,
+    cross_attention_block: Residual,
+    feed_forward: FeedForward,
+    feed_forward_block: Residual,
+
+    #[serde(skip)]
+    memory: Option<ArrayD<f32>>,
+    #[serde(skip)]
+    memory_grad: Option<ArrayD<f32>>,
+}
+
+impl TransformerDecoderLayer {
+    /// Cria um novo bloco decodificador com ativação ReLU, sem dropout e com pós-normalização.
+    ///
+    /// # Argumentos
+    ///
+    /// * `embed_dim` - Dimensão das entradas, da memória e da saída.
+    /// * `num_heads` - Número de cabeças das atenções.
+    /// * `ff_dim` - Dimensão oculta da rede feed-forward.
+    pub fn new(embed_dim: usize, num_heads: usize, ff_dim: usize) -> Self {
+        TransformerDecoderLayer {
+            self_attention: MultiHeadAttention::new(embed_dim, num_heads).with_causal(true),
+            cross_attention: MultiHeadAttention::new(embed_dim, num_heads),
+            norm_first: false,
+            self_attention_block: Residual::new(embed_dim, 0.0),
+            cross_attention_block: Residual::new(embed_dim, 0.0),
+            feed_forward: FeedForward::new(embed_dim, ff_dim, ActivationType::ReLU),
+            feed_forward_block: Residual::new(embed_dim, 0.0),
+            memory: None,
+            memory_grad: None,
+        }
+    }
+
+    /// Define a ativação da rede feed-forward.
+    pub fn with_activation(mut self, activation_type: ActivationType) -> Self {
+        self.feed_forward.activation_type = activation_type;
+        self
+    }
+
+    /// Define a taxa de dropout dos pesos de atenção e dos ramos residuais.
+    pub fn with_dropout(mut self, rate: f32) -> Self {
+        self.self_attention = self.self_attention.with_dropout(rate);
+        self.cross_attention = self.cross_attention.with_dropout(rate);
+        self.self_attention_block.dropout = Dropout::new(rate);
+        self.cross_attention_block.dropout = Dropout::new(rate);
+        self.feed_forward_block.dropout = Dropout::new(rate);
+        self
+    }
+
+    /// Escolhe entre pré-normalização (`true`) e pós-normalização (`false`, padrão).
+    pub fn with_norm_first(mut self, norm_first: bool) -> Self {
+        self.norm_first = norm_first;
+        self
+    }
+
+    /// Define a memória `(N, Tm, embed_dim)`, normalmente a saída do codificador, usada pelas
+    /// próximas passagens forward.
+    pub fn set_memory(&mut self, memory: ArrayD<f32>) {
+        self.memory = Some(memory);
+    }
+
+    /// Erro em relação à memória, calculado na última passagem backward.
+    pub fn memory_grad(&self) -> Option<&ArrayD<f32>> {
+        self.memory_grad.as_ref()
+    }
+
+    fn layers(&self) -> Vec<&dyn Layer> {
+        let mut layers: Vec<&dyn Layer> = vec![&self.self_attention];
+        layers.extend(self.self_attention_block.layers());
+        layers.push(&self.cross_attention);
+        layers.extend(self.cross_attention_block.layers());
+        layers.push(&self.feed_forward);
+        layers.extend(self.feed_forward_block.layers());
+        layers
+    }
+
+    fn layers_mut(&mut self) -> Vec<&mut dyn Layer> {
+        let mut layers: Vec<&mut dyn Layer> = vec![&mut self.self_attention];
+        layers.extend(self.self_attention_block.layers_mut());
+        layers.push(&mut self.cross_attention);
+        layers.extend(self.cross_attention_block.layers_mut());
+        layers.push(&mut self.feed_forward);
+        layers.extend(self.feed_forward_block.layers_mut());
+        layers
+    }
+}
+
+impl Layer for TransformerDecoderLayer {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let memory = self
+            .memory
+            .as_ref()
+            .expect("TransformerDecoderLayer: defina a memória com set_memory");
+        let norm_first = self.norm_first;
+
+        let attention = &mut self.self_attention;
+        let x = self
+            .self_attention_block
+            .forward(input, norm_first, |x| attention.forward(x));
+        let cross = &mut self.cross_attention;
+        let x = self.cross_attention_block.forward(&x, norm_first, |x| {
+            cross.forward_attention(x, memory, memory)
+        });
+        let feed_forward = &mut self.feed_forward;
+        self.feed_forward_block
+            .forward(&x, norm_first, |x| feed_forward.forward(x))
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let norm_first = self.norm_first;
+
+        let feed_forward = &mut self.feed_forward;
+        let error = self.feed_forward_block.backward(
+            output_error,
+            norm_first,
+            optimizer,
+            |error, optimizer| feed_forward.backward(error, optimizer),
+        );
+        let cross = &mut self.cross_attention;
+        let mut memory_grad = None;
+        let error = self.cross_attention_block.backward(
+            &error,
+            norm_first,
+            optimizer,
+            |error, optimizer| {
+                let (query_error, key_error, value_error) =
+                    cross.backward_attention(error, optimizer);
+                memory_grad = Some(key_error + value_error);
+                query_error
+            },
+        );
+        self.memory_grad = memory_grad;
+        let attention = &mut self.self_attention;
+        self.self_attention_block
+            .backward(&error, norm_first, optimizer, |error, optimizer| {
+                attention.backward(error, optimizer)
+            })
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        self.self_attention.output_shape(input_shape)
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        collect_weights(self.layers())
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        distribute_weights(self.layers_mut(), weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        collect_moments(self.layers())
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        distribute_moments(self.layers_mut(), moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.layers().iter().map(|layer| layer.param_count()).sum()
+    }
+
+    fn set_training(&mut self, training: bool) {
+        for layer in self.layers_mut() {
+            layer.set_training(training);
+        }
+    }
+
+    fn trainable(&self) -> bool {
+        any_trainable(self.layers())
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        for layer in self.layers_mut() {
+            layer.set_trainable(trainable);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// bran/src/layers/upsample.rs
+
+use super::Layer;
+use crate::optimizer::Optimizer;
+use ndarray::{Array2, Array3, ArrayD, Axis, Zip};
+use serde::{Deserialize, Serialize};
+
+/// Método de interpolação usado no aumento de resolução.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    /// Repete cada pixel (vizinho mais próximo).
+    Nearest,
+    /// Interpolação bilinear com centros de pixel alinhados (`align_corners = false`).
+    Bilinear,
+}
+
+/// Matriz `(len * scale, len)` que interpola um eixo de tamanho `len`.
+fn interpolation_matrix(len: usize, scale: usize, mode: Interpolation) -> Array2<f32> {
+    let mut matrix = Array2::zeros((len * scale, len));
+    for (out, mut row) in matrix.outer_iter_mut().enumerate() {
+        match mode {
+            Interpolation::Nearest => row[out / scale] = 1.0,
+            Interpolation::Bilinear => {
+                let src = ((out as f32 + 0.5) / scale as f32 - 0.5).max(0.0);
+                let i0 = (src.floor() as usize).min(len - 1);
+                let i1 = (i0 + 1).min(len - 1);
+                let frac = src - i0 as f32;
+                row[i0] += 1.0 - frac;
+                row[i1] += frac;
+            }
+        }
+    }
+    matrix
+}
+
+/// Camada de aumento de resolução 2D sobre entradas NCHW, sem parâmetros treináveis.
+///
+/// A interpolação é separável: cada imagem é multiplicada por uma matriz de interpolação
+/// das linhas e outra das colunas, e a passagem backward usa as transpostas.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Upsample2D {
+    /// Fator de aumento `(vertical, horizontal)`.
+    pub scale: (usize, usize),
+    /// Método de interpolação.
+    pub mode: Interpolation,
+
+    #[serde(skip)]
+    cache: Option<(Array2<f32>, Array2<f32>, Vec<usize>)>,
+}
+
+impl Upsample2D {
+    /// Cria uma nova camada de aumento de resolução.
+    ///
+    /// # Argumentos
+    ///
+    /// * `scale` - Fator de aumento `(vertical, horizontal)`.
+    /// * `mode` - Método de interpolação.
+    pub fn new(scale: (usize, usize), mode: Interpolation) -> Self {
+        assert!(
+            scale.0 > 0 && scale.1 > 0,
+            "o fator de escala deve ser positivo"
+        );
+        Upsample2D {
+            scale,
+            mode,
+            cache: None,
+        }
+    }
+}
+
+/// Aplica `rows · imagem · colsᵀ` a cada imagem `(H, W)` do tensor, em paralelo.
+fn apply(images: Array3<f32>, rows: &Array2<f32>, cols: &Array2<f32>) -> Array3<f32> {
+    let mut output = Array3::zeros((images.len_of(Axis(0)), rows.nrows(), cols.nrows()));
+    Zip::from(output.outer_iter_mut())
+        .and(images.outer_iter())
+        .par_for_each(|mut out, image| out.assign(&rows.dot(&image).dot(&cols.t())));
+    output
+}
+
+impl Layer for Upsample2D {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        assert_eq!(
+            input.ndim(),
+            4,
+            "Upsample2D espera uma entrada (N, C, H, W)"
+        );
+        let shape = input.shape().to_vec();
+        let (h, w) = (shape[2], shape[3]);
+        let rows = interpolation_matrix(h, self.scale.0, self.mode);
+        let cols = interpolation_matrix(w, self.scale.1, self.mode);
+
+        let images = input
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((shape[0] * shape[1], h, w))
+            .unwrap();
+        let output = apply(images, &rows, &cols)
+            .into_shape(self.output_shape(&shape))
+            .unwrap();
+
+        self.cache = Some((rows, cols, shape));
+        output
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        _optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let (rows, cols, shape) = self
+            .cache
+            .take()
+            .expect("Upsample2D::backward chamado antes de forward");
+        let errors = output_error
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((shape[0] * shape[1], rows.nrows(), cols.nrows()))
+            .unwrap();
+        apply(errors, &rows.t().to_owned(), &cols.t().to_owned())
+            .into_shape(shape)
+            .unwrap()
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        assert_eq!(
+            input_shape.len(),
+            4,
+            "Upsample2D espera uma entrada (N, C, H, W)"
+        );
+        vec![
+            input_shape[0],
+            input_shape[1],
+            input_shape[2] * self.scale.0,
+            input_shape[3] * self.scale.1,
+        ]
+    }
+}
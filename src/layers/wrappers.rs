@@ -0,0 +1,303 @@
+// bran/src/layers/wrappers.rs
+
+use super::{Layer, LayerType, Recurrent};
+use crate::optimizer::Optimizer;
+use ndarray::{concatenate, ArrayD, Axis, Slice};
+use serde::{Deserialize, Serialize};
+
+/// Inverte o eixo do tempo de um tensor `(N, T, ...)`.
+fn reverse_time(x: &ArrayD<f32>) -> ArrayD<f32> {
+    x.slice_axis(Axis(1), Slice::new(0, None, -1))
+        .as_standard_layout()
+        .into_owned()
+}
+
+/// Separa uma lista de tensores em duas, a primeira com `split` elementos.
+fn split_at(mut values: Vec<ArrayD<f32>>, split: usize) -> (Vec<ArrayD<f32>>, Vec<ArrayD<f32>>) {
+    let rest = values.split_off(split);
+    (values, rest)
+}
+
+/// Forma de combinar as saídas das duas direções de uma camada `Bidirectional`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MergeMode {
+    /// Concatena as saídas no último eixo (`2 * hidden_size` features).
+    Concat,
+    /// Soma as saídas.
+    Sum,
+    /// Média das saídas.
+    Mean,
+}
+
+/// Executa uma camada recorrente nos dois sentidos do tempo e combina as saídas.
+///
+/// A camada de trás recebe a sequência invertida; com `return_sequences`, sua saída é
+/// invertida de volta, de modo que o instante `t` das duas direções fique alinhado.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Bidirectional {
+    /// Camada que percorre a sequência do início ao fim.
+    pub forward_layer: Box<LayerType>,
+    /// Camada que percorre a sequência do fim ao início.
+    pub backward_layer: Box<LayerType>,
+    /// Forma de combinar as saídas.
+    pub merge_mode: MergeMode,
+    /// Se as camadas retornam todos os instantes.
+    pub return_sequences: bool,
+    /// Dimensão do estado oculto de cada direção.
+    pub hidden_size: usize,
+}
+
+impl Bidirectional {
+    /// Cria uma nova camada bidirecional.
+    ///
+    /// # Argumentos
+    ///
+    /// * `forward_layer` - Camada recorrente da direção direta.
+    /// * `backward_layer` - Camada recorrente da direção reversa, com a mesma configuração
+    ///   (e pesos inicializados de forma independente).
+    /// * `merge_mode` - Forma de combinar as saídas.
+    pub fn new<L: Recurrent + Into<LayerType>>(
+        forward_layer: L,
+        backward_layer: L,
+        merge_mode: MergeMode,
+    ) -> Self {
+        assert_eq!(
+            forward_layer.hidden_size(),
+            backward_layer.hidden_size(),
+            "as duas direções devem ter o mesmo hidden_size"
+        );
+        assert_eq!(
+            forward_layer.return_sequences(),
+            backward_layer.return_sequences(),
+            "as duas direções devem ter o mesmo return_sequences"
+        );
+        Bidirectional {
+            hidden_size: forward_layer.hidden_size(),
+            return_sequences: forward_layer.return_sequences(),
+            forward_layer: Box::new(forward_layer.into()),
+            backward_layer: Box::new(backward_layer.into()),
+            merge_mode,
+        }
+    }
+}
+
+impl Layer for Bidirectional {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        let forward = self.forward_layer.forward(input);
+        let mut backward = self.backward_layer.forward(&reverse_time(input));
+        if self.return_sequences {
+            backward = reverse_time(&backward);
+        }
+        match self.merge_mode {
+            MergeMode::Concat => {
+                let axis = Axis(forward.ndim() - 1);
+                concatenate(axis, &[forward.view(), backward.view()]).unwrap()
+            }
+            MergeMode::Sum => forward + backward,
+            MergeMode::Mean => (forward + backward) * 0.5,
+        }
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let (forward_error, mut backward_error) = match self.merge_mode {
+            MergeMode::Concat => {
+                let (axis, h) = (Axis(output_error.ndim() - 1), self.hidden_size);
+                (
+                    output_error.slice_axis(axis, Slice::from(..h)).to_owned(),
+                    output_error.slice_axis(axis, Slice::from(h..)).to_owned(),
+                )
+            }
+            MergeMode::Sum => (output_error.clone(), output_error.clone()),
+            MergeMode::Mean => {
+                let half = output_error * 0.5;
+                (half.clone(), half)
+            }
+        };
+        if self.return_sequences {
+            backward_error = reverse_time(&backward_error);
+        }
+        let forward_input_error = self.forward_layer.backward(&forward_error, optimizer);
+        let backward_input_error = self.backward_layer.backward(&backward_error, optimizer);
+        forward_input_error + reverse_time(&backward_input_error)
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        let mut shape = self.forward_layer.output_shape(input_shape);
+        if self.merge_mode == MergeMode::Concat {
+            *shape.last_mut().unwrap() *= 2;
+        }
+        shape
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        let mut weights = self.forward_layer.weights();
+        weights.extend(self.backward_layer.weights());
+        weights
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        let (forward, backward) = split_at(weights, self.forward_layer.weights().len());
+        self.forward_layer.set_weights(forward);
+        self.backward_layer.set_weights(backward);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        let mut moments = self.forward_layer.moments();
+        moments.extend(self.backward_layer.moments());
+        moments
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        let (forward, backward) = split_at(moments, self.forward_layer.moments().len());
+        self.forward_layer.set_moments(forward)?;
+        self.backward_layer.set_moments(backward)
+    }
+
+    fn param_count(&self) -> usize {
+        self.forward_layer.param_count() + self.backward_layer.param_count()
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.forward_layer.set_training(training);
+        self.backward_layer.set_training(training);
+    }
+
+    fn trainable(&self) -> bool {
+        self.forward_layer.trainable() || self.backward_layer.trainable()
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.forward_layer.set_trainable(trainable);
+        self.backward_layer.set_trainable(trainable);
+    }
+}
+
+/// Aplica a mesma camada, com os mesmos pesos, a cada instante de uma sequência `(N, T, ...)`.
+///
+/// Os eixos do lote e do tempo são unidos antes de chamar a camada interna, que recebe
+/// `(N * T, ...)`; a saída é reorganizada para `(N, T, ...)`. Serve, por exemplo, para aplicar
+/// uma `DenseLayer` ou uma `Conv2D` a cada quadro.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TimeDistributed {
+    /// Camada aplicada a cada instante.
+    pub layer: Box<LayerType>,
+
+    #[serde(skip)]
+    input_shape: Option<Vec<usize>>,
+}
+
+impl TimeDistributed {
+    /// Cria uma nova camada distribuída no tempo.
+    ///
+    /// # Argumentos
+    ///
+    /// * `layer` - Camada aplicada a cada instante.
+    pub fn new(layer: impl Into<LayerType>) -> Self {
+        TimeDistributed {
+            layer: Box::new(layer.into()),
+            input_shape: None,
+        }
+    }
+
+    /// Une os eixos do lote e do tempo: `(N, T, ...)` → `(N * T, ...)`.
+    fn merge_time(x: &ArrayD<f32>) -> ArrayD<f32> {
+        let shape: Vec<usize> = std::iter::once(x.shape()[0] * x.shape()[1])
+            .chain(x.shape()[2..].iter().copied())
+            .collect();
+        x.as_standard_layout()
+            .into_owned()
+            .into_shape(shape)
+            .unwrap()
+    }
+
+    /// Separa os eixos do lote e do tempo: `(N * T, ...)` → `(N, T, ...)`.
+    fn split_time(x: ArrayD<f32>, batch: usize, steps: usize) -> ArrayD<f32> {
+        let shape: Vec<usize> = [batch, steps]
+            .into_iter()
+            .chain(x.shape()[1..].iter().copied())
+            .collect();
+        x.as_standard_layout()
+            .into_owned()
+            .into_shape(shape)
+            .unwrap()
+    }
+}
+
+impl Layer for TimeDistributed {
+    fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
+        assert!(
+            input.ndim() >= 3,
+            "TimeDistributed espera uma entrada (N, T, ...)"
+        );
+        let (batch, steps) = (input.shape()[0], input.shape()[1]);
+        self.input_shape = Some(input.shape().to_vec());
+        let output = self.layer.forward(&Self::merge_time(input));
+        Self::split_time(output, batch, steps)
+    }
+
+    fn backward(
+        &mut self,
+        output_error: &ArrayD<f32>,
+        optimizer: &mut dyn Optimizer,
+    ) -> ArrayD<f32> {
+        let input_shape = self
+            .input_shape
+            .take()
+            .expect("TimeDistributed::backward chamado antes de forward");
+        let input_error = self
+            .layer
+            .backward(&Self::merge_time(output_error), optimizer);
+        Self::split_time(input_error, input_shape[0], input_shape[1])
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        assert!(
+            input_shape.len() >= 3,
+            "TimeDistributed espera uma entrada (N, T, ...)"
+        );
+        let merged: Vec<usize> = std::iter::once(input_shape[0] * input_shape[1])
+            .chain(input_shape[2..].iter().copied())
+            .collect();
+        let inner = self.layer.output_shape(&merged);
+        [input_shape[0], input_shape[1]]
+            .into_iter()
+            .chain(inner[1..].iter().copied())
+            .collect()
+    }
+
+    fn weights(&self) -> Vec<ArrayD<f32>> {
+        self.layer.weights()
+    }
+
+    fn set_weights(&mut self, weights: Vec<ArrayD<f32>>) {
+        self.layer.set_weights(weights);
+    }
+
+    fn moments(&self) -> Vec<ArrayD<f32>> {
+        self.layer.moments()
+    }
+
+    fn set_moments(&mut self, moments: Vec<ArrayD<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+        self.layer.set_moments(moments)
+    }
+
+    fn param_count(&self) -> usize {
+        self.layer.param_count()
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.layer.set_training(training);
+    }
+
+    fn trainable(&self) -> bool {
+        self.layer.trainable()
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.layer.set_trainable(trainable);
+    }
+}
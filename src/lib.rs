@@ -1,8 +1,11 @@
 // bran/src/lib.rs
 
 pub mod activations;
+pub mod callbacks;
+pub mod checkpoint;
 pub mod layers;
 pub mod loss;
+pub mod metrics;
 pub mod model;
 pub mod optimizer;
 pub mod prelude;
@@ -12,7 +15,7 @@ pub mod visualization;
 mod tests {
     use super::*;
     use approx::assert_abs_diff_eq;
-    use ndarray::{arr1, arr2, array};
+    use ndarray::{arr1, arr2, array, s, Array2, ArrayD};
     use prelude::*;
 
     #[test]
@@ -34,9 +37,41 @@ mod tests {
     #[test]
     fn test_dense_layer() {
         let mut layer = DenseLayer::new(2, 3, ActivationType::ReLU);
-        let input = arr2(&[[1.0, 2.0]]);
+        let input = arr2(&[[1.0, 2.0]]).into_dyn();
         let output = layer.forward(&input);
         assert_eq!(output.shape(), &[1, 3]);
+
+        // A derivada da ativação é avaliada antes da ativação, não sobre a saída
+        for activation in [ActivationType::Sigmoid, ActivationType::Tanh] {
+            check_gradients(&DenseLayer::new(3, 2, activation), &pattern(&[4, 3], 1));
+        }
+    }
+
+    #[test]
+    fn test_dense_layer_last_axis() {
+        let mut layer = DenseLayer::new(3, 2, ActivationType::Linear);
+        let input =
+            ndarray::Array3::from_shape_fn((4, 5, 3), |(b, t, f)| (b + t + f) as f32).into_dyn();
+        let output = layer.forward(&input);
+        assert_eq!(output.shape(), &[4, 5, 2]);
+
+        // Cada posição temporal é equivalente a uma amostra independente
+        let row = input
+            .slice(ndarray::s![1, 2, ..])
+            .to_owned()
+            .insert_axis(ndarray::Axis(0));
+        let expected = layer.forward(&row.into_dyn());
+        for (a, b) in output
+            .slice(ndarray::s![1, 2, ..])
+            .iter()
+            .zip(expected.iter())
+        {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-5);
+        }
+
+        layer.forward(&input);
+        let input_error = layer.backward(&output, &mut SGD::new(0.0, 0.0));
+        assert_eq!(input_error.shape(), input.shape());
     }
 
     #[test]
@@ -44,9 +79,61 @@ mod tests {
         let mut nn = NeuralNetwork::new();
         nn.add_layer(DenseLayer::new(2, 3, ActivationType::ReLU));
         nn.add_layer(DenseLayer::new(3, 1, ActivationType::Sigmoid));
-        let input = arr2(&[[1.0, 2.0]]);
+        let input = arr2(&[[1.0, 2.0]]).into_dyn();
         let output = nn.forward(&input);
         assert_eq!(output.shape(), &[1, 1]);
+        assert_eq!(nn.output_shape(&[8, 6, 2]), vec![8, 6, 1]);
+    }
+
+    #[test]
+    fn test_model_format_version() {
+        use serde::Serialize;
+
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(DenseLayer::new(2, 3, ActivationType::ReLU));
+        nn.add_layer(DenseLayer::new(3, 1, ActivationType::Sigmoid));
+        let input = arr2(&[[1.0, 2.0], [-0.5, 0.3]]).into_dyn();
+        let path = std::env::temp_dir().join("bran_test_format.bin");
+        let path = path.to_str().unwrap();
+
+        // Disposição anterior ao cabeçalho: uma lista de camadas densas
+        #[derive(Serialize)]
+        struct LegacyDense<'a> {
+            weights: &'a ndarray::Array2<f32>,
+            biases: &'a ndarray::Array1<f32>,
+            activation_type: &'a ActivationType,
+        }
+        let legacy: Vec<LegacyDense> = nn
+            .layers
+            .iter()
+            .map(|layer| match layer {
+                LayerType::Dense(dense) => LegacyDense {
+                    weights: &dense.weights,
+                    biases: &dense.biases,
+                    activation_type: &dense.activation_type,
+                },
+                _ => unreachable!(),
+            })
+            .collect();
+        std::fs::write(path, bincode::serialize(&(legacy,)).unwrap()).unwrap();
+        let mut loaded = NeuralNetwork::load(path).unwrap();
+        assert_eq!(nn.forward(&input), loaded.forward(&input));
+
+        // Arquivos novos começam com o cabeçalho de formato
+        nn.save(path).unwrap();
+        let mut bytes = std::fs::read(path).unwrap();
+        assert_eq!(&bytes[..4], b"BRAN");
+        assert_eq!(bytes[4..8], model::FORMAT_VERSION.to_le_bytes());
+
+        // Versões desconhecidas e arquivos sem relação geram erros claros
+        bytes[4..8].copy_from_slice(&(model::FORMAT_VERSION + 1).to_le_bytes());
+        std::fs::write(path, &bytes).unwrap();
+        let error = NeuralNetwork::load(path).err().unwrap().to_string();
+        assert!(error.contains("versão de formato"));
+        std::fs::write(path, b"nada a ver").unwrap();
+        assert!(NeuralNetwork::load(path).is_err());
+        assert!(Checkpoint::load(path).is_err());
+        std::fs::remove_file(path).unwrap();
     }
 
     #[test]
@@ -103,8 +190,8 @@ mod tests {
     #[test]
     fn test_mse_loss() {
         let mse = MeanSquaredError;
-        let predicted = arr2(&[[1.0, 2.0], [3.0, 4.0]]);
-        let target = arr2(&[[1.5, 2.5], [3.5, 4.5]]);
+        let predicted = arr2(&[[1.0, 2.0], [3.0, 4.0]]).into_dyn();
+        let target = arr2(&[[1.5, 2.5], [3.5, 4.5]]).into_dyn();
         let loss = mse.loss(&predicted, &target);
         assert_abs_diff_eq!(loss, 0.125, epsilon = 1e-6);
     }
@@ -112,8 +199,8 @@ mod tests {
     #[test]
     fn test_cross_entropy_loss() {
         let ce = CrossEntropyLoss;
-        let predicted = arr2(&[[0.6, 0.4], [0.3, 0.7]]);
-        let target = arr2(&[[1.0, 0.0], [0.0, 1.0]]);
+        let predicted = arr2(&[[0.6, 0.4], [0.3, 0.7]]).into_dyn();
+        let target = arr2(&[[1.0, 0.0], [0.0, 1.0]]).into_dyn();
         let loss = ce.loss(&predicted, &target);
         assert_abs_diff_eq!(loss, 0.4337, epsilon = 1e-4);
     }
@@ -127,4 +214,1777 @@ mod tests {
         assert_eq!(stats.epochs, vec![1.0, 2.0]);
         assert_eq!(stats.losses, vec![0.5, 0.3]);
     }
+
+    #[test]
+    fn test_metrics() {
+        let predicted = arr2(&[[0.8, 0.1], [0.3, 0.6]]).into_dyn();
+        let target = arr2(&[[1.0, 0.0], [1.0, 0.0]]).into_dyn();
+        assert_abs_diff_eq!(MetricType::Accuracy.compute(&predicted, &target), 0.5);
+        assert_abs_diff_eq!(
+            MetricType::CategoricalAccuracy.compute(&predicted, &target),
+            0.5
+        );
+        assert_abs_diff_eq!(
+            MetricType::MeanAbsoluteError.compute(&predicted, &target),
+            0.4,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn test_evaluate_and_validation() {
+        use std::sync::{Arc, Mutex};
+
+        let x = arr2(&[[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0], [0.5, 0.5]]).into_dyn();
+        let y = arr2(&[[0.0], [1.0], [1.0], [0.0], [1.0]]).into_dyn();
+
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(DenseLayer::new(2, 4, ActivationType::Tanh));
+        nn.add_layer(DenseLayer::new(4, 1, ActivationType::Sigmoid));
+        let evaluation = nn.evaluate(&x, &y, &MeanSquaredError, &[MetricType::Accuracy]);
+        assert!(evaluation.metric("accuracy").is_some());
+        assert!(evaluation.metric("mae").is_none());
+
+        let nn = Arc::new(Mutex::new(nn));
+        let stats = Arc::new(Mutex::new(TrainingStats::new()));
+        NeuralNetwork::train(
+            nn.clone(),
+            &x,
+            &y,
+            Arc::new(MeanSquaredError),
+            Arc::new(Mutex::new(SGD::new(0.1, 0.0))),
+            stats.clone(),
+            TrainConfig::new(3, 2)
+                .with_validation_split(0.4)
+                .with_metrics(vec![MetricType::MeanAbsoluteError]),
+        )
+        .unwrap();
+
+        {
+            let stats = stats.lock().unwrap();
+            assert_eq!(stats.losses.len(), 3);
+            assert_eq!(stats.val_losses.len(), 3);
+            assert_eq!(stats.metrics["mae"].len(), 3);
+            assert_eq!(stats.val_metrics["mae"].len(), 3);
+        }
+
+        // Configurações que deixariam um dos conjuntos vazio, ou sem mini-lotes, são rejeitadas
+        for config in [
+            TrainConfig::new(1, 2).with_validation_split(0.05),
+            TrainConfig::new(1, 2).with_validation_split(0.95),
+            TrainConfig::new(1, 0),
+        ] {
+            let result = NeuralNetwork::train(
+                nn.clone(),
+                &x,
+                &y,
+                Arc::new(MeanSquaredError),
+                Arc::new(Mutex::new(SGD::new(0.1, 0.0))),
+                stats.clone(),
+                config.with_progress(Progress::Silent),
+            );
+            assert!(result.is_err());
+        }
+        assert_eq!(stats.lock().unwrap().losses.len(), 3);
+    }
+
+    #[test]
+    fn test_early_stopping() {
+        use std::sync::{Arc, Mutex};
+
+        let x = arr2(&[[0.0, 1.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]).into_dyn();
+        let y = arr2(&[[1.0], [1.0], [0.0], [0.0]]).into_dyn();
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(DenseLayer::new(2, 1, ActivationType::Sigmoid));
+        let nn = Arc::new(Mutex::new(nn));
+        let stats = Arc::new(Mutex::new(TrainingStats::new()));
+
+        // Uma taxa de aprendizado negativa piora a perda a cada época
+        NeuralNetwork::train(
+            nn.clone(),
+            &x,
+            &y,
+            Arc::new(MeanSquaredError),
+            Arc::new(Mutex::new(SGD::new(-1.0, 0.0))),
+            stats.clone(),
+            TrainConfig::new(50, 4)
+                .with_early_stopping(EarlyStopping::new("loss", 2).with_restore_best_weights(true)),
+        )
+        .unwrap();
+
+        {
+            let stats = stats.lock().unwrap();
+            assert_eq!(stats.best_epoch, Some(1));
+            assert_eq!(stats.stopped_epoch, Some(3));
+            assert_eq!(stats.losses.len(), 3);
+        }
+
+        // Uma métrica indisponível é rejeitada antes da primeira época, sem treinar
+        let before = nn.lock().unwrap().get_weights();
+        for monitor in ["val_loss", "acuracy", "val_mae"] {
+            let started = Arc::new(Mutex::new(TrainingStats::new()));
+            let result = NeuralNetwork::train(
+                nn.clone(),
+                &x,
+                &y,
+                Arc::new(MeanSquaredError),
+                Arc::new(Mutex::new(SGD::new(0.1, 0.0))),
+                started.clone(),
+                TrainConfig::new(5, 4)
+                    .with_metrics(vec![MetricType::MeanAbsoluteError])
+                    .with_early_stopping(EarlyStopping::new(monitor, 2)),
+            );
+            let message = result.unwrap_err().to_string();
+            assert!(message.contains(monitor) && message.contains("mae"));
+            assert!(started.lock().unwrap().losses.is_empty());
+        }
+        assert_eq!(nn.lock().unwrap().get_weights(), before);
+    }
+
+    #[test]
+    fn test_callbacks() {
+        use std::sync::{Arc, Mutex};
+
+        struct Counter(Arc<Mutex<Vec<&'static str>>>);
+
+        impl Callback for Counter {
+            fn on_train_begin(&mut self, _ctx: &mut CallbackContext) {
+                self.0.lock().unwrap().push("train_begin");
+            }
+            fn on_epoch_end(&mut self, _epoch: usize, _ctx: &mut CallbackContext) {
+                self.0.lock().unwrap().push("epoch_end");
+            }
+            fn on_batch_end(&mut self, batch: usize, _loss: f32, ctx: &mut CallbackContext) {
+                self.0.lock().unwrap().push("batch_end");
+                if batch == 1 && ctx.stats.epochs.len() == 1 {
+                    ctx.stop_training = true;
+                }
+            }
+            fn on_train_end(&mut self, _ctx: &mut CallbackContext) {
+                self.0.lock().unwrap().push("train_end");
+            }
+        }
+
+        let x = arr2(&[[0.0], [1.0], [2.0], [3.0], [4.0], [5.0]]).into_dyn();
+        let y = arr2(&[[0.0], [1.0], [2.0], [3.0], [4.0], [5.0]]).into_dyn();
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(DenseLayer::new(1, 1, ActivationType::Linear));
+        let optimizer = Arc::new(Mutex::new(SGD::new(0.1, 0.0)));
+        let events = Arc::new(Mutex::new(Vec::new()));
+
+        NeuralNetwork::train(
+            Arc::new(Mutex::new(nn)),
+            &x,
+            &y,
+            Arc::new(MeanSquaredError),
+            optimizer.clone(),
+            Arc::new(Mutex::new(TrainingStats::new())),
+            TrainConfig::new(10, 2)
+                .with_callback(LearningRateScheduler::new(|epoch, _| 0.1 / epoch as f32))
+                .with_callback(Counter(events.clone())),
+        )
+        .unwrap();
+
+        // Três lotes na primeira época e dois na segunda, interrompida no lote 1
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "train_begin",
+                "batch_end",
+                "batch_end",
+                "batch_end",
+                "epoch_end",
+                "batch_end",
+                "batch_end",
+                "epoch_end",
+                "train_end"
+            ]
+        );
+        assert_abs_diff_eq!(optimizer.lock().unwrap().learning_rate(), 0.05);
+
+        // Interrompido no início do lote 1, que não chega a ser processado: a perda da época
+        // é a média apenas do lote 0
+        struct StopAtBatch(Arc<Mutex<Vec<f32>>>);
+
+        impl Callback for StopAtBatch {
+            fn on_batch_begin(&mut self, batch: usize, ctx: &mut CallbackContext) {
+                ctx.stop_training = batch == 1;
+            }
+            fn on_batch_end(&mut self, _batch: usize, loss: f32, _ctx: &mut CallbackContext) {
+                self.0.lock().unwrap().push(loss);
+            }
+        }
+
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(DenseLayer::new(1, 1, ActivationType::Linear));
+        let stats = Arc::new(Mutex::new(TrainingStats::new()));
+        let batch_losses = Arc::new(Mutex::new(Vec::new()));
+        NeuralNetwork::train(
+            Arc::new(Mutex::new(nn)),
+            &x,
+            &y,
+            Arc::new(MeanSquaredError),
+            Arc::new(Mutex::new(SGD::new(0.0, 0.0))),
+            stats.clone(),
+            TrainConfig::new(10, 2)
+                .with_progress(Progress::Silent)
+                .with_callback(StopAtBatch(batch_losses.clone())),
+        )
+        .unwrap();
+
+        let batch_losses = batch_losses.lock().unwrap();
+        let stats = stats.lock().unwrap();
+        assert_eq!(batch_losses.len(), 1);
+        assert_eq!(stats.losses.len(), 1);
+        assert_abs_diff_eq!(stats.losses[0], batch_losses[0], epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_progress_modes() {
+        use std::io::Write;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        static RECORDS: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+
+        struct Capture;
+
+        impl log::Log for Capture {
+            fn enabled(&self, metadata: &log::Metadata) -> bool {
+                metadata.target() == "bran"
+            }
+            fn log(&self, record: &log::Record) {
+                if self.enabled(record.metadata()) {
+                    let entry = (record.level(), record.args().to_string());
+                    RECORDS.lock().unwrap().push(entry);
+                }
+            }
+            fn flush(&self) {}
+        }
+
+        log::set_logger(&Capture).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        let x = arr2(&[[0.0], [1.0], [2.0]]).into_dyn();
+        let y = arr2(&[[0.0], [2.0], [4.0]]).into_dyn();
+        let nn = Arc::new(Mutex::new(NeuralNetwork::new()));
+        nn.lock()
+            .unwrap()
+            .add_layer(DenseLayer::new(1, 1, ActivationType::Linear));
+
+        for progress in [
+            Progress::Silent,
+            Progress::Epoch,
+            Progress::Bar,
+            Progress::Log,
+        ] {
+            let buffer = Buffer::default();
+            let stats = Arc::new(Mutex::new(TrainingStats::new()));
+            NeuralNetwork::train(
+                nn.clone(),
+                &x,
+                &y,
+                Arc::new(MeanSquaredError),
+                Arc::new(Mutex::new(SGD::new(0.01, 0.0))),
+                stats.clone(),
+                TrainConfig::new(2, 2)
+                    .with_progress(progress)
+                    .with_progress_writer(buffer.clone()),
+            )
+            .unwrap();
+            assert_eq!(stats.lock().unwrap().losses.len(), 2);
+
+            let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+            let records = std::mem::take(&mut *RECORDS.lock().unwrap());
+            match progress {
+                Progress::Silent => {
+                    assert!(output.is_empty());
+                    assert!(records.is_empty());
+                }
+                Progress::Epoch => {
+                    let lines: Vec<&str> = output.lines().collect();
+                    assert_eq!(lines.len(), 2);
+                    assert!(lines[0].starts_with("Epoch 1/2 - Loss: "));
+                    assert!(lines[1].starts_with("Epoch 2/2 - Loss: "));
+                    assert!(records.is_empty());
+                }
+                Progress::Bar => {
+                    // Dois mini-lotes por época, cada um redesenhando a barra, e um resumo
+                    assert_eq!(output.matches("samples/s").count(), 4);
+                    assert!(output.contains("Epoch 1/2 [===============               ] 1/2"));
+                    assert!(output.contains("Epoch 2/2 [==============================] 2/2"));
+                    assert_eq!(output.lines().count(), 2);
+                    assert!(records.is_empty());
+                }
+                Progress::Log => {
+                    assert!(output.is_empty());
+                    let count = |level| records.iter().filter(|(l, _)| *l == level).count();
+                    assert_eq!(count(log::Level::Debug), 4);
+                    assert_eq!(count(log::Level::Info), 2);
+                    assert!(records
+                        .iter()
+                        .any(|(level, message)| *level == log::Level::Info
+                            && message.starts_with("epoch 2/2 - Loss: ")));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_resume() {
+        use std::sync::{Arc, Mutex};
+
+        let x = arr2(&[[0.0, 1.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0], [0.5, 0.2]]).into_dyn();
+        let y = arr2(&[[1.0], [1.0], [0.0], [0.0], [1.0]]).into_dyn();
+        let mut initial = NeuralNetwork::new();
+        initial.add_layer(DenseLayer::new(2, 3, ActivationType::Tanh));
+        // A posição do gerador das máscaras também é retomada, sem repetir as da época 1
+        initial.add_layer(Dropout::new(0.3).with_seed(11));
+        initial.add_layer(DenseLayer::new(3, 1, ActivationType::Sigmoid));
+
+        let config = || {
+            TrainConfig::new(6, 2)
+                .with_progress(Progress::Silent)
+                .with_shuffle(7)
+                .with_early_stopping(EarlyStopping::new("loss", 10))
+        };
+        let adam = || Arc::new(Mutex::new(Adam::new(0.05, 0.9, 0.999, 1e-8, 0.0)));
+
+        // Treinamento sem interrupção
+        let uninterrupted = Arc::new(Mutex::new(initial.clone()));
+        let stats = Arc::new(Mutex::new(TrainingStats::new()));
+        NeuralNetwork::train(
+            uninterrupted.clone(),
+            &x,
+            &y,
+            Arc::new(MeanSquaredError),
+            adam(),
+            stats.clone(),
+            config(),
+        )
+        .unwrap();
+
+        // Treina três épocas gravando o checkpoint e retoma em uma rede nova
+        let path = std::env::temp_dir().join("bran_test_checkpoint_{epoch}.bin");
+        let path = path.to_str().unwrap();
+        let mut first_half = config().with_checkpoint(path, 3);
+        first_half.epochs = 3;
+        NeuralNetwork::train(
+            Arc::new(Mutex::new(initial)),
+            &x,
+            &y,
+            Arc::new(MeanSquaredError),
+            adam(),
+            Arc::new(Mutex::new(TrainingStats::new())),
+            first_half,
+        )
+        .unwrap();
+
+        let saved = path.replace("{epoch}", "3");
+        // O Adam conta uma atualização por camada: 2 camadas x 3 lotes x 3 épocas
+        assert_eq!(Checkpoint::load(&saved).unwrap().optimizer.step, 18);
+
+        // Um callback a mais é rejeitado antes de qualquer gancho ser chamado
+        struct Started(Arc<Mutex<bool>>);
+        impl Callback for Started {
+            fn on_train_begin(&mut self, _ctx: &mut CallbackContext) {
+                *self.0.lock().unwrap() = true;
+            }
+        }
+        let started = Arc::new(Mutex::new(false));
+        let resumed = Arc::new(Mutex::new(NeuralNetwork::new()));
+        let resumed_stats = Arc::new(Mutex::new(TrainingStats::new()));
+        assert!(NeuralNetwork::resume(
+            &saved,
+            resumed.clone(),
+            &x,
+            &y,
+            Arc::new(MeanSquaredError),
+            adam(),
+            resumed_stats.clone(),
+            config().with_callback(Started(started.clone())),
+        )
+        .is_err());
+        assert!(!*started.lock().unwrap());
+
+        // O modo de progresso não faz parte do checkpoint e pode mudar na retomada
+        NeuralNetwork::resume(
+            &saved,
+            resumed.clone(),
+            &x,
+            &y,
+            Arc::new(MeanSquaredError),
+            adam(),
+            resumed_stats.clone(),
+            config()
+                .with_progress(Progress::Epoch)
+                .with_progress_writer(std::io::sink()),
+        )
+        .unwrap();
+        std::fs::remove_file(&saved).unwrap();
+
+        assert_eq!(
+            uninterrupted.lock().unwrap().get_weights(),
+            resumed.lock().unwrap().get_weights()
+        );
+        assert_eq!(
+            stats.lock().unwrap().losses,
+            resumed_stats.lock().unwrap().losses
+        );
+
+        // Um intervalo de zero épocas é rejeitado em vez de virar 1 silenciosamente
+        assert!(NeuralNetwork::train(
+            resumed,
+            &x,
+            &y,
+            Arc::new(MeanSquaredError),
+            adam(),
+            resumed_stats,
+            config().with_checkpoint(path, 0),
+        )
+        .is_err());
+    }
+
+    /// Tensor determinístico com valores em [-1, 1] para os testes.
+    fn pattern(shape: &[usize], seed: usize) -> ndarray::ArrayD<f32> {
+        let len: usize = shape.iter().product();
+        ndarray::ArrayD::from_shape_vec(
+            shape.to_vec(),
+            (0..len)
+                .map(|i| ((i * 7 + seed * 13) as f32 * 0.37).sin())
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    /// Compara os gradientes da passagem backward com diferenças finitas centrais.
+    ///
+    /// A perda usada é `sum(saida * r)`, de modo que `r` é o erro da saída. Com SGD de taxa 1
+    /// e sem regularização, o gradiente de cada parâmetro é `antes - depois` da atualização.
+    fn check_gradients<L: Layer + Clone>(layer: &L, input: &ndarray::ArrayD<f32>) {
+        let eps = 1e-2;
+        let close = |analytic: f32, numeric: f32| {
+            assert!(
+                (analytic - numeric).abs() <= 1e-2 * (1.0 + analytic.abs().max(numeric.abs())),
+                "gradiente analítico {} difere do numérico {}",
+                analytic,
+                numeric
+            );
+        };
+
+        let mut probe = layer.clone();
+        let r = pattern(probe.forward(input).shape(), 3);
+        let loss = |layer: &mut L, input: &ndarray::ArrayD<f32>| (layer.forward(input) * &r).sum();
+
+        let mut trained = layer.clone();
+        trained.forward(input);
+        let input_error = trained.backward(&r, &mut SGD::new(1.0, 0.0));
+
+        // Gradiente em relação à entrada
+        for i in 0..input.len() {
+            let mut plus = input.clone();
+            plus.as_slice_mut().unwrap()[i] += eps;
+            let mut minus = input.clone();
+            minus.as_slice_mut().unwrap()[i] -= eps;
+            let numeric = (loss(&mut probe, &plus) - loss(&mut probe, &minus)) / (2.0 * eps);
+            close(input_error.as_slice().unwrap()[i], numeric);
+        }
+
+        // Gradientes dos parâmetros treináveis, que vêm antes das estatísticas em `weights`
+        let before = layer.weights();
+        let after = trained.weights();
+        for p in 0..layer.moments().len() / 2 {
+            for i in 0..before[p].len() {
+                let mut weights = before.clone();
+                weights[p].as_slice_mut().unwrap()[i] += eps;
+                probe.set_weights(weights.clone());
+                let plus = loss(&mut probe, input);
+                weights[p].as_slice_mut().unwrap()[i] -= 2.0 * eps;
+                probe.set_weights(weights);
+                let minus = loss(&mut probe, input);
+                let analytic = before[p].as_slice().unwrap()[i] - after[p].as_slice().unwrap()[i];
+                close(analytic, (plus - minus) / (2.0 * eps));
+            }
+        }
+    }
+
+    #[test]
+    fn test_conv2d_gradients() {
+        let input = pattern(&[2, 2, 6, 5], 1);
+
+        let strided = Conv2D::new(2, 3, (3, 3), ActivationType::Tanh)
+            .with_stride((2, 2))
+            .with_padding(Padding::Same);
+        assert_eq!(strided.output_shape(&[2, 2, 6, 5]), vec![2, 3, 3, 3]);
+        check_gradients(&strided, &input);
+
+        let dilated = Conv2D::new(2, 2, (2, 3), ActivationType::Sigmoid).with_dilation((2, 1));
+        assert_eq!(dilated.output_shape(&[2, 2, 6, 5]), vec![2, 2, 4, 3]);
+        check_gradients(&dilated, &input);
+
+        let explicit = Conv2D::new(2, 1, (3, 2), ActivationType::Linear)
+            .with_padding(Padding::Explicit(1, 2))
+            .with_bias(false);
+        assert_eq!(explicit.param_count(), 12);
+        assert_eq!(explicit.output_shape(&[2, 2, 6, 5]), vec![2, 1, 6, 8]);
+        check_gradients(&explicit, &input);
+    }
+
+    #[test]
+    fn test_conv2d_known_values_and_serialization() {
+        // Kernel de soma 2x2 sobre uma imagem 3x3
+        let mut conv = Conv2D::new(1, 1, (2, 2), ActivationType::Linear);
+        conv.set_weights(vec![
+            ndarray::ArrayD::ones(vec![1, 1, 2, 2]),
+            arr1(&[0.5]).into_dyn(),
+        ]);
+        let image = arr1(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0])
+            .into_shape((1, 1, 3, 3))
+            .unwrap()
+            .into_dyn();
+        let output = conv.forward(&image);
+        assert_eq!(output.into_raw_vec(), vec![12.5, 16.5, 24.5, 28.5]);
+
+        // Uma rede com camadas convolucionais e densas é salva e carregada junto
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(Conv2D::new(1, 2, (2, 2), ActivationType::ReLU).with_padding(Padding::Same));
+        nn.add_layer(DenseLayer::new(3, 1, ActivationType::Sigmoid));
+        assert_eq!(nn.output_shape(&[1, 1, 3, 3]), vec![1, 2, 3, 1]);
+        assert_eq!(nn.param_count(), 2 * 4 + 2 + 3 + 1);
+
+        let path = std::env::temp_dir().join("bran_test_conv2d.bin");
+        let path = path.to_str().unwrap();
+        nn.save(path).unwrap();
+        let mut loaded = NeuralNetwork::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(nn.get_weights(), loaded.get_weights());
+        assert_eq!(nn.forward(&image), loaded.forward(&image));
+    }
+
+    #[test]
+    fn test_conv1d_causal() {
+        let input = pattern(&[2, 3, 9], 2);
+
+        let causal = Conv1D::new(3, 2, 3, ActivationType::Tanh)
+            .with_padding(Padding1D::Causal)
+            .with_dilation(2);
+        assert_eq!(causal.output_shape(&[2, 3, 9]), vec![2, 2, 9]);
+        check_gradients(&causal, &input);
+
+        let strided = Conv1D::new(3, 2, 4, ActivationType::Linear)
+            .with_stride(2)
+            .with_padding(Padding1D::Same);
+        assert_eq!(strided.output_shape(&[2, 3, 9]), vec![2, 2, 5]);
+        check_gradients(&strided, &input);
+
+        // A saída no instante t não depende de entradas posteriores a t
+        let mut layer = causal.clone();
+        let before = layer.forward(&input);
+        let mut future = input.clone();
+        future.slice_mut(ndarray::s![.., .., 5..]).fill(10.0);
+        let after = layer.forward(&future);
+        assert_eq!(
+            before.slice(ndarray::s![.., .., ..5]),
+            after.slice(ndarray::s![.., .., ..5])
+        );
+        assert_ne!(
+            before.slice(ndarray::s![.., .., 5..]),
+            after.slice(ndarray::s![.., .., 5..])
+        );
+    }
+
+    #[test]
+    fn test_conv_transpose2d() {
+        let input = pattern(&[2, 3, 3, 4], 4);
+
+        let same = ConvTranspose2D::new(3, 2, (3, 3), ActivationType::Tanh)
+            .with_stride((2, 2))
+            .with_padding(Padding::Same);
+        assert_eq!(same.output_shape(&[2, 3, 3, 4]), vec![2, 2, 6, 8]);
+        check_gradients(&same, &input);
+
+        // O output_padding também se aplica com Padding::Same, inclusive com kernel menor que
+        // o stride
+        let same = same.with_output_padding((1, 1));
+        assert_eq!(same.output_shape(&[2, 3, 3, 4]), vec![2, 2, 7, 9]);
+        check_gradients(&same, &input);
+        let small = ConvTranspose2D::new(3, 2, (1, 2), ActivationType::Linear)
+            .with_stride((2, 3))
+            .with_padding(Padding::Same)
+            .with_output_padding((1, 2));
+        assert_eq!(small.output_shape(&[2, 3, 3, 4]), vec![2, 2, 7, 14]);
+        check_gradients(&small, &input);
+
+        // Desfaz as dimensões de uma Conv2D com a mesma configuração
+        let explicit = ConvTranspose2D::new(3, 2, (3, 2), ActivationType::Linear)
+            .with_stride((2, 2))
+            .with_padding(Padding::Explicit(1, 0))
+            .with_output_padding((1, 1))
+            .with_dilation((1, 2));
+        let output_shape = explicit.output_shape(&[2, 3, 3, 4]);
+        assert_eq!(output_shape, vec![2, 2, 6, 10]);
+        let conv = Conv2D::new(2, 3, (3, 2), ActivationType::Linear)
+            .with_stride((2, 2))
+            .with_padding(Padding::Explicit(1, 0))
+            .with_dilation((1, 2));
+        assert_eq!(conv.output_shape(&output_shape), vec![2, 3, 3, 4]);
+        check_gradients(&explicit, &input);
+    }
+
+    #[test]
+    fn test_upsample2d() {
+        let image = arr2(&[[0.0, 1.0], [2.0, 3.0]])
+            .into_shape((1, 1, 2, 2))
+            .unwrap()
+            .into_dyn();
+
+        let mut nearest = Upsample2D::new((2, 1), Interpolation::Nearest);
+        assert_eq!(
+            nearest.forward(&image).into_raw_vec(),
+            vec![0.0, 1.0, 0.0, 1.0, 2.0, 3.0, 2.0, 3.0]
+        );
+
+        let mut bilinear = Upsample2D::new((1, 2), Interpolation::Bilinear);
+        assert_eq!(
+            bilinear.forward(&image).into_raw_vec(),
+            vec![0.0, 0.25, 0.75, 1.0, 2.0, 2.25, 2.75, 3.0]
+        );
+
+        let input = pattern(&[2, 2, 3, 4], 5);
+        check_gradients(&Upsample2D::new((2, 3), Interpolation::Nearest), &input);
+        check_gradients(&Upsample2D::new((3, 2), Interpolation::Bilinear), &input);
+    }
+
+    #[test]
+    fn test_grouped_conv2d() {
+        use ndarray::{s, Axis};
+
+        let input = pattern(&[2, 4, 5, 5], 6);
+        let mut grouped = Conv2D::new(4, 6, (3, 3), ActivationType::Tanh)
+            .with_groups(2)
+            .with_padding(Padding::Same);
+        assert_eq!(grouped.kernel.value.shape(), &[6, 2, 3, 3]);
+        check_gradients(&grouped, &input);
+
+        // Cada grupo equivale a uma convolução independente sobre metade dos canais
+        let output = grouped.forward(&input);
+        let weights = grouped.weights();
+        for g in 0..2 {
+            let mut half =
+                Conv2D::new(2, 3, (3, 3), ActivationType::Tanh).with_padding(Padding::Same);
+            half.set_weights(vec![
+                weights[0]
+                    .slice(s![g * 3..(g + 1) * 3, .., .., ..])
+                    .to_owned()
+                    .into_dyn(),
+                weights[1]
+                    .slice(s![g * 3..(g + 1) * 3])
+                    .to_owned()
+                    .into_dyn(),
+            ]);
+            let expected = half.forward(
+                &input
+                    .slice_axis(Axis(1), (g * 2..(g + 1) * 2).into())
+                    .to_owned(),
+            );
+            let actual = output.slice_axis(Axis(1), (g * 3..(g + 1) * 3).into());
+            for (a, e) in actual.iter().zip(expected.iter()) {
+                assert_abs_diff_eq!(a, e, epsilon = 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_separable_conv2d() {
+        let standard = Conv2D::new(32, 64, (3, 3), ActivationType::ReLU);
+        let separable = SeparableConv2D::new(32, 64, (3, 3), ActivationType::ReLU);
+        assert_eq!(standard.param_count(), 32 * 64 * 9 + 64);
+        assert_eq!(separable.param_count(), 32 * 9 + 32 * 64 + 64);
+
+        let layer = SeparableConv2D::new(3, 4, (3, 3), ActivationType::Tanh)
+            .with_stride((2, 2))
+            .with_padding(Padding::Same)
+            .with_depth_multiplier(2);
+        assert_eq!(layer.output_shape(&[2, 3, 6, 5]), vec![2, 4, 3, 3]);
+        assert_eq!(layer.param_count(), 6 * 9 + 6 * 4 + 4);
+        check_gradients(&layer, &pattern(&[2, 3, 6, 5], 7));
+
+        // As duas convoluções contam como um único passo do Adam
+        let mut adam = Adam::new(0.01, 0.9, 0.999, 1e-8, 0.0);
+        let mut layer = layer;
+        let output = layer.forward(&pattern(&[2, 3, 6, 5], 7));
+        layer.backward(&output, &mut adam);
+        assert_eq!(adam.state().step, 1);
+    }
+
+    #[test]
+    fn test_pooling() {
+        let image = arr2(&[
+            [1.0, 5.0, 2.0, 0.0],
+            [3.0, 4.0, 8.0, 1.0],
+            [0.0, 2.0, 6.0, 7.0],
+        ])
+        .into_shape((1, 1, 3, 4))
+        .unwrap()
+        .into_dyn();
+        let mut sgd = SGD::new(0.1, 0.0);
+
+        // Max pooling com preenchimento Same: a última linha forma uma janela parcial
+        let mut max = MaxPool2D::new((2, 2)).with_padding(Padding::Same);
+        assert_eq!(max.output_shape(&[1, 1, 3, 4]), vec![1, 1, 2, 2]);
+        assert_eq!(max.forward(&image).into_raw_vec(), vec![5.0, 8.0, 2.0, 7.0]);
+        let error = arr1(&[1.0, 2.0, 3.0, 4.0])
+            .into_shape(vec![1, 1, 2, 2])
+            .unwrap();
+        assert_eq!(
+            max.backward(&error, &mut sgd).into_raw_vec(),
+            vec![0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 3.0, 0.0, 4.0]
+        );
+
+        // A média ignora as posições de preenchimento
+        let mut avg = AvgPool2D::new((2, 2)).with_padding(Padding::Same);
+        assert_eq!(
+            avg.forward(&image).into_raw_vec(),
+            vec![3.25, 2.75, 1.0, 6.5]
+        );
+        check_gradients(
+            &AvgPool2D::new((3, 2)).with_stride((1, 2)),
+            &pattern(&[2, 2, 5, 4], 8),
+        );
+
+        let sequence = arr1(&[1.0, 3.0, 2.0, 5.0, 4.0])
+            .into_shape(vec![1, 1, 5])
+            .unwrap();
+        let mut max1d = MaxPool1D::new(2).with_stride(1);
+        assert_eq!(
+            max1d.forward(&sequence).into_raw_vec(),
+            vec![3.0, 3.0, 5.0, 5.0]
+        );
+        assert_eq!(
+            max1d
+                .backward(&ndarray::ArrayD::ones(vec![1, 1, 4]), &mut sgd)
+                .into_raw_vec(),
+            vec![0.0, 2.0, 0.0, 2.0, 0.0]
+        );
+        check_gradients(
+            &AvgPool1D::new(3).with_padding(Padding1D::Same),
+            &pattern(&[2, 3, 7], 9),
+        );
+
+        // Pooling global reduz os eixos espaciais a (lote, canais)
+        let mut global_avg = GlobalAvgPool::new();
+        assert_eq!(global_avg.forward(&image).into_raw_vec(), vec![39.0 / 12.0]);
+        check_gradients(&GlobalAvgPool::new(), &pattern(&[2, 3, 7], 10));
+        let mut global_max = GlobalMaxPool::new();
+        assert_eq!(global_max.output_shape(&[1, 1, 3, 4]), vec![1, 1]);
+        assert_eq!(global_max.forward(&image).into_raw_vec(), vec![8.0]);
+        let input_error = global_max.backward(&ndarray::ArrayD::ones(vec![1, 1]), &mut sgd);
+        assert_eq!(input_error.shape(), &[1, 1, 3, 4]);
+        assert_eq!(input_error[[0, 0, 1, 2]], 1.0);
+        assert_eq!(input_error.sum(), 1.0);
+    }
+
+    #[test]
+    fn test_flatten_reshape_conv_to_dense() {
+        use std::sync::{Arc, Mutex};
+
+        let mut reshape = Reshape::new(&[2, 6]);
+        let input = pattern(&[3, 4, 3], 11);
+        let output = reshape.forward(&input);
+        assert_eq!(output.shape(), &[3, 2, 6]);
+        let error = reshape.backward(&output, &mut SGD::new(0.1, 0.0));
+        assert_eq!(error, input);
+
+        // Uma base convolucional alimenta uma cabeça densa dentro da mesma rede
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(Conv2D::new(1, 2, (3, 3), ActivationType::Tanh).with_padding(Padding::Same));
+        nn.add_layer(MaxPool2D::new((2, 2)));
+        nn.add_layer(Flatten::new());
+        nn.add_layer(DenseLayer::new(8, 1, ActivationType::Sigmoid));
+        assert_eq!(nn.output_shape(&[8, 1, 4, 4]), vec![8, 1]);
+
+        // Classifica se a metade superior da imagem é mais clara que a inferior
+        let x = pattern(&[8, 1, 4, 4], 12);
+        let y = ndarray::Array2::from_shape_fn((8, 1), |(i, _)| {
+            let image = x.index_axis(ndarray::Axis(0), i);
+            let top: f32 = image.iter().take(8).sum();
+            let bottom: f32 = image.iter().skip(8).sum();
+            (top > bottom) as u8 as f32
+        })
+        .into_dyn();
+
+        let stats = Arc::new(Mutex::new(TrainingStats::new()));
+        NeuralNetwork::train(
+            Arc::new(Mutex::new(nn)),
+            &x,
+            &y,
+            Arc::new(MeanSquaredError),
+            Arc::new(Mutex::new(Adam::new(0.05, 0.9, 0.999, 1e-8, 0.0))),
+            stats.clone(),
+            TrainConfig::new(30, 4).with_progress(Progress::Silent),
+        )
+        .unwrap();
+        let losses = &stats.lock().unwrap().losses;
+        assert!(losses.last().unwrap() < &losses[0]);
+    }
+
+    #[test]
+    fn test_dropout_modes() {
+        let mut sgd = SGD::new(0.1, 0.0);
+        let input = ndarray::ArrayD::ones(vec![200, 50]);
+
+        // Em modo de inferência, o dropout é a identidade
+        let mut dropout = Dropout::new(0.4).with_seed(1);
+        assert_eq!(dropout.forward(&input), input);
+
+        // No treinamento, descarta ~40% e escala os restantes por 1 / 0.6
+        dropout.set_training(true);
+        let output = dropout.forward(&input);
+        let kept = output.iter().filter(|&&x| x > 0.0).count() as f32 / input.len() as f32;
+        assert_abs_diff_eq!(kept, 0.6, epsilon = 0.02);
+        assert_abs_diff_eq!(output.mean().unwrap(), 1.0, epsilon = 0.05);
+        assert_eq!(dropout.backward(&input, &mut sgd), output);
+
+        // O alpha dropout preserva média zero e variância um
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand::rngs::StdRng::seed_from_u64(2);
+        let x = ndarray::ArrayD::from_shape_simple_fn(vec![200, 50], || {
+            // Box-Muller: amostra de uma normal padrão
+            let (u, v): (f32, f32) = (rng.gen_range(1e-7..1.0), rng.gen());
+            (-2.0 * u.ln()).sqrt() * (2.0 * std::f32::consts::PI * v).cos()
+        });
+        let mut alpha = AlphaDropout::new(0.2).with_seed(3);
+        alpha.set_training(true);
+        let y = alpha.forward(&x);
+        assert_abs_diff_eq!(y.mean().unwrap(), 0.0, epsilon = 0.05);
+        assert_abs_diff_eq!(y.var(0.0), 1.0, epsilon = 0.08);
+
+        // O dropout espacial descarta canais inteiros
+        let mut spatial = SpatialDropout2D::new(0.5).with_seed(4);
+        spatial.set_training(true);
+        let maps = spatial.forward(&ndarray::ArrayD::ones(vec![4, 8, 3, 3]));
+        for channel in maps.lanes(ndarray::Axis(2)) {
+            assert!(channel.iter().all(|&x| x == channel[0]));
+        }
+        assert!(maps.iter().any(|&x| x == 0.0) && maps.iter().any(|&x| x == 2.0));
+
+        // A rede repassa o modo às camadas; novas redes começam em modo de inferência
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(DenseLayer::new(50, 10, ActivationType::ReLU));
+        nn.add_layer(Dropout::new(0.5).with_seed(5));
+        assert!(!nn.is_training());
+        assert_eq!(nn.forward(&input), nn.forward(&input));
+        nn.train_mode();
+        assert!(nn.is_training());
+        assert_ne!(nn.forward(&input), nn.forward(&input));
+        let evaluation = nn.evaluate(
+            &input,
+            &ndarray::ArrayD::zeros(vec![200, 10]),
+            &MeanSquaredError,
+            &[],
+        );
+        assert!(nn.is_training());
+        nn.eval_mode();
+        let expected = nn.forward(&input);
+        assert_abs_diff_eq!(
+            evaluation.loss,
+            MeanSquaredError.loss(&expected, &ndarray::ArrayD::zeros(vec![200, 10]))
+        );
+    }
+
+    #[test]
+    fn test_batch_norm() {
+        let mut bn = BatchNorm2d::new(3);
+        bn.set_training(true);
+        check_gradients(&bn, &pattern(&[4, 3, 2, 3], 13));
+        let mut bn1d = BatchNorm1d::new(5);
+        bn1d.set_training(true);
+        check_gradients(&bn1d, &pattern(&[6, 5], 14));
+
+        // No treinamento, cada canal sai com média zero e variância um
+        let input = pattern(&[8, 3, 4, 4], 15) * 3.0 + 2.0;
+        let output = bn.forward(&input);
+        for channel in 0..3 {
+            let values = output.index_axis(ndarray::Axis(1), channel);
+            assert_abs_diff_eq!(values.mean().unwrap(), 0.0, epsilon = 1e-4);
+            assert_abs_diff_eq!(values.var(0.0), 1.0, epsilon = 1e-3);
+        }
+        assert!(bn.running_mean.iter().all(|&m| m > 0.1));
+
+        // As estatísticas acumuladas são salvas com a rede e usadas na inferência
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(bn);
+        let path = std::env::temp_dir().join("bran_test_batch_norm.bin");
+        let path = path.to_str().unwrap();
+        nn.save(path).unwrap();
+        let mut loaded = NeuralNetwork::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(nn.get_weights(), loaded.get_weights());
+        assert_eq!(loaded.param_count(), 6);
+        let first = loaded.forward(&input);
+        let single = loaded.forward(&input.slice_axis(ndarray::Axis(0), (0..1).into()).to_owned());
+        for (a, b) in first
+            .index_axis(ndarray::Axis(0), 0)
+            .iter()
+            .zip(single.iter())
+        {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-6);
+        }
+        check_gradients(&BatchNorm2d::new(3), &pattern(&[2, 3, 2, 2], 16));
+    }
+
+    #[test]
+    fn test_layer_rms_group_norm() {
+        let sequences = pattern(&[2, 3, 4], 17);
+        let mut layer_norm = LayerNorm::new(&[4]);
+        layer_norm.set_weights(vec![pattern(&[4], 18) + 1.0, pattern(&[4], 19)]);
+        check_gradients(&layer_norm, &sequences);
+        check_gradients(&LayerNorm::new(&[3, 4]), &sequences);
+
+        // Cada amostra sai com média zero e variância um nos eixos normalizados
+        let output = LayerNorm::new(&[4]).forward(&(sequences.clone() * 5.0 + 1.0));
+        for row in output.lanes(ndarray::Axis(2)) {
+            assert_abs_diff_eq!(row.mean().unwrap(), 0.0, epsilon = 1e-5);
+            assert_abs_diff_eq!(row.var(0.0), 1.0, epsilon = 1e-3);
+        }
+
+        let mut rms_norm = RMSNorm::new(&[4]);
+        rms_norm.set_weights(vec![pattern(&[4], 20) + 1.0]);
+        check_gradients(&rms_norm, &sequences);
+        let output = RMSNorm::new(&[4]).forward(&sequences);
+        for row in output.lanes(ndarray::Axis(2)) {
+            assert_abs_diff_eq!(row.mapv(|x| x * x).mean().unwrap(), 1.0, epsilon = 1e-3);
+        }
+
+        let maps = pattern(&[2, 6, 2, 3], 21);
+        let mut group_norm = GroupNorm::new(3, 6);
+        group_norm.set_weights(vec![pattern(&[6], 22) + 1.0, pattern(&[6], 23)]);
+        check_gradients(&group_norm, &maps);
+        assert_eq!(group_norm.param_count(), 12);
+
+        // Com um grupo por canal, cada mapa é normalizado separadamente
+        let output = GroupNorm::new(6, 6).forward(&maps);
+        for map in output
+            .lanes(ndarray::Axis(3))
+            .into_iter()
+            .collect::<Vec<_>>()
+            .chunks(2)
+        {
+            let mean = map.iter().flat_map(|row| row.iter()).sum::<f32>() / 6.0;
+            assert_abs_diff_eq!(mean, 0.0, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_embedding() {
+        let table = arr2(&[[1.0, 0.0], [0.0, 2.0], [3.0, 4.0], [5.0, 5.0]]);
+        let mut embedding = Embedding::from_pretrained(table.clone()).with_padding_idx(0);
+        let input = arr2(&[[1.0, 2.0, 0.0], [2.0, 2.0, 0.0]]).into_dyn();
+        let output = embedding.forward(&input);
+        assert_eq!(output.shape(), &[2, 3, 2]);
+        assert_eq!(output.slice(s![1, 0, ..]), arr1(&[3.0, 4.0]));
+        assert_eq!(output.slice(s![0, 2, ..]), arr1(&[0.0, 0.0]));
+
+        // Apenas as linhas consultadas mudam; a de preenchimento permanece zero
+        let mut sgd = SGD::new(1.0, 0.0);
+        let input_error = embedding.backward(&ArrayD::ones(output.raw_dim()), &mut sgd);
+        assert_eq!(input_error, ArrayD::zeros(input.raw_dim()));
+        let updated = &embedding.weights()[0];
+        assert_eq!(updated.slice(s![0, ..]), arr1(&[0.0, 0.0]));
+        assert_eq!(updated.slice(s![1, ..]), arr1(&[-1.0, 1.0]));
+        assert_eq!(updated.slice(s![2, ..]), arr1(&[0.0, 1.0]));
+        assert_eq!(updated.slice(s![3, ..]), arr1(&[5.0, 5.0]));
+
+        // Adam "preguiçoso" conta um passo e não altera linhas ausentes do lote
+        let mut adam = Adam::new(0.1, 0.9, 0.999, 1e-8, 0.0);
+        embedding.forward(&arr1(&[1.0]).into_dyn());
+        embedding.backward(&arr2(&[[1.0, -1.0]]).into_dyn(), &mut adam);
+        assert_eq!(adam.t, 1);
+        let updated = &embedding.weights()[0];
+        assert_abs_diff_eq!(updated[[1, 0]], -1.1, epsilon = 1e-5);
+        assert_abs_diff_eq!(updated[[1, 1]], 1.1, epsilon = 1e-5);
+        assert_eq!(updated.slice(s![3, ..]), arr1(&[5.0, 5.0]));
+
+        // A norma máxima reescala os vetores consultados
+        let mut clipped = Embedding::from_pretrained(table.clone()).with_max_norm(1.0);
+        let output = clipped.forward(&arr1(&[2.0, 0.0]).into_dyn());
+        assert_abs_diff_eq!(output[[0, 0]], 0.6, epsilon = 1e-5);
+        assert_abs_diff_eq!(output[[0, 1]], 0.8, epsilon = 1e-5);
+        assert_eq!(output.slice(s![1, ..]), arr1(&[1.0, 0.0]));
+
+        let mut loaded = Embedding::new(4, 2);
+        assert_eq!(loaded.param_count(), 8);
+        loaded.load_pretrained(table).unwrap();
+        assert_eq!(
+            loaded.forward(&arr1(&[3.0]).into_dyn()),
+            arr2(&[[5.0, 5.0]]).into_dyn()
+        );
+        assert!(loaded.load_pretrained(Array2::zeros((3, 2))).is_err());
+    }
+
+    #[test]
+    fn test_recurrent_layers() {
+        let input = pattern(&[2, 4, 3], 24);
+        for return_sequences in [false, true] {
+            let rnn =
+                SimpleRNN::new(3, 3, ActivationType::Tanh).with_return_sequences(return_sequences);
+            check_gradients(&rnn, &input);
+            check_gradients(
+                &LSTM::new(3, 3).with_return_sequences(return_sequences),
+                &input,
+            );
+            check_gradients(
+                &GRU::new(3, 3).with_return_sequences(return_sequences),
+                &input,
+            );
+        }
+        assert_eq!(LSTM::new(3, 5).output_shape(&[2, 4, 3]), vec![2, 5]);
+        assert_eq!(
+            GRU::new(3, 5)
+                .with_return_sequences(true)
+                .output_shape(&[2, 4, 3]),
+            vec![2, 4, 5]
+        );
+        assert_eq!(LSTM::new(3, 5).param_count(), 4 * (5 * 3 + 5 * 5 + 5));
+        assert_eq!(GRU::new(3, 5).param_count(), 3 * (5 * 3 + 5 * 5 + 2 * 5));
+
+        // Com BPTT truncado em janelas de 2 instantes, o erro do último estado não chega à
+        // primeira janela
+        let mut truncated = LSTM::new(3, 3).with_truncation(2);
+        let output = truncated.forward(&input);
+        let input_error = truncated.backward(
+            &ndarray::ArrayD::ones(output.raw_dim()),
+            &mut SGD::new(0.0, 0.0),
+        );
+        assert!(input_error.slice(s![.., ..2, ..]).iter().all(|&e| e == 0.0));
+        assert!(input_error.slice(s![.., 2.., ..]).iter().any(|&e| e != 0.0));
+
+        // Estado inicial: gradiente numérico e estado final do LSTM
+        let initial = pattern(&[2, 3], 25).into_dimensionality().unwrap();
+        let mut gru = GRU::new(3, 3);
+        gru.set_initial_state(vec![initial.clone()]);
+        let r = pattern(&[2, 3], 26);
+        let mut probe = gru.clone();
+        let output = gru.forward(&input);
+        gru.backward(&r, &mut SGD::new(0.0, 0.0));
+        let grad = &gru.initial_state_grad().unwrap()[0];
+        let eps = 1e-2;
+        for i in 0..initial.len() {
+            let mut shifted = initial.clone();
+            shifted.as_slice_mut().unwrap()[i] += eps;
+            probe.set_initial_state(vec![shifted.clone()]);
+            let plus = (probe.forward(&input) * &r).sum();
+            shifted.as_slice_mut().unwrap()[i] -= 2.0 * eps;
+            probe.set_initial_state(vec![shifted]);
+            let minus = (probe.forward(&input) * &r).sum();
+            assert_abs_diff_eq!(
+                grad.as_slice().unwrap()[i],
+                (plus - minus) / (2.0 * eps),
+                epsilon = 1e-2
+            );
+        }
+        probe.clear_initial_state();
+        assert!(probe.forward(&input) != output);
+
+        let mut lstm = LSTM::new(3, 3);
+        let output = lstm.forward(&input);
+        let state = lstm.final_state().unwrap();
+        assert_eq!(state.len(), 2);
+        assert_eq!(state[0].clone().into_dyn(), output);
+    }
+
+    #[test]
+    fn test_bidirectional_time_distributed() {
+        let input = pattern(&[2, 4, 3], 27);
+        for merge_mode in [MergeMode::Concat, MergeMode::Sum, MergeMode::Mean] {
+            for return_sequences in [false, true] {
+                let layer = Bidirectional::new(
+                    GRU::new(3, 2).with_return_sequences(return_sequences),
+                    GRU::new(3, 2).with_return_sequences(return_sequences),
+                    merge_mode,
+                );
+                check_gradients(&layer, &input);
+            }
+        }
+
+        // A direção reversa enxerga a sequência invertida, alinhada ao instante original
+        let mut forward = LSTM::new(3, 2).with_return_sequences(true);
+        let mut backward = LSTM::new(3, 2).with_return_sequences(true);
+        let mut bidirectional =
+            Bidirectional::new(forward.clone(), backward.clone(), MergeMode::Concat);
+        let output = bidirectional.forward(&input);
+        assert_eq!(output.shape(), &[2, 4, 4]);
+        assert_eq!(
+            output.slice(s![.., .., ..2]).into_dyn(),
+            forward.forward(&input)
+        );
+        let reversed = input.slice(s![.., ..;-1, ..]).to_owned().into_dyn();
+        let expected = backward.forward(&reversed);
+        assert_eq!(
+            output.slice(s![.., .., 2..]),
+            expected.slice(s![.., ..;-1, ..])
+        );
+        assert_eq!(
+            bidirectional.param_count(),
+            2 * LSTM::new(3, 2).param_count()
+        );
+
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(bidirectional);
+        let path = std::env::temp_dir().join("bran_test_bidirectional.bin");
+        let path = path.to_str().unwrap();
+        nn.save(path).unwrap();
+        let mut loaded = NeuralNetwork::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded.forward(&input), output);
+
+        // TimeDistributed aplica os mesmos pesos a cada instante
+        let dense = DenseLayer::new(3, 5, ActivationType::Tanh);
+        let mut distributed = TimeDistributed::new(dense.clone());
+        let output = distributed.forward(&input);
+        assert_eq!(output.shape(), &[2, 4, 5]);
+        let step = dense
+            .clone()
+            .forward(&input.index_axis(ndarray::Axis(1), 2).to_owned());
+        assert_eq!(output.index_axis(ndarray::Axis(1), 2), step);
+        let linear = TimeDistributed::new(DenseLayer::new(3, 5, ActivationType::Linear));
+        check_gradients(&linear, &input);
+
+        let frames = pattern(&[2, 3, 1, 4, 4], 28);
+        let conv = TimeDistributed::new(
+            Conv2D::new(1, 2, (3, 3), ActivationType::Tanh).with_padding(Padding::Same),
+        );
+        assert_eq!(conv.output_shape(&[2, 3, 1, 4, 4]), vec![2, 3, 2, 4, 4]);
+        check_gradients(&conv, &frames);
+    }
+
+    #[test]
+    fn test_multi_head_attention() {
+        let input = pattern(&[2, 3, 4], 29);
+        check_gradients(&MultiHeadAttention::new(4, 2), &input);
+        check_gradients(&MultiHeadAttention::new(4, 2).with_causal(true), &input);
+        let mut padded = MultiHeadAttention::new(4, 1);
+        padded.set_padding_mask(arr2(&[[false, false, true], [false, true, true]]));
+        check_gradients(&padded, &input);
+        assert_eq!(MultiHeadAttention::new(4, 2).param_count(), 4 * (16 + 4));
+
+        // Com a máscara causal, alterar o último instante não muda os anteriores
+        let mut causal = MultiHeadAttention::new(4, 2).with_causal(true);
+        let output = causal.forward(&input);
+        let weights = causal.attention_weights().unwrap();
+        assert_eq!(weights.shape(), &[2, 2, 3, 3]);
+        assert_eq!(weights[[1, 0, 0, 1]], 0.0);
+        for row in weights.lanes(ndarray::Axis(3)) {
+            assert_abs_diff_eq!(row.sum(), 1.0, epsilon = 1e-5);
+        }
+        let mut changed = input.clone();
+        changed.slice_mut(s![.., 2, ..]).fill(5.0);
+        let changed_output = causal.forward(&changed);
+        assert_eq!(
+            output.slice(s![.., ..2, ..]),
+            changed_output.slice(s![.., ..2, ..])
+        );
+
+        // Chaves de preenchimento recebem peso zero
+        padded.forward(&input);
+        let weights = padded.attention_weights().unwrap();
+        assert_eq!(weights[[0, 0, 1, 2]], 0.0);
+        assert_eq!(weights[[1, 0, 2, 1]], 0.0);
+        assert_abs_diff_eq!(weights[[1, 0, 2, 0]], 1.0, epsilon = 1e-6);
+
+        // Atenção cruzada: gradiente numérico em relação às chaves/valores
+        let memory = pattern(&[2, 5, 4], 30);
+        let mut cross = MultiHeadAttention::new(4, 2);
+        let mut probe = cross.clone();
+        let r = pattern(&[2, 3, 4], 31);
+        cross.forward_attention(&input, &memory, &memory);
+        let (_, key_error, value_error) = cross.backward_attention(&r, &mut SGD::new(0.0, 0.0));
+        let memory_error = key_error + value_error;
+        let eps = 1e-2;
+        for i in 0..memory.len() {
+            let mut plus = memory.clone();
+            plus.as_slice_mut().unwrap()[i] += eps;
+            let mut minus = memory.clone();
+            minus.as_slice_mut().unwrap()[i] -= eps;
+            let numeric = ((probe.forward_attention(&input, &plus, &plus) * &r).sum()
+                - (probe.forward_attention(&input, &minus, &minus) * &r).sum())
+                / (2.0 * eps);
+            assert_abs_diff_eq!(memory_error.as_slice().unwrap()[i], numeric, epsilon = 1e-2);
+        }
+
+        // O dropout nos pesos de atenção só atua em modo de treinamento
+        let mut dropout = MultiHeadAttention::new(4, 2).with_dropout(0.5).with_seed(7);
+        let eval = dropout.forward(&input);
+        dropout.set_training(true);
+        assert!(dropout.forward(&input) != eval);
+        dropout.set_training(false);
+        assert_eq!(dropout.forward(&input), eval);
+    }
+
+    #[test]
+    fn test_transformer_blocks() {
+        let input = pattern(&[2, 3, 4], 32);
+        let encoder = TransformerEncoderLayer::new(4, 2, 6).with_activation(ActivationType::Tanh);
+        check_gradients(&encoder, &input);
+        check_gradients(&encoder.clone().with_norm_first(true), &input);
+        assert_eq!(encoder.output_shape(&[2, 3, 4]), vec![2, 3, 4]);
+        // Atenção (4 projeções), duas normalizações e a rede feed-forward
+        assert_eq!(
+            encoder.param_count(),
+            4 * (16 + 4) + 2 * 8 + (6 * 4 + 6) + (4 * 6 + 4)
+        );
+
+        let memory = pattern(&[2, 5, 4], 33);
+        let mut decoder = TransformerDecoderLayer::new(4, 2, 6)
+            .with_activation(ActivationType::Tanh)
+            .with_norm_first(true);
+        decoder.set_memory(memory.clone());
+        check_gradients(&decoder, &input);
+
+        // Gradiente numérico em relação à memória do codificador
+        let mut probe = decoder.clone();
+        let r = pattern(&[2, 3, 4], 34);
+        decoder.forward(&input);
+        decoder.backward(&r, &mut SGD::new(0.0, 0.0));
+        let memory_grad = decoder.memory_grad().unwrap().clone();
+        let eps = 1e-2;
+        for i in 0..memory.len() {
+            let mut loss = |delta: f32| {
+                let mut shifted = memory.clone();
+                shifted.as_slice_mut().unwrap()[i] += delta;
+                probe.set_memory(shifted);
+                (probe.forward(&input) * &r).sum()
+            };
+            let numeric = (loss(eps) - loss(-eps)) / (2.0 * eps);
+            assert_abs_diff_eq!(memory_grad.as_slice().unwrap()[i], numeric, epsilon = 1e-2);
+        }
+
+        // A autoatenção do decodificador é causal
+        let output = probe.forward(&input);
+        let mut changed = input.clone();
+        changed.slice_mut(s![.., 2, ..]).fill(5.0);
+        assert_eq!(
+            output.slice(s![.., ..2, ..]),
+            probe.forward(&changed).slice(s![.., ..2, ..])
+        );
+
+        // Codificações posicionais
+        let mut sinusoidal = SinusoidalPositionalEncoding::new(8, 4);
+        let encoded = sinusoidal.forward(&input);
+        assert_abs_diff_eq!(encoded[[0, 0, 1]] - input[[0, 0, 1]], 1.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            encoded[[1, 2, 2]] - input[[1, 2, 2]],
+            (2.0f32 / 100.0).sin(),
+            epsilon = 1e-6
+        );
+        assert_eq!(sinusoidal.param_count(), 0);
+        check_gradients(&sinusoidal, &input);
+
+        let learned = LearnedPositionalEncoding::new(8, 4);
+        assert_eq!(learned.param_count(), 32);
+        check_gradients(&learned, &input);
+    }
+
+    #[test]
+    fn test_graph_model() {
+        // Bloco residual com porta multiplicativa e concatenação: `x` e `h` alimentam vários nós
+        let mut graph = GraphModel::new();
+        let x = graph.input();
+        let h = graph.layer(DenseLayer::new(3, 3, ActivationType::Linear), x);
+        let gate = graph.layer(DenseLayer::new(3, 3, ActivationType::Linear), h);
+        let gated = graph.multiply(&[h, gate]);
+        let residual = graph.add(&[x, gated, x]);
+        let joined = graph.concat(&[residual, h], 1);
+        let y = graph.layer(DenseLayer::new(6, 2, ActivationType::Linear), joined);
+        graph.output(y);
+
+        let input = pattern(&[4, 3], 35);
+        check_gradients(&graph, &input);
+        assert_eq!(graph.output_shape(&[4, 3]), vec![4, 2]);
+        assert_eq!(graph.param_count(), 12 + 12 + 14);
+
+        // A saída combina os nós como esperado
+        let mut probe = graph.clone();
+        let output = probe.forward(&input);
+        let dense = |node: NodeId, x: &ArrayD<f32>| {
+            let mut layer = graph.node_layer(node).unwrap().clone();
+            layer.forward(x)
+        };
+        let h_value = dense(h, &input);
+        let residual_value = &input * 2.0 + &h_value * &dense(gate, &h_value);
+        let joined_value =
+            ndarray::concatenate(ndarray::Axis(1), &[residual_value.view(), h_value.view()])
+                .unwrap();
+        for (a, b) in output.iter().zip(dense(y, &joined_value).iter()) {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-5);
+        }
+
+        // Várias entradas e saídas; entradas sem influência recebem erro zero
+        let mut multi = GraphModel::new();
+        let a = multi.input();
+        let b = multi.input();
+        multi.input(); // não influencia nenhuma saída
+        let sum = multi.add(&[a, b]);
+        let product = multi.multiply(&[a, b]);
+        multi.output(sum);
+        multi.output(product);
+        let (va, vb) = (pattern(&[2, 3], 36), pattern(&[2, 3], 37));
+        let outputs = multi.forward_inputs(&[va.clone(), vb.clone(), ArrayD::zeros(vec![2, 3])]);
+        assert_eq!(outputs[1], &va * &vb);
+        let ones = ArrayD::ones(vec![2, 3]);
+        let errors = multi.backward_outputs(&[ones.clone(), ones.clone()], &mut SGD::new(0.1, 0.0));
+        assert_eq!(errors[0], &ones + &vb);
+        assert_eq!(errors[1], &ones + &va);
+        assert_eq!(errors[2], ArrayD::<f32>::zeros(vec![2, 3]));
+        assert_eq!(multi.num_inputs(), 3);
+
+        // Como camada de uma NeuralNetwork, com pesos salvos e restaurados
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(graph.clone());
+        nn.add_layer(DenseLayer::new(2, 1, ActivationType::Linear));
+        let weights = nn.get_weights();
+        assert_eq!(weights[0].len(), 6);
+        let mut other = NeuralNetwork::new();
+        other.add_layer(graph);
+        other.add_layer(DenseLayer::new(2, 1, ActivationType::Linear));
+        other.set_weights(weights);
+        assert_eq!(other.forward(&input), nn.forward(&input));
+    }
+
+    #[test]
+    fn test_multi_input_output_model() {
+        use std::collections::HashMap;
+        use std::sync::{Arc, Mutex};
+
+        // Entradas tabular e "textual" combinadas; uma saída de classe e uma de regressão
+        let mut model = GraphModel::new();
+        let tabular = model.named_input("tabular");
+        let text = model.named_input("text");
+        let a = model.layer(DenseLayer::new(3, 4, ActivationType::Tanh), tabular);
+        let b = model.layer(DenseLayer::new(5, 4, ActivationType::Tanh), text);
+        let joined = model.concat(&[a, b], 1);
+        let class = model.layer(DenseLayer::new(8, 1, ActivationType::Sigmoid), joined);
+        let value = model.layer(DenseLayer::new(8, 1, ActivationType::Linear), joined);
+        model.named_output("class", class);
+        model.named_output("value", value);
+        assert_eq!(model.input_names(), ["tabular", "text"]);
+        assert_eq!(model.output_names(), ["class", "value"]);
+
+        let x_tabular = pattern(&[8, 3], 38);
+        let x_text = pattern(&[8, 5], 39);
+        let y_class = x_tabular
+            .slice(s![.., 0..1])
+            .mapv(|v| (v > 0.0) as u8 as f32);
+        let y_value = x_text.slice(s![.., 0..1]).to_owned() * 2.0;
+        let inputs = HashMap::from([
+            ("tabular".to_string(), x_tabular),
+            ("text".to_string(), x_text),
+        ]);
+        let targets = HashMap::from([
+            ("class".to_string(), y_class.into_dyn()),
+            ("value".to_string(), y_value.into_dyn()),
+        ]);
+
+        let outputs = model.forward_named(&inputs);
+        assert_eq!(outputs["class"].shape(), &[8, 1]);
+        assert_eq!(outputs["value"].shape(), &[8, 1]);
+
+        // A perda total é a soma ponderada das perdas de cada saída
+        let losses = MultiOutputLoss::new()
+            .with_output("class", CrossEntropyLoss, 1.0)
+            .with_output("value", MeanSquaredError, 0.5);
+        let before = model.evaluate_named(&inputs, &targets, &losses);
+        let class_loss = before.metric("class_loss").unwrap();
+        let value_loss = before.metric("value_loss").unwrap();
+        assert_abs_diff_eq!(before.loss, class_loss + 0.5 * value_loss, epsilon = 1e-6);
+
+        // O treinamento usa o mesmo laço de NeuralNetwork::train, com validação e callbacks
+        let fit = |model: &mut GraphModel, losses: &MultiOutputLoss, config: TrainConfig| {
+            let stats = Arc::new(Mutex::new(TrainingStats::new()));
+            let optimizer = Arc::new(Mutex::new(SGD::new(0.1, 0.0)));
+            let config = config.with_progress(Progress::Silent);
+            model
+                .fit(&inputs, &targets, losses, optimizer, stats.clone(), config)
+                .map(|_| Arc::try_unwrap(stats).ok().unwrap().into_inner().unwrap())
+        };
+        let stats = fit(
+            &mut model,
+            &losses,
+            TrainConfig::new(30, 4)
+                .with_shuffle(3)
+                .with_validation_split(0.25)
+                .with_metrics(vec![MetricType::MeanAbsoluteError]),
+        )
+        .unwrap();
+        assert_eq!(stats.losses.len(), 30);
+        assert_eq!(stats.val_losses.len(), 30);
+        assert_eq!(
+            stats.metrics.keys().collect::<Vec<_>>(),
+            ["class_loss", "class_mae", "value_loss", "value_mae"]
+        );
+        assert!(stats.val_metrics.contains_key("value_loss"));
+        assert!(stats.accuracies[0].is_nan());
+        let after = model.evaluate_named(&inputs, &targets, &losses);
+        assert!(after.loss < before.loss);
+
+        // Uma saída com peso zero não atualiza a camada que só ela usa
+        let head = model.node_layer(value).unwrap().weights();
+        let frozen = MultiOutputLoss::new()
+            .with_output("class", CrossEntropyLoss, 1.0)
+            .with_output("value", MeanSquaredError, 0.0);
+        // As métricas por saída também podem ser monitoradas pelos callbacks
+        let config = TrainConfig::new(20, 4)
+            .with_early_stopping(EarlyStopping::new("class_loss", 1).with_min_delta(f32::MAX));
+        let stats = fit(&mut model, &frozen, config).unwrap();
+        assert_eq!(stats.losses.len(), 2);
+        assert_eq!(model.node_layer(value).unwrap().weights(), head);
+
+        // Argumentos inválidos geram erros em vez de pânicos
+        assert!(fit(&mut model, &losses, TrainConfig::new(1, 0)).is_err());
+        let missing = MultiOutputLoss::new().with_output("other", MeanSquaredError, 1.0);
+        assert!(fit(&mut model, &missing, TrainConfig::new(1, 4)).is_err());
+        let misspelled =
+            TrainConfig::new(1, 4).with_early_stopping(EarlyStopping::new("loss_class", 1));
+        assert!(fit(&mut model, &losses, misspelled).is_err());
+        assert_eq!(model.output_names(), ["class", "value"]);
+    }
+
+    #[test]
+    fn test_layer_freezing() {
+        // Uma camada congelada propaga o mesmo erro, mas não altera seus parâmetros
+        let cases: Vec<(LayerType, ArrayD<f32>)> = vec![
+            (
+                DenseLayer::new(3, 4, ActivationType::Tanh).into(),
+                pattern(&[5, 3], 30),
+            ),
+            (
+                Conv2D::new(2, 3, (2, 2), ActivationType::ReLU).into(),
+                pattern(&[2, 2, 3, 3], 31),
+            ),
+            (LayerNorm::new(&[4]).into(), pattern(&[2, 3, 4], 32)),
+            (LSTM::new(3, 4).into(), pattern(&[2, 5, 3], 33)),
+            (
+                TransformerEncoderLayer::new(4, 2, 8).into(),
+                pattern(&[2, 3, 4], 34),
+            ),
+        ];
+        for (layer, input) in cases {
+            let mut trainable = layer.clone();
+            let mut frozen = layer;
+            frozen.set_trainable(false);
+            assert!(!frozen.trainable());
+
+            let weights = frozen.weights();
+            let error = pattern(trainable.forward(&input).shape(), 35);
+            frozen.forward(&input);
+            let expected = trainable.backward(&error, &mut SGD::new(0.5, 0.0));
+            let input_error = frozen.backward(&error, &mut SGD::new(0.5, 0.0));
+            assert_eq!(frozen.weights(), weights);
+            assert_ne!(trainable.weights(), weights);
+            for (a, b) in input_error.iter().zip(expected.iter()) {
+                assert_abs_diff_eq!(a, b, epsilon = 1e-5);
+            }
+        }
+
+        // Congela as duas primeiras camadas para ajustar apenas a última
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(DenseLayer::new(3, 4, ActivationType::Tanh));
+        nn.add_layer(LayerNorm::new(&[4]));
+        nn.add_layer(DenseLayer::new(4, 2, ActivationType::Linear));
+        nn.freeze(..2);
+        assert_eq!(nn.trainable_param_count(), 4 * 2 + 2);
+        assert_eq!(nn.param_count(), 3 * 4 + 4 + 2 * 4 + 4 * 2 + 2);
+
+        let before = nn.get_weights();
+        let input = pattern(&[6, 3], 36);
+        let output = nn.forward(&input);
+        nn.backward(&output, &mut SGD::new(0.1, 0.0)).unwrap();
+        let after = nn.get_weights();
+        assert_eq!(after[..2], before[..2]);
+        assert_ne!(after[2], before[2]);
+
+        // O estado congelado é salvo com a rede
+        let path = std::env::temp_dir().join("bran_test_layer_freezing.bin");
+        let path = path.to_str().unwrap();
+        nn.save(path).unwrap();
+        let mut loaded = NeuralNetwork::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert!(!loaded.layers[0].trainable() && loaded.layers[2].trainable());
+
+        loaded.unfreeze(1..);
+        assert_eq!(loaded.trainable_param_count(), 2 * 4 + 4 * 2 + 2);
+        loaded.unfreeze(..);
+        assert_eq!(loaded.trainable_param_count(), loaded.param_count());
+    }
+
+    #[test]
+    fn test_dense_bias_and_weight_tying() {
+        // Sem viés, a camada é linear e não guarda nem atualiza vieses
+        let mut layer = DenseLayer::new(3, 2, ActivationType::Linear).with_bias(false);
+        assert!(!layer.use_bias());
+        assert_eq!(layer.param_count(), 6);
+        let zeros = layer.forward(&ArrayD::zeros(vec![4, 3]));
+        assert!(zeros.iter().all(|&x| x == 0.0));
+        check_gradients(&layer, &pattern(&[4, 3], 40));
+        assert_eq!(
+            DenseLayer::new(3, 2, ActivationType::Linear)
+                .with_bias(false)
+                .with_bias(true)
+                .biases
+                .len(),
+            2
+        );
+
+        // Autoencoder com o decodificador usando a transposta dos pesos do codificador
+        let mut untied = NeuralNetwork::new();
+        untied.add_layer(DenseLayer::new(4, 2, ActivationType::Tanh).with_bias(false));
+        untied.add_layer(DenseLayer::new(2, 4, ActivationType::Linear));
+        let mut tied = untied.clone();
+        assert!(tied.tie_weights(0, 1, false).is_err());
+        assert!(tied.tie_weights(0, 0, true).is_err());
+        tied.tie_weights(0, 1, true).unwrap();
+        assert!(tied.tie_weights(0, 1, true).is_err());
+        assert_eq!(tied.param_count(), 8 + 4);
+        let encoder = tied.get_weights()[0][0].clone();
+        untied.set_weights(tied.get_weights());
+        let initial = tied.clone();
+
+        // Com SGD, a atualização única com os gradientes somados equivale à soma das
+        // atualizações que cada camada faria separadamente
+        let input = pattern(&[6, 4], 41);
+        let error = pattern(&[6, 4], 42);
+        for network in [&mut tied, &mut untied] {
+            network.forward(&input);
+            network.backward(&error, &mut SGD::new(0.1, 0.0)).unwrap();
+        }
+        let separate = untied.get_weights();
+        let shared = tied.get_weights();
+        let expected = &separate[0][0] + &separate[1][0].t() - &encoder;
+        for (a, b) in shared[0][0].iter().zip(expected.iter()) {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-5);
+        }
+        assert_eq!(shared[1][0], shared[0][0].t());
+        assert_eq!(shared[1][1], separate[1][1]);
+
+        // Com a camada target congelada, sua parte do gradiente ainda chega aos pesos
+        // compartilhados, mas o viés dela não muda
+        let mut frozen = initial.clone();
+        frozen.freeze(1..2);
+        frozen.forward(&input);
+        frozen.backward(&error, &mut SGD::new(0.1, 0.0)).unwrap();
+        let weights = frozen.get_weights();
+        for (a, b) in weights[0][0].iter().zip(expected.iter()) {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-5);
+        }
+        assert_eq!(weights[1][0], weights[0][0].t());
+        assert_eq!(weights[1][1], initial.get_weights()[1][1]);
+
+        // O compartilhamento é salvo com a rede
+        let path = std::env::temp_dir().join("bran_test_weight_tying.bin");
+        let path = path.to_str().unwrap();
+        tied.save(path).unwrap();
+        let mut loaded = NeuralNetwork::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded.ties(), tied.ties());
+        loaded.forward(&input);
+        loaded
+            .backward(&error, &mut Adam::new(0.01, 0.9, 0.999, 1e-8, 0.0))
+            .unwrap();
+        let weights = loaded.get_weights();
+        assert_ne!(weights[0][0], shared[0][0]);
+        assert_eq!(weights[1][0], weights[0][0].t());
+
+        // Alterar as camadas depois de tie_weights invalida o compartilhamento, o que é
+        // reportado como erro em vez de atualizar as camadas erradas
+        loaded.validate_ties().unwrap();
+        let mut shifted = loaded.clone();
+        shifted
+            .layers
+            .insert(0, DenseLayer::new(4, 4, ActivationType::Linear).into());
+        assert!(shifted.validate_ties().is_err());
+        let before = shifted.get_weights();
+        shifted.forward(&pattern(&[6, 4], 43));
+        assert!(shifted.backward(&error, &mut SGD::new(0.1, 0.0)).is_err());
+        assert_eq!(shifted.get_weights(), before);
+        shifted.save(path).unwrap();
+        assert!(NeuralNetwork::load(path).is_err());
+        std::fs::remove_file(path).unwrap();
+
+        let mut replaced = loaded.clone();
+        replaced.layers[1] = Dropout::new(0.1).into();
+        assert!(replaced.validate_ties().is_err());
+
+        // Embedding compartilhado com a camada de saída: a tabela (vocab, dim) tem a forma dos
+        // pesos da camada densa dim -> vocab
+        let mut untied = NeuralNetwork::new();
+        untied.add_layer(Embedding::new(5, 3).with_padding_idx(0));
+        untied.add_layer(DenseLayer::new(3, 5, ActivationType::Linear));
+        let mut tied = untied.clone();
+        assert!(tied.tie_weights(0, 1, true).is_err());
+        let mut limited = untied.clone();
+        limited.layers[0] = Embedding::new(5, 3).with_max_norm(1.0).into();
+        assert!(limited.tie_weights(0, 1, false).is_err());
+        tied.tie_weights(0, 1, false).unwrap();
+        assert_eq!(tied.param_count(), 15 + 5);
+        untied.set_weights(tied.get_weights());
+        let table = tied.get_weights()[0][0].clone();
+
+        // Os gradientes da camada densa chegam a todas as linhas da tabela, exceto à de
+        // preenchimento, inclusive às que não aparecem no lote
+        let input = arr1(&[1.0, 2.0, 2.0, 4.0]).into_dyn();
+        let error = pattern(&[4, 5], 44);
+        for network in [&mut tied, &mut untied] {
+            network.forward(&input);
+            network.backward(&error, &mut SGD::new(0.1, 0.0)).unwrap();
+        }
+        let separate = untied.get_weights();
+        let shared = tied.get_weights();
+        let mut expected = &separate[0][0] + &separate[1][0] - &table;
+        expected.index_axis_mut(ndarray::Axis(0), 0).fill(0.0);
+        for (a, b) in shared[0][0].iter().zip(expected.iter()) {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-5);
+        }
+        assert_ne!(
+            shared[0][0].index_axis(ndarray::Axis(0), 3),
+            table.index_axis(ndarray::Axis(0), 3)
+        );
+        assert_eq!(shared[1][0], shared[0][0]);
+        assert_eq!(shared[1][1], separate[1][1]);
+    }
+
+    #[test]
+    fn test_weight_constraints_and_normalization() {
+        // As restrições valem desde a criação e após cada atualização
+        let mut dense = DenseLayer::new(6, 4, ActivationType::Linear)
+            .with_constraint(WeightConstraint::MaxNorm(0.5));
+        dense.forward(&pattern(&[5, 6], 50));
+        dense.backward(&(pattern(&[5, 4], 51) * 10.0), &mut SGD::new(1.0, 0.0));
+        for row in dense.weights.outer_iter() {
+            assert!(row.dot(&row).sqrt() <= 0.5 + 1e-5);
+        }
+        let mut conv = Conv2D::new(2, 3, (2, 2), ActivationType::Linear)
+            .with_constraint(WeightConstraint::NonNeg);
+        assert!(conv.kernel.value.iter().all(|&w| w >= 0.0));
+        let output = conv.forward(&pattern(&[2, 2, 4, 4], 52));
+        conv.backward(&pattern(output.shape(), 53), &mut SGD::new(1.0, 0.0));
+        assert!(conv.kernel.value.iter().all(|&w| w >= 0.0));
+
+        // A normalização de pesos preserva a saída inicial e adiciona um ganho por neurônio
+        let input = pattern(&[3, 4], 54);
+        let mut plain = DenseLayer::new(4, 3, ActivationType::Linear);
+        let mut normalized = plain.clone().with_weight_norm();
+        assert_eq!(normalized.param_count(), 4 * 3 + 3 + 3);
+        for (a, b) in plain
+            .forward(&input)
+            .iter()
+            .zip(normalized.forward(&input).iter())
+        {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-5);
+        }
+        check_gradients(&normalized, &input);
+
+        // Pesos, vieses e ganhos são atualizados em um único passo do Adam
+        let mut adam = Adam::new(0.01, 0.9, 0.999, 1e-8, 0.0);
+        normalized.forward(&input);
+        normalized.backward(&pattern(&[3, 3], 61), &mut adam);
+        assert_eq!(adam.state().step, 1);
+        check_gradients(
+            &Conv2D::new(2, 2, (2, 2), ActivationType::Linear).with_weight_norm(),
+            &pattern(&[1, 2, 3, 3], 55),
+        );
+
+        // Com normalização espectral, o maior valor singular dos pesos efetivos é um
+        let mut spectral = DenseLayer::new(5, 4, ActivationType::Linear)
+            .with_bias(false)
+            .with_spectral_norm(50);
+        spectral.set_weights(vec![pattern(&[4, 5], 56) * 3.0, ArrayD::zeros(vec![0])]);
+        let effective = spectral
+            .forward(&Array2::eye(5).into_dyn())
+            .into_dimensionality::<ndarray::Ix2>()
+            .unwrap();
+        let mut v = ndarray::Array1::ones(4);
+        for _ in 0..500 {
+            v = effective.t().dot(&effective.dot(&v));
+            v /= v.dot(&v).sqrt();
+        }
+        let image = effective.dot(&v);
+        assert_abs_diff_eq!(image.dot(&image).sqrt(), 1.0, epsilon = 1e-3);
+        check_gradients(&spectral, &pattern(&[3, 5], 57));
+        check_gradients(
+            &Conv1D::new(2, 3, 2, ActivationType::Linear).with_spectral_norm(50),
+            &pattern(&[2, 2, 5], 58),
+        );
+
+        // Substituir os pesos de uma convolução separável recalcula a normalização interna
+        let mut separable = SeparableConv2D::new(2, 3, (2, 2), ActivationType::Linear);
+        separable.pointwise = separable.pointwise.with_spectral_norm(1);
+        let other = SeparableConv2D::new(2, 3, (2, 2), ActivationType::Linear);
+        separable.set_weights(other.weights());
+        let mut expected = separable.clone();
+        expected.pointwise =
+            Conv2D::new(2, 3, (1, 1), ActivationType::Linear).with_spectral_norm(50);
+        expected.pointwise.set_weights(other.pointwise.weights());
+        let input = pattern(&[1, 2, 3, 3], 62);
+        for (a, b) in separable
+            .forward(&input)
+            .iter()
+            .zip(expected.forward(&input).iter())
+        {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-4);
+        }
+
+        // Uma restrição agiria sobre os pesos antes da normalização, e não sobre os pesos
+        // usados pela camada, então a combinação é recusada
+        let dense = || DenseLayer::new(3, 2, ActivationType::Linear);
+        let max_norm = WeightConstraint::MaxNorm(1.0);
+        assert!(
+            std::panic::catch_unwind(|| dense().with_weight_norm().with_constraint(max_norm))
+                .is_err()
+        );
+        assert!(std::panic::catch_unwind(|| dense()
+            .with_constraint(max_norm)
+            .with_spectral_norm(1))
+        .is_err());
+        let conv = || Conv1D::new(2, 3, 2, ActivationType::Linear);
+        assert!(std::panic::catch_unwind(|| conv()
+            .with_spectral_norm(1)
+            .with_constraint(max_norm))
+        .is_err());
+        assert!(std::panic::catch_unwind(|| conv()
+            .with_constraint(WeightConstraint::NonNeg)
+            .with_weight_norm())
+        .is_err());
+
+        // A reparametrização é salva com a rede
+        let mut nn = NeuralNetwork::new();
+        nn.add_layer(Conv2D::new(2, 3, (2, 2), ActivationType::ReLU).with_weight_norm());
+        nn.add_layer(Flatten::new());
+        nn.add_layer(DenseLayer::new(27, 2, ActivationType::Linear).with_spectral_norm(1));
+        nn.add_layer(
+            DenseLayer::new(2, 2, ActivationType::Linear)
+                .with_constraint(WeightConstraint::MaxNorm(1.0)),
+        );
+        let input = pattern(&[2, 2, 4, 4], 59);
+        let error = pattern(&[2, 2], 60);
+        nn.forward(&input);
+        nn.backward(&error, &mut Adam::new(0.01, 0.9, 0.999, 1e-8, 0.0))
+            .unwrap();
+        let path = std::env::temp_dir().join("bran_test_weight_normalization.bin");
+        let path = path.to_str().unwrap();
+        nn.save(path).unwrap();
+        let mut loaded = NeuralNetwork::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded.forward(&input), nn.forward(&input));
+    }
 }
@@ -1,35 +1,38 @@
 // bran/src/loss.rs
 
+use crate::metrics::Evaluation;
 use ndarray::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Trait que define métodos para funções de perda.
 pub trait Loss {
     /// Calcula a perda entre a predição e o alvo.
     ///
     /// # Parâmetros
-    /// * `predicted` - Array 2D contendo as predições do modelo
-    /// * `target` - Array 2D contendo os valores alvo reais
+    /// * `predicted` - Tensor contendo as predições do modelo
+    /// * `target` - Tensor contendo os valores alvo reais
     ///
     /// # Retorno
     /// Retorna um valor float representando a perda total
-    fn loss(&self, predicted: &Array2<f32>, target: &Array2<f32>) -> f32;
+    fn loss(&self, predicted: &ArrayD<f32>, target: &ArrayD<f32>) -> f32;
 
     /// Calcula o gradiente da perda em relação à predição.
     ///
     /// # Parâmetros
-    /// * `predicted` - Array 2D contendo as predições do modelo
-    /// * `target` - Array 2D contendo os valores alvo reais
+    /// * `predicted` - Tensor contendo as predições do modelo
+    /// * `target` - Tensor contendo os valores alvo reais
     ///
     /// # Retorno
-    /// Retorna um Array 2D contendo os gradientes da perda
-    fn derivative(&self, predicted: &Array2<f32>, target: &Array2<f32>) -> Array2<f32>;
+    /// Retorna um tensor, com a forma da predição, contendo os gradientes da perda
+    fn derivative(&self, predicted: &ArrayD<f32>, target: &ArrayD<f32>) -> ArrayD<f32>;
 }
 
 /// Implementação da função de perda Mean Squared Error (MSE).
 pub struct MeanSquaredError;
 
 impl Loss for MeanSquaredError {
-    fn loss(&self, predicted: &Array2<f32>, target: &Array2<f32>) -> f32 {
+    fn loss(&self, predicted: &ArrayD<f32>, target: &ArrayD<f32>) -> f32 {
         // Calcula a diferença entre predições e alvos
         let diff = predicted - target;
         // Eleva as diferenças ao quadrado
@@ -38,7 +41,7 @@ impl Loss for MeanSquaredError {
         squared_diff.sum() / (2 * target.len()) as f32
     }
 
-    fn derivative(&self, predicted: &Array2<f32>, target: &Array2<f32>) -> Array2<f32> {
+    fn derivative(&self, predicted: &ArrayD<f32>, target: &ArrayD<f32>) -> ArrayD<f32> {
         // Calcula o gradiente da MSE
         (predicted - target) / target.len() as f32
     }
@@ -48,7 +51,7 @@ impl Loss for MeanSquaredError {
 pub struct CrossEntropyLoss;
 
 impl Loss for CrossEntropyLoss {
-    fn loss(&self, predicted: &Array2<f32>, target: &Array2<f32>) -> f32 {
+    fn loss(&self, predicted: &ArrayD<f32>, target: &ArrayD<f32>) -> f32 {
         // Adiciona epsilon para evitar log(0)
         let epsilon = 1e-10;
         // Limita as predições entre epsilon e 1-epsilon para estabilidade numérica
@@ -60,7 +63,115 @@ impl Loss for CrossEntropyLoss {
         loss.sum() / target.len() as f32
     }
 
-    fn derivative(&self, predicted: &Array2<f32>, target: &Array2<f32>) -> Array2<f32> {
+    fn derivative(&self, predicted: &ArrayD<f32>, target: &ArrayD<f32>) -> ArrayD<f32> {
         predicted - target
     }
 }
+
+/// Perda de um modelo com várias saídas nomeadas, usada por `GraphModel::fit`.
+///
+/// Cada saída tem sua própria função de perda e um peso. A perda total é a soma ponderada
+/// das perdas das saídas, e o erro de cada saída é a derivada da sua perda multiplicada pelo
+/// peso. Saídas sem perda não contribuem para o treinamento.
+#[derive(Clone, Default)]
+pub struct MultiOutputLoss {
+    losses: Vec<(String, Arc<dyn Loss + Sync + Send>, f32)>,
+}
+
+impl MultiOutputLoss {
+    /// Cria uma perda sem saídas.
+    pub fn new() -> Self {
+        MultiOutputLoss::default()
+    }
+
+    /// Adiciona a perda de uma saída.
+    ///
+    /// # Parâmetros
+    /// * `output` - Nome da saída
+    /// * `loss` - Função de perda da saída
+    /// * `weight` - Peso da saída na perda total
+    pub fn with_output(
+        mut self,
+        output: &str,
+        loss: impl Loss + Sync + Send + 'static,
+        weight: f32,
+    ) -> Self {
+        assert!(
+            !self.losses.iter().any(|(name, _, _)| name == output),
+            "a saída '{}' já tem uma perda",
+            output
+        );
+        self.losses
+            .push((output.to_string(), Arc::new(loss), weight));
+        self
+    }
+
+    /// Nomes das saídas com perda, na ordem em que foram adicionadas.
+    pub fn output_names(&self) -> impl Iterator<Item = &str> {
+        self.losses.iter().map(|(name, _, _)| name.as_str())
+    }
+
+    /// Predição e alvo de uma saída, com mensagens claras quando faltam.
+    fn pair<'a>(
+        output: &str,
+        predicted: &'a HashMap<String, ArrayD<f32>>,
+        targets: &'a HashMap<String, ArrayD<f32>>,
+    ) -> (&'a ArrayD<f32>, &'a ArrayD<f32>) {
+        (
+            predicted
+                .get(output)
+                .unwrap_or_else(|| panic!("saída '{}' não existe", output)),
+            targets
+                .get(output)
+                .unwrap_or_else(|| panic!("alvo da saída '{}' não fornecido", output)),
+        )
+    }
+
+    /// Calcula a perda total ponderada e a perda de cada saída.
+    ///
+    /// # Parâmetros
+    /// * `predicted` - Saídas do modelo, indexadas pelo nome
+    /// * `targets` - Alvos, indexados pelo nome da saída
+    ///
+    /// # Retorno
+    /// Retorna uma `Evaluation` com a perda total e, como métricas, a perda (sem peso) de cada
+    /// saída com o nome `<saida>_loss`, na ordem em que as saídas foram adicionadas
+    pub fn evaluate(
+        &self,
+        predicted: &HashMap<String, ArrayD<f32>>,
+        targets: &HashMap<String, ArrayD<f32>>,
+    ) -> Evaluation {
+        let metrics: Vec<(String, f32)> = self
+            .losses
+            .iter()
+            .map(|(output, loss, _)| {
+                let (predicted, target) = Self::pair(output, predicted, targets);
+                (format!("{}_loss", output), loss.loss(predicted, target))
+            })
+            .collect();
+        Evaluation {
+            loss: self
+                .losses
+                .iter()
+                .zip(&metrics)
+                .map(|((_, _, weight), (_, value))| weight * value)
+                .sum(),
+            metrics,
+        }
+    }
+
+    /// Calcula o erro de cada saída com perda: a derivada da perda multiplicada pelo peso.
+    pub fn derivatives(
+        &self,
+        predicted: &HashMap<String, ArrayD<f32>>,
+        targets: &HashMap<String, ArrayD<f32>>,
+    ) -> HashMap<String, ArrayD<f32>> {
+        self.losses
+            .iter()
+            .map(|(output, loss, weight)| {
+                let (predicted, target) = Self::pair(output, predicted, targets);
+                (output.clone(), loss.derivative(predicted, target) * *weight)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,160 @@
+// bran/src/metrics.rs
+
+use ndarray::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Trait que define métodos para métricas de avaliação.
+/// Ao contrário das funções de perda, métricas não precisam ser diferenciáveis
+/// e servem apenas para acompanhar o desempenho do modelo.
+pub trait Metric: Send + Sync {
+    /// Nome da métrica, usado como chave em `TrainingStats` e nos relatórios.
+    fn name(&self) -> &'static str;
+
+    /// Calcula o valor da métrica entre a predição e o alvo.
+    ///
+    /// # Parâmetros
+    /// * `predicted` - Tensor contendo as predições do modelo
+    /// * `target` - Tensor contendo os valores alvo reais
+    ///
+    /// # Retorno
+    /// Retorna a média da métrica sobre o lote
+    fn compute(&self, predicted: &ArrayD<f32>, target: &ArrayD<f32>) -> f32;
+}
+
+/// Acurácia binária: compara predições e alvos arredondados elemento a elemento.
+pub struct Accuracy;
+
+impl Metric for Accuracy {
+    fn name(&self) -> &'static str {
+        "accuracy"
+    }
+
+    fn compute(&self, predicted: &ArrayD<f32>, target: &ArrayD<f32>) -> f32 {
+        if target.is_empty() {
+            return 0.0;
+        }
+        let correct = predicted
+            .iter()
+            .zip(target.iter())
+            .filter(|(p, t)| p.round() == t.round())
+            .count();
+        correct as f32 / target.len() as f32
+    }
+}
+
+/// Acurácia categórica: compara o índice de maior valor ao longo do último eixo (one-hot).
+pub struct CategoricalAccuracy;
+
+impl Metric for CategoricalAccuracy {
+    fn name(&self) -> &'static str {
+        "categorical_accuracy"
+    }
+
+    fn compute(&self, predicted: &ArrayD<f32>, target: &ArrayD<f32>) -> f32 {
+        if target.is_empty() {
+            return 0.0;
+        }
+        let axis = Axis(target.ndim() - 1);
+        let rows = target.len() / target.len_of(axis);
+        let correct = predicted
+            .lanes(axis)
+            .into_iter()
+            .zip(target.lanes(axis))
+            .filter(|(p, t)| argmax(p) == argmax(t))
+            .count();
+        correct as f32 / rows as f32
+    }
+}
+
+/// Erro absoluto médio (MAE).
+pub struct MeanAbsoluteError;
+
+impl Metric for MeanAbsoluteError {
+    fn name(&self) -> &'static str {
+        "mae"
+    }
+
+    fn compute(&self, predicted: &ArrayD<f32>, target: &ArrayD<f32>) -> f32 {
+        (predicted - target).mapv(f32::abs).mean().unwrap_or(0.0)
+    }
+}
+
+/// Raiz do erro quadrático médio (RMSE).
+pub struct RootMeanSquaredError;
+
+impl Metric for RootMeanSquaredError {
+    fn name(&self) -> &'static str {
+        "rmse"
+    }
+
+    fn compute(&self, predicted: &ArrayD<f32>, target: &ArrayD<f32>) -> f32 {
+        (predicted - target)
+            .mapv(|x| x.powi(2))
+            .mean()
+            .unwrap_or(0.0)
+            .sqrt()
+    }
+}
+
+/// Índice do maior elemento de um vetor.
+fn argmax(row: &ArrayView1<f32>) -> usize {
+    row.iter()
+        .enumerate()
+        .fold((0, f32::NEG_INFINITY), |(best_i, best), (i, &x)| {
+            if x > best {
+                (i, x)
+            } else {
+                (best_i, best)
+            }
+        })
+        .0
+}
+
+/// Enum que representa as métricas disponíveis.
+/// Facilita a configuração do treinamento e a serialização das estatísticas.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MetricType {
+    Accuracy,
+    CategoricalAccuracy,
+    MeanAbsoluteError,
+    RootMeanSquaredError,
+}
+
+impl Metric for MetricType {
+    fn name(&self) -> &'static str {
+        match self {
+            MetricType::Accuracy => Accuracy.name(),
+            MetricType::CategoricalAccuracy => CategoricalAccuracy.name(),
+            MetricType::MeanAbsoluteError => MeanAbsoluteError.name(),
+            MetricType::RootMeanSquaredError => RootMeanSquaredError.name(),
+        }
+    }
+
+    fn compute(&self, predicted: &ArrayD<f32>, target: &ArrayD<f32>) -> f32 {
+        match self {
+            MetricType::Accuracy => Accuracy.compute(predicted, target),
+            MetricType::CategoricalAccuracy => CategoricalAccuracy.compute(predicted, target),
+            MetricType::MeanAbsoluteError => MeanAbsoluteError.compute(predicted, target),
+            MetricType::RootMeanSquaredError => RootMeanSquaredError.compute(predicted, target),
+        }
+    }
+}
+
+/// Resultado da avaliação de um modelo sobre um conjunto de dados.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Evaluation {
+    /// Valor da função de perda.
+    pub loss: f32,
+    /// Valores das métricas, na mesma ordem em que foram solicitadas.
+    pub metrics: Vec<(String, f32)>,
+}
+
+impl Evaluation {
+    /// Retorna o valor de uma métrica pelo nome, se ela foi calculada.
+    pub fn metric(&self, name: &str) -> Option<f32> {
+        self.metrics
+            .iter()
+            .find(|(metric, _)| metric == name)
+            .map(|(_, value)| *value)
+    }
+}
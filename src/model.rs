@@ -1,35 +1,127 @@
 // bran/src/model.rs
 
-use crate::layers::DenseLayer;
+use crate::activations::ActivationType;
+use crate::callbacks::{Callback, CallbackContext, EarlyStopping};
+use crate::checkpoint::Checkpoint;
+use crate::layers::{DenseLayer, Layer, LayerType, TiedWeights};
 use crate::loss::Loss;
+use crate::metrics::{Accuracy, Evaluation, Metric, MetricType};
 use crate::optimizer::Optimizer;
-use crate::visualization::TrainingStats;
-use ndarray::{parallel::prelude::*, s, Array2};
+use crate::visualization::{Progress, ProgressReporter, TrainingStats};
+use ndarray::{Array1, Array2, ArrayD, ArrayViewD, Axis, Slice};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::RangeBounds;
 use std::sync::{Arc, Mutex};
 use std::{fs::File, io::Read, io::Write};
 
+/// Identificador gravado no início dos arquivos de modelo e de checkpoint.
+const FORMAT_MAGIC: &[u8; 4] = b"BRAN";
+
+/// Versão do formato dos arquivos de modelo e de checkpoint, gravada logo após
+/// `FORMAT_MAGIC`. Deve ser incrementada sempre que a disposição serializada mudar.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Serializa `value` com `bincode`, precedido do cabeçalho de formato.
+pub(crate) fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut encoded = FORMAT_MAGIC.to_vec();
+    encoded.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    encoded.extend(bincode::serialize(value)?);
+    Ok(encoded)
+}
+
+/// Retira o cabeçalho de formato de `buffer`. Retorna `None` se o arquivo não tiver
+/// cabeçalho, e um erro se a versão não for a atual.
+pub(crate) fn strip_header(buffer: &[u8]) -> Result<Option<&[u8]>, Box<dyn std::error::Error>> {
+    let Some(rest) = buffer.strip_prefix(FORMAT_MAGIC.as_slice()) else {
+        return Ok(None);
+    };
+    let Some((version, payload)) = rest.split_first_chunk::<4>() else {
+        return Err("cabeçalho de formato incompleto".into());
+    };
+    let version = u32::from_le_bytes(*version);
+    if version != FORMAT_VERSION {
+        return Err(format!(
+            "versão de formato {} não suportada (esta versão do bran lê a versão {})",
+            version, FORMAT_VERSION
+        )
+        .into());
+    }
+    Ok(Some(payload))
+}
+
 /// Estrutura principal que representa uma rede neural.
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default, Clone)]
 pub struct NeuralNetwork {
-    pub layers: Vec<DenseLayer>,
+    /// Camadas da rede, na ordem da passagem forward. Inserir, remover ou substituir camadas
+    /// depois de `tie_weights` pode invalidar os compartilhamentos de pesos (veja `validate_ties`).
+    pub layers: Vec<LayerType>,
+
+    /// Pares de camadas que compartilham a matriz de pesos.
+    ties: Vec<WeightTie>,
+
+    /// Indica se a rede está em modo de treinamento. Redes novas ou carregadas de arquivo
+    /// começam em modo de inferência.
+    #[serde(skip)]
+    training: bool,
+}
+
+/// Compartilhamento da matriz de pesos entre duas camadas densas ou de embedding de uma
+/// `NeuralNetwork`, criado com `NeuralNetwork::tie_weights`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct WeightTie {
+    /// Índice da camada dona dos pesos.
+    pub source: usize,
+    /// Índice da camada que usa os pesos da camada `source`.
+    pub target: usize,
+    /// Se a camada `target` usa a transposta dos pesos, como no decodificador de um autoencoder.
+    pub transposed: bool,
 }
 
 impl NeuralNetwork {
     /// Cria uma nova rede neural vazia.
     pub fn new() -> Self {
-        NeuralNetwork { layers: Vec::new() }
+        NeuralNetwork::default()
+    }
+
+    /// Coloca todas as camadas em modo de treinamento (dropout ativo).
+    ///
+    /// `train` faz isso automaticamente; este método serve para laços de treinamento próprios.
+    pub fn train_mode(&mut self) {
+        self.set_training(true);
+    }
+
+    /// Coloca todas as camadas em modo de inferência (dropout desativado).
+    pub fn eval_mode(&mut self) {
+        self.set_training(false);
+    }
+
+    /// Indica se a rede está em modo de treinamento.
+    pub fn is_training(&self) -> bool {
+        self.training
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.training = training;
+        for layer in &mut self.layers {
+            layer.set_training(training);
+        }
     }
 
     /// Salva o modelo utilizando a serialização binária `bincode` para melhorar a performance.
     ///
+    /// O arquivo começa com um cabeçalho com a versão do formato (`FORMAT_VERSION`).
+    ///
     /// # Parâmetros
     /// - `filename`: O caminho onde o modelo será salvo.
     ///
     /// # Retornos
     /// - `Result<(), Box<dyn std::error::Error>>`: Retorna Ok se o modelo foi salvo com sucesso, ou um erro caso contrário.
     pub fn save(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let encoded: Vec<u8> = bincode::serialize(&self)?;
+        let encoded = encode(self)?;
         let mut file = File::create(filename)?;
         file.write_all(&encoded)?;
         Ok(())
@@ -37,43 +129,335 @@ impl NeuralNetwork {
 
     /// Carrega um modelo a partir de um arquivo serializado com `bincode`.
     ///
+    /// Arquivos sem cabeçalho de formato, gravados antes da introdução das camadas não
+    /// densas, são lidos no formato antigo, em que a rede tinha apenas camadas densas.
+    ///
     /// # Parâmetros
     /// - `filename`: O caminho de onde o modelo será carregado.
     ///
     /// # Retornos
-    /// - `Result<NeuralNetwork, Box<dyn std::error::Error>>`: A rede neural carregada ou um erro, caso a operação falhe.
+    /// - `Result<NeuralNetwork, Box<dyn std::error::Error>>`: A rede neural carregada ou um erro, caso a operação falhe
+    ///   ou a versão do formato não seja suportada.
     pub fn load(filename: &str) -> Result<NeuralNetwork, Box<dyn std::error::Error>> {
         let mut file = File::open(filename)?;
         let mut buffer = Vec::new();
         file.read_to_end(&mut buffer)?;
-        let mut deserialized: NeuralNetwork = bincode::deserialize(&buffer)?;
+        // As camadas restauram suas funções de ativação na desserialização
+        let deserialized: NeuralNetwork = match strip_header(&buffer)? {
+            Some(payload) => bincode::deserialize(payload)?,
+            None => Self::from_legacy(&buffer)?,
+        };
+        deserialized.validate_ties()?;
+        Ok(deserialized)
+    }
 
-        // Restaura as ativações nas camadas após a desserialização
-        for layer in &mut deserialized.layers {
-            layer.restore_activation();
+    /// Lê um modelo gravado no formato sem cabeçalho, com uma lista de camadas densas.
+    fn from_legacy(buffer: &[u8]) -> Result<NeuralNetwork, Box<dyn std::error::Error>> {
+        #[derive(Deserialize)]
+        struct LegacyDense {
+            weights: Array2<f32>,
+            biases: Array1<f32>,
+            activation_type: ActivationType,
         }
 
-        Ok(deserialized)
+        #[derive(Deserialize)]
+        struct LegacyNetwork {
+            layers: Vec<LegacyDense>,
+        }
+
+        let not_a_model = "o arquivo não é um modelo do bran: não tem cabeçalho de formato nem a disposição antiga, só com camadas densas";
+        let legacy: LegacyNetwork = bincode::deserialize(buffer).map_err(|_| not_a_model)?;
+        let mut network = NeuralNetwork::new();
+        for layer in legacy.layers {
+            let (output_size, input_size) = layer.weights.dim();
+            if layer.biases.len() != output_size {
+                return Err(not_a_model.into());
+            }
+            let mut dense = DenseLayer::new(input_size, output_size, layer.activation_type);
+            dense.weights = layer.weights;
+            dense.biases = layer.biases;
+            network.add_layer(dense);
+        }
+        Ok(network)
     }
 
     /// Adiciona uma nova camada à rede neural.
     ///
     /// # Parâmetros
-    /// - `layer`: A nova camada a ser adicionada à rede.
-    pub fn add_layer(&mut self, layer: DenseLayer) {
+    /// - `layer`: A nova camada a ser adicionada à rede (qualquer tipo convertível em `LayerType`).
+    pub fn add_layer(&mut self, layer: impl Into<LayerType>) {
+        let mut layer = layer.into();
+        layer.set_training(self.training);
         self.layers.push(layer);
     }
 
+    /// Número total de parâmetros treináveis da rede. Pesos compartilhados contam uma vez.
+    pub fn param_count(&self) -> usize {
+        let shared: usize = self.ties.iter().map(|tie| self.tied_len(tie)).sum();
+        self.layers
+            .iter()
+            .map(|layer| layer.param_count())
+            .sum::<usize>()
+            - shared
+    }
+
+    /// Número de parâmetros das camadas não congeladas. Pesos compartilhados contam uma vez,
+    /// e são treináveis se a camada dona deles for.
+    pub fn trainable_param_count(&self) -> usize {
+        let shared: usize = self
+            .ties
+            .iter()
+            .filter(|tie| self.layers[tie.target].trainable())
+            .map(|tie| self.tied_len(tie))
+            .sum();
+        self.layers
+            .iter()
+            .filter(|layer| layer.trainable())
+            .map(|layer| layer.param_count())
+            .sum::<usize>()
+            - shared
+    }
+
+    /// Congela as camadas de um intervalo: seus parâmetros deixam de ser atualizados, mas o erro
+    /// continua sendo propagado através delas. Útil para transferência de aprendizado.
+    ///
+    /// # Parâmetros
+    /// - `range`: Índices das camadas a congelar, por exemplo `..3` ou `1..=2`.
+    pub fn freeze(&mut self, range: impl RangeBounds<usize>) {
+        self.set_trainable(range, false);
+    }
+
+    /// Libera para treinamento as camadas de um intervalo.
+    ///
+    /// # Parâmetros
+    /// - `range`: Índices das camadas a liberar, por exemplo `2..` ou `..`.
+    pub fn unfreeze(&mut self, range: impl RangeBounds<usize>) {
+        self.set_trainable(range, true);
+    }
+
+    fn set_trainable(&mut self, range: impl RangeBounds<usize>, trainable: bool) {
+        let bounds = (range.start_bound().cloned(), range.end_bound().cloned());
+        for layer in &mut self.layers[bounds] {
+            layer.set_trainable(trainable);
+        }
+    }
+
+    /// Faz a camada `target` usar a matriz de pesos da camada `source`, como em autoencoders
+    /// com pesos amarrados ou em embeddings compartilhados com a camada de saída.
+    ///
+    /// As duas camadas devem ser densas ou de embedding. A matriz de uma `DenseLayer` tem forma
+    /// `(output_size, input_size)` e a de um `Embedding`, `(num_embeddings, embedding_dim)`;
+    /// assim, a tabela de um `Embedding` é compartilhada sem transposição com a camada densa
+    /// de saída `embedding_dim -> num_embeddings`.
+    ///
+    /// Os pesos de `target` são substituídos pelos de `source`. Na passagem backward, o
+    /// gradiente dos pesos calculado em `target` é somado ao de `source`, e os pesos são
+    /// atualizados uma única vez; os vieses continuam independentes. Um `Embedding` continua
+    /// atualizando apenas as linhas com gradiente, exceto a de `padding_idx`. O
+    /// compartilhamento é salvo junto com a rede.
+    ///
+    /// # Parâmetros
+    /// - `source`: Índice da camada dona dos pesos.
+    /// - `target`: Índice da camada que passa a usá-los.
+    /// - `transposed`: Se `target` usa a transposta dos pesos. Sem transposição, as duas
+    ///   matrizes devem ter a mesma forma; com ela, a forma de `target` é a transposta da de
+    ///   `source`.
+    ///
+    /// # Retornos
+    /// - `Result<(), Box<dyn std::error::Error>>`: Erro se alguma das camadas não for densa ou
+    ///   de embedding, tiver pesos normalizados ou um embedding com norma máxima, se as formas
+    ///   forem incompatíveis, se `target` já usar outros pesos ou se o compartilhamento formar
+    ///   uma cadeia.
+    pub fn tie_weights(
+        &mut self,
+        source: usize,
+        target: usize,
+        transposed: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if source == target {
+            return Err("uma camada não pode compartilhar pesos consigo mesma".into());
+        }
+        let tie = WeightTie {
+            source,
+            target,
+            transposed,
+        };
+        self.check_tie(&tie)?;
+        if self.ties.iter().any(|tie| tie.target == target) {
+            return Err(format!("a camada {} já usa os pesos de outra camada", target).into());
+        }
+        if self
+            .ties
+            .iter()
+            .any(|tie| tie.target == source || tie.source == target)
+        {
+            return Err("compartilhamentos de pesos encadeados não são suportados".into());
+        }
+
+        self.ties.push(tie);
+        self.sync_ties();
+        Ok(())
+    }
+
+    /// Verifica que as duas camadas de `tie` existem, podem compartilhar pesos e têm pesos de
+    /// formas compatíveis.
+    fn check_tie(&self, tie: &WeightTie) -> Result<(), Box<dyn std::error::Error>> {
+        let (Some(source_layer), Some(target_layer)) =
+            (self.tied(tie.source), self.tied(tie.target))
+        else {
+            return Err(format!(
+                "as camadas {} e {} devem existir e ser densas ou de embedding",
+                tie.source, tie.target
+            )
+            .into());
+        };
+        let (source_weights, target_weights) =
+            (source_layer.tied_weights()?, target_layer.tied_weights()?);
+        let (rows, cols) = source_weights.dim();
+        let expected = if tie.transposed {
+            (cols, rows)
+        } else {
+            (rows, cols)
+        };
+        if target_weights.dim() != expected {
+            return Err(format!(
+                "pesos {:?} da camada {} incompatíveis com {:?}",
+                target_weights.dim(),
+                tie.target,
+                expected
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Verifica que os compartilhamentos de pesos ainda correspondem às camadas da rede.
+    ///
+    /// Os compartilhamentos guardam as posições das camadas; inserir, remover ou substituir
+    /// camadas em `layers` depois de `tie_weights` pode invalidá-los.
+    ///
+    /// # Retornos
+    /// - `Result<(), Box<dyn std::error::Error>>`: Erro descrevendo o primeiro compartilhamento inválido.
+    pub fn validate_ties(&self) -> Result<(), Box<dyn std::error::Error>> {
+        for tie in &self.ties {
+            self.check_tie(tie).map_err(|error| {
+                format!(
+                    "compartilhamento de pesos {} -> {} inválido: {}",
+                    tie.source, tie.target, error
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Compartilhamentos de pesos da rede, na ordem em que foram criados.
+    pub fn ties(&self) -> &[WeightTie] {
+        &self.ties
+    }
+
+    fn tied(&self, index: usize) -> Option<&dyn TiedWeights> {
+        self.layers.get(index).and_then(LayerType::as_tied)
+    }
+
+    fn tied_mut(&mut self, index: usize) -> Option<&mut dyn TiedWeights> {
+        self.layers.get_mut(index).and_then(LayerType::as_tied_mut)
+    }
+
+    /// Número de pesos compartilhados por `tie`.
+    fn tied_len(&self, tie: &WeightTie) -> usize {
+        self.tied(tie.source)
+            .and_then(|layer| layer.tied_weights().ok())
+            .map_or(0, |weights| weights.len())
+    }
+
+    /// Copia os pesos de cada camada `source` para a camada `target` correspondente.
+    /// Compartilhamentos inválidos são ignorados; `validate_ties` os reporta.
+    fn sync_ties(&mut self) {
+        for tie in self.ties.clone() {
+            if self.check_tie(&tie).is_err() {
+                continue;
+            }
+            let weights = self.tied(tie.source).unwrap().tied_weights().unwrap();
+            let weights = if tie.transposed {
+                weights.t().to_owned()
+            } else {
+                weights.to_owned()
+            };
+            self.tied_mut(tie.target).unwrap().set_tied_weights(weights);
+        }
+    }
+
+    /// Retorna uma cópia do estado persistente (pesos, vieses e estatísticas) de todas as camadas.
+    pub fn get_weights(&self) -> Vec<Vec<ArrayD<f32>>> {
+        self.layers.iter().map(|layer| layer.weights()).collect()
+    }
+
+    /// Substitui o estado persistente de todas as camadas.
+    ///
+    /// # Parâmetros
+    /// - `weights`: Tensores de cada camada, na ordem das camadas, como retornado por `get_weights`.
+    pub fn set_weights(&mut self, weights: Vec<Vec<ArrayD<f32>>>) {
+        assert_eq!(
+            weights.len(),
+            self.layers.len(),
+            "número de conjuntos de pesos difere do número de camadas"
+        );
+        for (layer, layer_weights) in self.layers.iter_mut().zip(weights) {
+            layer.set_weights(layer_weights);
+        }
+        self.sync_ties();
+    }
+
+    /// Retorna uma cópia dos momentos do otimizador guardados em cada camada.
+    pub fn moments(&self) -> Vec<Vec<ArrayD<f32>>> {
+        self.layers.iter().map(|layer| layer.moments()).collect()
+    }
+
+    /// Substitui os momentos do otimizador de todas as camadas.
+    ///
+    /// # Parâmetros
+    /// - `moments`: Momentos na ordem das camadas, como retornado por `moments`.
+    ///
+    /// # Retornos
+    /// - `Result<(), Box<dyn std::error::Error>>`: Erro se o número de camadas ou as dimensões não coincidirem.
+    pub fn set_moments(
+        &mut self,
+        moments: Vec<Vec<ArrayD<f32>>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if moments.len() != self.layers.len() {
+            return Err("número de momentos difere do número de camadas".into());
+        }
+        for (layer, layer_moments) in self.layers.iter_mut().zip(moments) {
+            layer.set_moments(layer_moments)?;
+        }
+        Ok(())
+    }
+
+    /// Calcula a forma da saída da rede para uma entrada de forma `input_shape`.
+    ///
+    /// # Parâmetros
+    /// - `input_shape`: Forma da entrada, incluindo o eixo do lote.
+    ///
+    /// # Retornos
+    /// - `Vec<usize>`: A forma da saída da última camada.
+    pub fn output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        self.layers
+            .iter()
+            .fold(input_shape.to_vec(), |shape, layer| {
+                layer.output_shape(&shape)
+            })
+    }
+
     /// Executa a propagação forward para um lote de entradas.
     ///
     /// Os dados são propagados através de todas as camadas da rede.
     ///
     /// # Parâmetros
-    /// - `input`: Os dados de entrada para a rede.
+    /// - `input`: Os dados de entrada para a rede, com o lote no primeiro eixo.
     ///
     /// # Retornos
-    /// - `Array2<f32>`: A saída gerada pela última camada da rede.
-    pub fn forward(&mut self, input: &Array2<f32>) -> Array2<f32> {
+    /// - `ArrayD<f32>`: A saída gerada pela última camada da rede.
+    pub fn forward(&mut self, input: &ArrayD<f32>) -> ArrayD<f32> {
         let mut output = input.clone();
         for layer in &mut self.layers {
             output = layer.forward(&output);
@@ -83,107 +467,718 @@ impl NeuralNetwork {
 
     /// Executa a retropropagação (backward) para um lote de erros de saída.
     ///
-    /// Atualiza os pesos e vieses de todas as camadas usando o otimizador fornecido.
+    /// Atualiza os pesos e vieses de todas as camadas usando o otimizador fornecido. Camadas
+    /// com pesos compartilhados só são atualizadas ao final, depois que os gradientes de todas
+    /// as camadas que usam os mesmos pesos foram somados.
     ///
     /// # Parâmetros
     /// - `output_error`: O erro da saída que será propagado de volta.
     /// - `optimizer`: O otimizador que será usado para atualizar os pesos.
     ///
     /// # Retornos
-    /// - `Result<(), Box<dyn std::error::Error>>`: Retorna Ok se a operação foi bem-sucedida.
+    /// - `Result<(), Box<dyn std::error::Error>>`: Erro, sem atualizar nenhuma camada, se algum
+    ///   compartilhamento de pesos não corresponder mais às camadas (veja `validate_ties`).
     pub fn backward(
         &mut self,
-        output_error: &Array2<f32>,
+        output_error: &ArrayD<f32>,
         optimizer: &mut dyn Optimizer,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        self.validate_ties()?;
+
+        // Uma camada congelada que usa os pesos de uma camada treinável ainda contribui
+        // para o gradiente desses pesos
+        let trainable_targets: Vec<usize> = self
+            .ties
+            .iter()
+            .filter(|tie| self.layers[tie.source].trainable())
+            .map(|tie| tie.target)
+            .collect();
+
         let mut error = output_error.clone();
-        for layer in self.layers.iter_mut().rev() {
-            error = layer.backward(&error, optimizer);
+        for (index, layer) in self.layers.iter_mut().enumerate().rev() {
+            let tied = self
+                .ties
+                .iter()
+                .any(|tie| tie.source == index || tie.target == index);
+            error = if tied {
+                layer
+                    .as_tied_mut()
+                    .unwrap()
+                    .compute_gradients(&error, trainable_targets.contains(&index))
+            } else {
+                layer.backward(&error, optimizer)
+            };
+        }
+
+        if !self.ties.is_empty() {
+            // As camadas já foram verificadas por `validate_ties`
+            for tie in self.ties.clone() {
+                let target = self.tied_mut(tie.target).unwrap();
+                if let Some(gradients) = target.take_weight_gradients() {
+                    let gradients = if tie.transposed {
+                        gradients.reversed_axes()
+                    } else {
+                        gradients
+                    };
+                    let source = self.tied_mut(tie.source).unwrap();
+                    source.add_weight_gradients(gradients.view());
+                }
+            }
+            for tie in self.ties.clone() {
+                self.tied_mut(tie.source)
+                    .unwrap()
+                    .apply_gradients(optimizer);
+                self.tied_mut(tie.target)
+                    .unwrap()
+                    .apply_gradients(optimizer);
+            }
+            self.sync_ties();
         }
         Ok(())
     }
 
+    /// Avalia a rede neural sobre um conjunto de dados, sem atualizar os pesos.
+    ///
+    /// A avaliação é feita em modo de inferência; o modo anterior é restaurado ao final.
+    ///
+    /// # Parâmetros
+    /// - `x`: Dados de entrada.
+    /// - `y`: Rótulos/alvos correspondentes.
+    /// - `loss_fn`: Função de perda a ser calculada.
+    /// - `metrics`: Métricas adicionais a serem calculadas.
+    ///
+    /// # Retornos
+    /// - `Evaluation`: A perda e os valores das métricas, na ordem em que foram solicitadas.
+    pub fn evaluate(
+        &mut self,
+        x: &ArrayD<f32>,
+        y: &ArrayD<f32>,
+        loss_fn: &dyn Loss,
+        metrics: &[MetricType],
+    ) -> Evaluation {
+        let training = self.training;
+        self.eval_mode();
+        let output = self.forward(x);
+        self.set_training(training);
+        Evaluation {
+            loss: loss_fn.loss(&output, y),
+            metrics: metrics
+                .iter()
+                .map(|metric| (metric.name().to_string(), metric.compute(&output, y)))
+                .collect(),
+        }
+    }
+
     /// Treina a rede neural utilizando os dados fornecidos.
     ///
+    /// A perda e as métricas de treinamento de cada época são a média ponderada dos
+    /// valores obtidos em cada mini-lote, evitando uma passagem forward extra sobre
+    /// todo o conjunto de treinamento. Se um callback interromper a época no meio, a média
+    /// considera apenas os mini-lotes processados. Se houver dados de validação, a rede é avaliada
+    /// sobre eles ao final de cada época. Os callbacks configurados são chamados no
+    /// início e no fim do treinamento, de cada época e de cada mini-lote, e podem
+    /// interromper o treinamento. O progresso é reportado conforme `TrainConfig::progress`.
+    ///
+    /// Se `TrainConfig::checkpoint` estiver definido, um `Checkpoint` completo é gravado a
+    /// cada N épocas; se `TrainConfig::resume` estiver definido, o estado salvo é restaurado
+    /// e o treinamento continua a partir da época seguinte à do checkpoint.
+    ///
+    /// A rede fica em modo de treinamento durante as épocas (exceto na validação) e
+    /// termina em modo de inferência.
+    ///
+    /// Os mini-lotes são aplicados em sequência, na ordem dos dados; o paralelismo fica
+    /// a cargo das operações sobre as matrizes dentro de cada camada e do otimizador.
+    ///
     /// # Parâmetros
     /// - `neural_net`: A rede neural encapsulada em `Arc<Mutex<Self>>` para permitir acesso concorrente seguro.
-    /// - `x_train`: Dados de entrada para o treinamento.
+    /// - `x_train`: Dados de entrada para o treinamento, com as amostras no primeiro eixo.
     /// - `y_train`: Rótulos/alvos correspondentes.
-    /// - `epochs`: Número de épocas de treinamento.
-    /// - `batch_size`: Tamanho do lote para o treinamento em mini-lotes.
     /// - `loss_fn`: Função de perda a ser usada, encapsulada em `Arc` para ser segura para threads.
     /// - `optimizer`: Otimizador para a atualização dos pesos, também encapsulado em `Arc<Mutex>` para garantir acesso seguro entre threads.
     /// - `stats`: Estrutura para coletar estatísticas de treinamento, encapsulada em `Arc<Mutex>`.
+    /// - `config`: Configuração do treinamento (épocas, tamanho do lote, validação, métricas e callbacks).
+    ///
+    /// # Retornos
+    /// - `Result<(), Box<dyn std::error::Error>>`: Erro se `batch_size` for zero, se o conjunto de
+    ///   treinamento ou o de validação ficar vazio, ou se a restauração ou a gravação de um
+    ///   checkpoint falhar.
     pub fn train(
         neural_net: Arc<Mutex<Self>>,
-        x_train: &Array2<f32>,
-        y_train: &Array2<f32>,
-        epochs: usize,
-        batch_size: usize,
+        x_train: &ArrayD<f32>,
+        y_train: &ArrayD<f32>,
         loss_fn: Arc<dyn Loss + Sync + Send>,
         optimizer: Arc<Mutex<dyn Optimizer + Send>>,
         stats: Arc<Mutex<TrainingStats>>,
-    ) {
-        let n_samples = x_train.shape()[0];
-
-        for epoch in 0..epochs {
-            // Itera sobre mini-lotes em paralelo
-            (0..n_samples)
-                .into_par_iter()
-                .step_by(batch_size)
-                .for_each(|i| {
-                    let end = usize::min(i + batch_size, n_samples);
-                    let x_batch = x_train.slice(s![i..end, ..]).to_owned();
-                    let y_batch = y_train.slice(s![i..end, ..]).to_owned();
-
-                    // Passagem forward e backward
-                    {
-                        let mut neural_net = neural_net.lock().unwrap(); // Escopo do lock é limitado
-                        let output = neural_net.forward(&x_batch);
-                        let error = loss_fn.derivative(&output, &y_batch);
-
-                        // Atualiza os pesos e vieses
-                        let mut optimizer = optimizer.lock().unwrap();
-                        neural_net.backward(&error, &mut *optimizer).unwrap();
-                    }
-                });
+        mut config: TrainConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let metrics = config.metrics.clone();
+        let data = Supervised {
+            x: x_train.view(),
+            y: y_train.view(),
+            loss: loss_fn.as_ref(),
+            metrics: &metrics,
+        };
+
+        // Separa os dados de validação, se configurados
+        let validation = config.validation.take();
+        let (data, validation) = match &validation {
+            None => (data, None),
+            Some(Validation::Data(x_val, y_val)) => {
+                let validation = Supervised {
+                    x: x_val.view(),
+                    y: y_val.view(),
+                    ..data.clone()
+                };
+                (data, Some(validation))
+            }
+            Some(Validation::Split(fraction)) => split_validation(data, *fraction)?,
+            Some(Validation::Named(..)) => {
+                return Err("dados de validação nomeados só se aplicam a GraphModel::fit".into())
+            }
+        };
+
+        Self::fit_data(neural_net, data, validation, optimizer, stats, config)
+    }
+
+    /// Laço de treinamento compartilhado por `train` e `GraphModel::fit`: épocas, mini-lotes,
+    /// embaralhamento, validação, callbacks, progresso e checkpoints. `config.validation` é
+    /// ignorado; os dados de validação já vêm separados em `validation`.
+    pub(crate) fn fit_data<D: TrainingData>(
+        neural_net: Arc<Mutex<Self>>,
+        data: D,
+        validation: Option<D>,
+        optimizer: Arc<Mutex<dyn Optimizer + Send>>,
+        stats: Arc<Mutex<TrainingStats>>,
+        config: TrainConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let TrainConfig {
+            epochs,
+            batch_size,
+            progress,
+            progress_writer,
+            mut callbacks,
+            shuffle_seed,
+            checkpoint,
+            resume,
+            ..
+        } = config;
+
+        if batch_size == 0 {
+            return Err("batch_size deve ser maior que zero".into());
+        }
+        if matches!(checkpoint, Some((_, 0))) {
+            return Err("o intervalo entre checkpoints deve ser maior que zero".into());
+        }
+
+        // O reporter de progresso roda antes dos demais callbacks. Ele não tem estado e fica
+        // fora dos checkpoints, para que o modo de progresso possa mudar ao retomar
+        let reporters = usize::from(progress != Progress::Silent);
+        if progress != Progress::Silent {
+            let mut reporter = ProgressReporter::new(progress);
+            if let Some(writer) = progress_writer {
+                reporter.writer = Some(writer);
+            }
+            callbacks.insert(0, Box::new(reporter));
+        }
+
+        let n_samples = data.samples();
+        if n_samples == 0 {
+            return Err("o conjunto de treinamento está vazio".into());
+        }
+        let steps_per_epoch = n_samples.div_ceil(batch_size);
+
+        // Verifica a configuração dos callbacks, como a métrica monitorada pelo
+        // EarlyStopping, antes de qualquer efeito colateral
+        let series = available_series(&data, validation.as_ref());
+        for callback in &callbacks {
+            callback.validate(&series)?;
+        }
+        if let Some(saved) = &resume {
+            if saved.callbacks.len() != callbacks.len() - reporters {
+                return Err("número de callbacks difere do checkpoint".into());
+            }
+        }
+
+        // Executa um gancho em todos os callbacks e indica se algum pediu a interrupção
+        let run_callbacks =
+            |callbacks: &mut [Box<dyn Callback>],
+             hook: &mut dyn FnMut(&mut dyn Callback, &mut CallbackContext)| {
+                let mut neural_net = neural_net.lock().unwrap();
+                let mut optimizer = optimizer.lock().unwrap();
+                let mut stats = stats.lock().unwrap();
+                let mut ctx = CallbackContext {
+                    model: &mut neural_net,
+                    optimizer: &mut *optimizer,
+                    stats: &mut stats,
+                    epochs,
+                    steps_per_epoch,
+                    batch_size,
+                    samples: n_samples,
+                    stop_training: false,
+                };
+                for callback in callbacks.iter_mut() {
+                    hook(callback.as_mut(), &mut ctx);
+                }
+                ctx.stop_training
+            };
 
-            // Calcula a perda e a acurácia após a época
-            let (loss, accuracy) = {
+        // Restaura o estado salvo antes de notificar os callbacks
+        let initial_epoch = match &resume {
+            Some(saved) => {
                 let mut neural_net = neural_net.lock().unwrap();
-                let output = neural_net.forward(&x_train);
-                let loss = loss_fn.loss(&output, &y_train);
-                let accuracy = calculate_accuracy(&y_train, &output);
-                (loss, accuracy)
+                *neural_net = saved.model.clone();
+                neural_net.set_moments(saved.moments.clone())?;
+                optimizer.lock().unwrap().load_state(&saved.optimizer);
+                *stats.lock().unwrap() = saved.stats.clone();
+                saved.epoch
+            }
+            None => 0,
+        };
+
+        // Ativa o dropout e as demais diferenças de comportamento durante o treinamento
+        neural_net.lock().unwrap().train_mode();
+
+        let mut stop_training =
+            run_callbacks(&mut callbacks, &mut |cb, ctx| cb.on_train_begin(ctx));
+
+        if let Some(saved) = &resume {
+            for (callback, state) in callbacks[reporters..].iter_mut().zip(&saved.callbacks) {
+                if let Some(state) = state {
+                    callback.load_state(state);
+                }
+            }
+        }
+
+        for epoch in initial_epoch + 1..=epochs {
+            if stop_training
+                || run_callbacks(&mut callbacks, &mut |cb, ctx| cb.on_epoch_begin(epoch, ctx))
+            {
+                break;
+            }
+
+            // Acumula a perda e as métricas de cada lote, ponderadas pelo tamanho do lote
+            let mut loss = 0.0;
+            let mut accuracy = 0.0;
+            let mut metric_sums: Vec<(String, f32)> = Vec::new();
+            let mut processed = 0;
+
+            // A ordem de cada época depende apenas da semente e do número da época,
+            // o que permite retomar o treinamento com a mesma sequência de mini-lotes
+            let mut indices: Vec<usize> = (0..n_samples).collect();
+            if let Some(seed) = shuffle_seed {
+                let mut rng = StdRng::seed_from_u64(seed.wrapping_add(epoch as u64));
+                indices.shuffle(&mut rng);
+            }
+
+            for (batch, i) in (0..n_samples).step_by(batch_size).enumerate() {
+                if stop_training {
+                    break;
+                }
+                stop_training |=
+                    run_callbacks(&mut callbacks, &mut |cb, ctx| cb.on_batch_begin(batch, ctx));
+                if stop_training {
+                    break;
+                }
+
+                let end = usize::min(i + batch_size, n_samples);
+                let weight = (end - i) as f32;
+                processed += end - i;
+
+                // Passagem forward e backward, atualizando os pesos e vieses
+                let (evaluation, batch_accuracy) = {
+                    let mut neural_net = neural_net.lock().unwrap(); // Escopo do lock é limitado
+                    let mut optimizer = optimizer.lock().unwrap();
+                    data.train_batch(&mut neural_net, &indices[i..end], &mut *optimizer)?
+                };
+                let batch_loss = evaluation.loss;
+                loss += batch_loss * weight;
+                accuracy += batch_accuracy * weight;
+                if metric_sums.is_empty() {
+                    metric_sums = evaluation
+                        .metrics
+                        .iter()
+                        .map(|(name, _)| (name.clone(), 0.0))
+                        .collect();
+                }
+                for ((_, sum), (_, value)) in metric_sums.iter_mut().zip(&evaluation.metrics) {
+                    *sum += value * weight;
+                }
+
+                stop_training |= run_callbacks(&mut callbacks, &mut |cb, ctx| {
+                    cb.on_batch_end(batch, batch_loss, ctx)
+                });
+            }
+
+            // Uma época interrompida antes do primeiro lote não tem o que registrar
+            if processed == 0 {
+                break;
+            }
+
+            // As médias consideram apenas as amostras efetivamente processadas, caso a
+            // época tenha sido interrompida no meio
+            let scale = processed as f32;
+            let (loss, accuracy) = (loss / scale, accuracy / scale);
+            let metrics: Vec<(String, f32)> = metric_sums
+                .into_iter()
+                .map(|(name, sum)| (name, sum / scale))
+                .collect();
+
+            // Avalia a rede no conjunto de validação
+            let evaluation = match &validation {
+                Some(validation) => {
+                    let mut neural_net = neural_net.lock().unwrap();
+                    Some(validation.evaluate(&mut neural_net)?)
+                }
+                None => None,
             };
 
             // Atualiza as estatísticas de treinamento
             {
                 let mut stats = stats.lock().unwrap();
-                stats.log_epoch(epoch as f32 + 1.0, loss, accuracy);
+                stats.log_epoch(epoch as f32, loss, accuracy);
+                stats.log_metrics(&metrics);
+                if let Some(evaluation) = &evaluation {
+                    stats.log_validation(evaluation);
+                }
             }
 
-            println!(
-                "Epoch {}/{} - Loss: {:.6} - Accuracy: {:.4}",
-                epoch + 1,
-                epochs,
-                loss,
-                accuracy
-            );
+            stop_training |=
+                run_callbacks(&mut callbacks, &mut |cb, ctx| cb.on_epoch_end(epoch, ctx));
+
+            // Grava o checkpoint periódico. Uma época interrompida não é gravada, pois a
+            // retomada começa na época seguinte à do checkpoint
+            if let Some((path, every)) = &checkpoint {
+                if processed == n_samples && epoch % every == 0 {
+                    let neural_net = neural_net.lock().unwrap();
+                    let saved = Checkpoint {
+                        epoch,
+                        model: neural_net.clone(),
+                        moments: neural_net.moments(),
+                        optimizer: optimizer.lock().unwrap().state(),
+                        stats: stats.lock().unwrap().clone(),
+                        seed: shuffle_seed,
+                        callbacks: callbacks[reporters..]
+                            .iter()
+                            .map(|callback| callback.state())
+                            .collect(),
+                    };
+                    saved.save(&path.replace("{epoch}", &epoch.to_string()))?;
+                }
+            }
         }
+
+        neural_net.lock().unwrap().eval_mode();
+        run_callbacks(&mut callbacks, &mut |cb, ctx| cb.on_train_end(ctx));
+        Ok(())
     }
+
+    /// Retoma um treinamento a partir de um checkpoint salvo em arquivo.
+    ///
+    /// Restaura os pesos, os momentos e o estado do otimizador, as estatísticas, a semente
+    /// do embaralhamento e o estado dos callbacks, e continua com `train` a partir da época
+    /// seguinte à do checkpoint. Com os mesmos dados e a mesma configuração, o resultado é
+    /// idêntico ao de um treinamento sem interrupção.
+    ///
+    /// # Parâmetros
+    /// - `filename`: O caminho do checkpoint gravado durante o treinamento.
+    /// - Os demais parâmetros são os mesmos de `train`; `config.epochs` é o total de épocas,
+    ///   incluindo as já concluídas.
+    #[allow(clippy::too_many_arguments)]
+    pub fn resume(
+        filename: &str,
+        neural_net: Arc<Mutex<Self>>,
+        x_train: &ArrayD<f32>,
+        y_train: &ArrayD<f32>,
+        loss_fn: Arc<dyn Loss + Sync + Send>,
+        optimizer: Arc<Mutex<dyn Optimizer + Send>>,
+        stats: Arc<Mutex<TrainingStats>>,
+        config: TrainConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let checkpoint = Checkpoint::load(filename)?;
+        Self::train(
+            neural_net,
+            x_train,
+            y_train,
+            loss_fn,
+            optimizer,
+            stats,
+            config.with_resume(checkpoint),
+        )
+    }
+}
+
+/// Origem dos dados de validação usados durante o treinamento.
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum Validation {
+    /// Conjunto de validação fornecido explicitamente (`x_val`, `y_val`).
+    Data(ArrayD<f32>, ArrayD<f32>),
+    /// Entradas e alvos de validação indexados pelo nome, para `GraphModel::fit`.
+    Named(HashMap<String, ArrayD<f32>>, HashMap<String, ArrayD<f32>>),
+    /// Fração final dos dados de treinamento reservada para validação.
+    Split(f32),
+}
+
+/// Dados percorridos pelo laço de treinamento de `NeuralNetwork::fit_data`. O laço só
+/// precisa contar e separar as amostras, treinar um mini-lote e avaliar a rede.
+pub(crate) trait TrainingData: Sized {
+    /// Número de amostras.
+    fn samples(&self) -> usize;
+
+    /// Divide as amostras em `[..at]` e `[at..]`.
+    fn split_at(&self, at: usize) -> (Self, Self);
+
+    /// Nomes das métricas de `Evaluation::metrics` retornadas por `train_batch` e `evaluate`.
+    fn metric_names(&self) -> Vec<String>;
+
+    /// Se `train_batch` calcula a acurácia; caso contrário, ela é registrada como NaN.
+    fn tracks_accuracy(&self) -> bool {
+        true
+    }
+
+    /// Executa as passagens forward e backward sobre as amostras `indices`, atualizando a
+    /// rede, e retorna a avaliação e a acurácia do mini-lote.
+    fn train_batch(
+        &self,
+        neural_net: &mut NeuralNetwork,
+        indices: &[usize],
+        optimizer: &mut dyn Optimizer,
+    ) -> Result<(Evaluation, f32), Box<dyn std::error::Error>>;
+
+    /// Avalia a rede sobre todas as amostras, em modo de inferência.
+    fn evaluate(
+        &self,
+        neural_net: &mut NeuralNetwork,
+    ) -> Result<Evaluation, Box<dyn std::error::Error>>;
+}
+
+/// Nomes das séries que `fit_data` registra em `TrainingStats` a cada época, como aceitos por
+/// `TrainingStats::latest`.
+fn available_series<D: TrainingData>(data: &D, validation: Option<&D>) -> Vec<String> {
+    let mut series = vec!["loss".to_string()];
+    let metrics = data.metric_names();
+    if data.tracks_accuracy() && !metrics.iter().any(|name| name == "accuracy") {
+        series.push("accuracy".to_string());
+    }
+    series.extend(metrics);
+    if let Some(validation) = validation {
+        series.push("val_loss".to_string());
+        series.extend(
+            validation
+                .metric_names()
+                .into_iter()
+                .map(|name| format!("val_{}", name)),
+        );
+    }
+    series
+}
+
+/// Reserva a fração final de `data` para validação.
+pub(crate) fn split_validation<D: TrainingData>(
+    data: D,
+    fraction: f32,
+) -> Result<(D, Option<D>), Box<dyn std::error::Error>> {
+    if !(fraction > 0.0 && fraction < 1.0) {
+        return Err("validation_split deve estar no intervalo (0, 1)".into());
+    }
+    let n = data.samples();
+    let split = n - (n as f32 * fraction).round() as usize;
+    if split == 0 || split == n {
+        return Err(format!(
+            "validation_split {} deixa o conjunto de treinamento ou o de validação vazio com {} amostras",
+            fraction, n
+        )
+        .into());
+    }
+    let (train, validation) = data.split_at(split);
+    Ok((train, Some(validation)))
+}
+
+/// Entradas e alvos de `NeuralNetwork::train`, com a função de perda e as métricas.
+#[derive(Clone)]
+struct Supervised<'a> {
+    x: ArrayViewD<'a, f32>,
+    y: ArrayViewD<'a, f32>,
+    loss: &'a dyn Loss,
+    metrics: &'a [MetricType],
 }
 
-/// Função para calcular a acurácia entre as saídas previstas e os rótulos reais.
-fn calculate_accuracy(y_true: &Array2<f32>, y_pred: &Array2<f32>) -> f32 {
-    let mut correct = 0;
-    let total = y_true.shape()[0];
+impl TrainingData for Supervised<'_> {
+    fn samples(&self) -> usize {
+        self.x.len_of(Axis(0))
+    }
+
+    fn split_at(&self, at: usize) -> (Self, Self) {
+        let part = |range: Slice| {
+            let mut part = self.clone();
+            part.x.slice_axis_inplace(Axis(0), range);
+            part.y.slice_axis_inplace(Axis(0), range);
+            part
+        };
+        (part(Slice::from(..at)), part(Slice::from(at..)))
+    }
+
+    fn metric_names(&self) -> Vec<String> {
+        self.metrics
+            .iter()
+            .map(|metric| metric.name().to_string())
+            .collect()
+    }
 
-    for (true_val, pred_val) in y_true.iter().zip(y_pred.iter()) {
-        if true_val.round() == pred_val.round() {
-            correct += 1;
+    fn train_batch(
+        &self,
+        neural_net: &mut NeuralNetwork,
+        indices: &[usize],
+        optimizer: &mut dyn Optimizer,
+    ) -> Result<(Evaluation, f32), Box<dyn std::error::Error>> {
+        let x_batch = self.x.select(Axis(0), indices);
+        let y_batch = self.y.select(Axis(0), indices);
+        let output = neural_net.forward(&x_batch);
+        let evaluation = Evaluation {
+            loss: self.loss.loss(&output, &y_batch),
+            metrics: self
+                .metrics
+                .iter()
+                .map(|metric| (metric.name().to_string(), metric.compute(&output, &y_batch)))
+                .collect(),
+        };
+        let accuracy = Accuracy.compute(&output, &y_batch);
+        let error = self.loss.derivative(&output, &y_batch);
+        neural_net.backward(&error, optimizer)?;
+        Ok((evaluation, accuracy))
+    }
+
+    fn evaluate(
+        &self,
+        neural_net: &mut NeuralNetwork,
+    ) -> Result<Evaluation, Box<dyn std::error::Error>> {
+        Ok(neural_net.evaluate(
+            &self.x.to_owned(),
+            &self.y.to_owned(),
+            self.loss,
+            self.metrics,
+        ))
+    }
+}
+
+/// Configuração do método `NeuralNetwork::train`.
+pub struct TrainConfig {
+    /// Número de épocas de treinamento.
+    pub epochs: usize,
+    /// Tamanho do lote para o treinamento em mini-lotes.
+    pub batch_size: usize,
+    /// Dados de validação avaliados ao final de cada época.
+    pub validation: Option<Validation>,
+    /// Métricas calculadas para o treinamento e para a validação.
+    pub metrics: Vec<MetricType>,
+    /// Forma de reportar o progresso (por padrão, uma linha por época).
+    pub progress: Progress,
+    /// Destino das linhas de progresso dos modos `Epoch` e `Bar`; `None` usa a saída padrão
+    /// e a saída de erro.
+    pub progress_writer: Option<Box<dyn Write>>,
+    /// Callbacks chamados durante o laço de treinamento, na ordem em que foram adicionados.
+    pub callbacks: Vec<Box<dyn Callback>>,
+    /// Semente para embaralhar os mini-lotes a cada época; `None` mantém a ordem dos dados.
+    pub shuffle_seed: Option<u64>,
+    /// Caminho e intervalo (em épocas) para gravação de checkpoints.
+    pub checkpoint: Option<(String, usize)>,
+    /// Checkpoint a partir do qual o treinamento é retomado.
+    pub resume: Option<Checkpoint>,
+}
+
+impl TrainConfig {
+    /// Cria uma nova configuração sem validação, sem métricas adicionais e com
+    /// progresso reportado por época.
+    pub fn new(epochs: usize, batch_size: usize) -> Self {
+        TrainConfig {
+            epochs,
+            batch_size,
+            validation: None,
+            metrics: Vec::new(),
+            progress: Progress::Epoch,
+            progress_writer: None,
+            callbacks: Vec::new(),
+            shuffle_seed: None,
+            checkpoint: None,
+            resume: None,
         }
     }
 
-    correct as f32 / total as f32
+    /// Usa `x_val` e `y_val` como conjunto de validação.
+    pub fn with_validation_data(mut self, x_val: ArrayD<f32>, y_val: ArrayD<f32>) -> Self {
+        self.validation = Some(Validation::Data(x_val, y_val));
+        self
+    }
+
+    /// Usa `inputs` e `targets`, indexados pelo nome, como conjunto de validação de
+    /// `GraphModel::fit`.
+    pub fn with_named_validation_data(
+        mut self,
+        inputs: HashMap<String, ArrayD<f32>>,
+        targets: HashMap<String, ArrayD<f32>>,
+    ) -> Self {
+        self.validation = Some(Validation::Named(inputs, targets));
+        self
+    }
+
+    /// Reserva a fração final (entre 0 e 1) dos dados de treinamento para validação.
+    pub fn with_validation_split(mut self, fraction: f32) -> Self {
+        self.validation = Some(Validation::Split(fraction));
+        self
+    }
+
+    /// Define as métricas calculadas a cada época.
+    pub fn with_metrics(mut self, metrics: Vec<MetricType>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Define como o progresso do treinamento é reportado.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Escreve o progresso dos modos `Epoch` e `Bar` em `writer`, como um arquivo de log ou
+    /// um buffer em memória.
+    pub fn with_progress_writer(mut self, writer: impl Write + 'static) -> Self {
+        self.progress_writer = Some(Box::new(writer));
+        self
+    }
+
+    /// Adiciona um callback ao laço de treinamento.
+    pub fn with_callback(mut self, callback: impl Callback + 'static) -> Self {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// Interrompe o treinamento quando a métrica monitorada deixa de melhorar.
+    ///
+    /// Atalho para `with_callback(early_stopping)`.
+    pub fn with_early_stopping(self, early_stopping: EarlyStopping) -> Self {
+        self.with_callback(early_stopping)
+    }
+
+    /// Embaralha os mini-lotes a cada época de forma reprodutível a partir de `seed`.
+    pub fn with_shuffle(mut self, seed: u64) -> Self {
+        self.shuffle_seed = Some(seed);
+        self
+    }
+
+    /// Grava um `Checkpoint` completo a cada `every` épocas.
+    ///
+    /// Se `path` contiver `{epoch}`, o marcador é substituído pelo número da época,
+    /// gerando um arquivo por checkpoint; caso contrário o arquivo é sobrescrito. Com `every`
+    /// igual a zero, o treinamento retorna um erro.
+    pub fn with_checkpoint(mut self, path: &str, every: usize) -> Self {
+        self.checkpoint = Some((path.to_string(), every));
+        self
+    }
+
+    /// Retoma o treinamento a partir de `checkpoint`, restaurando também a semente de embaralhamento.
+    pub fn with_resume(mut self, checkpoint: Checkpoint) -> Self {
+        self.shuffle_seed = checkpoint.seed;
+        self.resume = Some(checkpoint);
+        self
+    }
 }
@@ -1,6 +1,83 @@
 // bran/src/optimizer.rs
 
-use ndarray::{Array1, Array2, Zip};
+use ndarray::{Array1, Array2, ArrayD, Axis, Zip};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Parâmetro treinável de forma arbitrária, com seu gradiente e seus momentos.
+///
+/// As camadas calculam o gradiente em `grad` durante a passagem backward e entregam
+/// os parâmetros ao otimizador com `Optimizer::update_parameters`.
+#[derive(Serialize, Clone)]
+pub struct Parameter {
+    /// Valor do parâmetro.
+    pub value: ArrayD<f32>,
+    /// Se verdadeiro, a regularização L2 do otimizador é aplicada (pesos, mas não vieses).
+    pub decay: bool,
+
+    /// Gradiente acumulado na última passagem backward (ignorado na serialização).
+    #[serde(skip)]
+    pub grad: ArrayD<f32>,
+    /// Momento de primeira ordem (Adam, ignorado na serialização).
+    #[serde(skip)]
+    pub m: ArrayD<f32>,
+    /// Momento de segunda ordem (Adam, ignorado na serialização).
+    #[serde(skip)]
+    pub v: ArrayD<f32>,
+}
+
+impl Parameter {
+    /// Cria um novo parâmetro com gradiente e momentos zerados.
+    ///
+    /// # Argumentos
+    ///
+    /// * `value` - Valor inicial do parâmetro.
+    /// * `decay` - Se a regularização L2 deve ser aplicada ao parâmetro.
+    pub fn new(value: ArrayD<f32>, decay: bool) -> Self {
+        Parameter {
+            grad: ArrayD::zeros(value.raw_dim()),
+            m: ArrayD::zeros(value.raw_dim()),
+            v: ArrayD::zeros(value.raw_dim()),
+            value,
+            decay,
+        }
+    }
+
+    /// Número de elementos do parâmetro.
+    pub fn len(&self) -> usize {
+        self.value.len()
+    }
+
+    /// Verdadeiro se o parâmetro não tem elementos.
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+}
+
+/// Implementação manual da deserialização para Parameter, recriando o gradiente e os momentos.
+impl<'de> Deserialize<'de> for Parameter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct ParameterData {
+            value: ArrayD<f32>,
+            decay: bool,
+        }
+
+        let data = ParameterData::deserialize(deserializer)?;
+        Ok(Parameter::new(data.value, data.decay))
+    }
+}
+
+/// Estado mutável de um otimizador, salvo nos checkpoints de treinamento.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OptimizerState {
+    /// Taxa de aprendizado atual (pode ter sido alterada por um agendador).
+    pub learning_rate: f32,
+    /// Número de atualizações já realizadas (o contador `t` do Adam).
+    pub step: usize,
+}
 
 /// Define a interface para otimizadores que ajustam pesos e vieses durante o treinamento de redes neurais.
 pub trait Optimizer {
@@ -16,6 +93,7 @@ pub trait Optimizer {
     /// - `v_w`: Momento v dos pesos.
     /// - `m_b`: Momento m dos vieses.
     /// - `v_b`: Momento v dos vieses.
+    #[allow(clippy::too_many_arguments)]
     fn update(
         &mut self,
         weights: &mut Array2<f32>,
@@ -27,6 +105,53 @@ pub trait Optimizer {
         m_b: &mut Array1<f32>,
         v_b: &mut Array1<f32>,
     );
+
+    /// Atualiza os parâmetros de uma camada a partir dos gradientes em `Parameter::grad`.
+    ///
+    /// Equivale a uma chamada de `update`: cada chamada conta como um passo do otimizador.
+    ///
+    /// # Parâmetros
+    ///
+    /// - `params`: Parâmetros da camada, com os gradientes já calculados.
+    fn update_parameters(&mut self, params: &mut [&mut Parameter]);
+
+    /// Atualiza apenas algumas linhas (primeiro eixo) de um parâmetro, como em camadas de
+    /// embedding, em que cada lote toca poucas linhas de uma matriz grande.
+    ///
+    /// A implementação padrão monta o gradiente completo e chama `update_parameters`;
+    /// `SGD` e `Adam` atualizam somente as linhas indicadas. Conta como um passo do otimizador.
+    ///
+    /// # Parâmetros
+    ///
+    /// - `param`: Parâmetro cujas linhas serão atualizadas.
+    /// - `rows`: Índices distintos das linhas atualizadas.
+    /// - `grads`: Gradiente de cada linha, na ordem de `rows`, com forma `(rows.len(), ...)`.
+    fn update_rows(&mut self, param: &mut Parameter, rows: &[usize], grads: &ArrayD<f32>) {
+        param.grad = ArrayD::zeros(param.value.raw_dim());
+        for (&row, grad) in rows.iter().zip(grads.outer_iter()) {
+            param.grad.index_axis_mut(Axis(0), row).assign(&grad);
+        }
+        self.update_parameters(&mut [param]);
+    }
+
+    /// Retorna a taxa de aprendizado atual.
+    fn learning_rate(&self) -> f32;
+
+    /// Define uma nova taxa de aprendizado (usado por agendadores).
+    fn set_learning_rate(&mut self, learning_rate: f32);
+
+    /// Retorna o estado mutável do otimizador.
+    fn state(&self) -> OptimizerState {
+        OptimizerState {
+            learning_rate: self.learning_rate(),
+            step: 0,
+        }
+    }
+
+    /// Restaura um estado obtido com `state`.
+    fn load_state(&mut self, state: &OptimizerState) {
+        self.set_learning_rate(state.learning_rate);
+    }
 }
 
 /// Otimizador SGD (Stochastic Gradient Descent) com suporte para regularização L2.
@@ -67,6 +192,35 @@ impl Optimizer for SGD {
             *b -= self.learning_rate * bg;
         });
     }
+
+    fn update_parameters(&mut self, params: &mut [&mut Parameter]) {
+        for param in params.iter_mut() {
+            let l2_reg = if param.decay { self.l2_reg } else { 0.0 };
+            let Parameter { value, grad, .. } = &mut **param;
+            Zip::from(value).and(&*grad).par_for_each(|w, &g| {
+                *w -= self.learning_rate * (g + l2_reg * *w);
+            });
+        }
+    }
+
+    fn update_rows(&mut self, param: &mut Parameter, rows: &[usize], grads: &ArrayD<f32>) {
+        let l2_reg = if param.decay { self.l2_reg } else { 0.0 };
+        for (&row, grad) in rows.iter().zip(grads.outer_iter()) {
+            Zip::from(param.value.index_axis_mut(Axis(0), row))
+                .and(&grad)
+                .for_each(|w, &g| {
+                    *w -= self.learning_rate * (g + l2_reg * *w);
+                });
+        }
+    }
+
+    fn learning_rate(&self) -> f32 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f32) {
+        self.learning_rate = learning_rate;
+    }
 }
 
 /// Otimizador Adam (Adaptive Moment Estimation) com suporte para regularização L2.
@@ -157,4 +311,74 @@ impl Optimizer for Adam {
                 *b -= self.learning_rate * mb / (vb.sqrt() + self.epsilon);
             });
     }
+
+    fn update_parameters(&mut self, params: &mut [&mut Parameter]) {
+        self.t += 1; // Um passo por camada, como em `update`
+
+        let bias_correction1 = 1.0 - self.beta1.powi(self.t as i32);
+        let bias_correction2 = 1.0 - self.beta2.powi(self.t as i32);
+
+        for param in params.iter_mut() {
+            let l2_reg = if param.decay { self.l2_reg } else { 0.0 };
+            let Parameter {
+                value, grad, m, v, ..
+            } = &mut **param;
+            Zip::from(value)
+                .and(&*grad)
+                .and(m)
+                .and(v)
+                .par_for_each(|w, &g, m, v| {
+                    *m = self.beta1 * *m + (1.0 - self.beta1) * g;
+                    *v = self.beta2 * *v + (1.0 - self.beta2) * g * g;
+                    let m_hat = *m / bias_correction1;
+                    let v_hat = *v / bias_correction2;
+                    *w -= self.learning_rate * m_hat / (v_hat.sqrt() + self.epsilon)
+                        + l2_reg * self.learning_rate * *w;
+                });
+        }
+    }
+
+    /// Adam "preguiçoso": os momentos só são atualizados nas linhas presentes no lote.
+    fn update_rows(&mut self, param: &mut Parameter, rows: &[usize], grads: &ArrayD<f32>) {
+        self.t += 1;
+
+        let bias_correction1 = 1.0 - self.beta1.powi(self.t as i32);
+        let bias_correction2 = 1.0 - self.beta2.powi(self.t as i32);
+        let l2_reg = if param.decay { self.l2_reg } else { 0.0 };
+
+        for (&row, grad) in rows.iter().zip(grads.outer_iter()) {
+            Zip::from(param.value.index_axis_mut(Axis(0), row))
+                .and(&grad)
+                .and(param.m.index_axis_mut(Axis(0), row))
+                .and(param.v.index_axis_mut(Axis(0), row))
+                .for_each(|w, &g, m, v| {
+                    *m = self.beta1 * *m + (1.0 - self.beta1) * g;
+                    *v = self.beta2 * *v + (1.0 - self.beta2) * g * g;
+                    let m_hat = *m / bias_correction1;
+                    let v_hat = *v / bias_correction2;
+                    *w -= self.learning_rate * m_hat / (v_hat.sqrt() + self.epsilon)
+                        + l2_reg * self.learning_rate * *w;
+                });
+        }
+    }
+
+    fn learning_rate(&self) -> f32 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f32) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn state(&self) -> OptimizerState {
+        OptimizerState {
+            learning_rate: self.learning_rate,
+            step: self.t,
+        }
+    }
+
+    fn load_state(&mut self, state: &OptimizerState) {
+        self.learning_rate = state.learning_rate;
+        self.t = state.step;
+    }
 }
@@ -2,11 +2,24 @@
 
 // Re-exporte os itens principais para facilitar o uso
 pub use crate::activations::{Activation, ActivationType};
-pub use crate::layers::DenseLayer;
-pub use crate::loss::{CrossEntropyLoss, Loss, MeanSquaredError};
-pub use crate::model::NeuralNetwork;
-pub use crate::optimizer::{Adam, Optimizer, SGD};
-pub use crate::visualization::TrainingStats;
+pub use crate::callbacks::{
+    Callback, CallbackContext, EarlyStopping, LearningRateScheduler, MonitorMode,
+};
+pub use crate::checkpoint::Checkpoint;
+pub use crate::layers::{
+    AlphaDropout, AvgPool1D, AvgPool2D, BatchNorm1d, BatchNorm2d, Bidirectional, Conv1D, Conv2D,
+    ConvTranspose2D, DenseLayer, Dropout, Embedding, Flatten, GlobalAvgPool, GlobalMaxPool,
+    GraphModel, GroupNorm, Interpolation, Layer, LayerNorm, LayerType, LearnedPositionalEncoding,
+    MaxPool1D, MaxPool2D, Merge, MergeMode, MultiHeadAttention, NodeId, Padding, Padding1D,
+    RMSNorm, Recurrent, Reshape, SeparableConv2D, SimpleRNN, SinusoidalPositionalEncoding,
+    SpatialDropout2D, TimeDistributed, TransformerDecoderLayer, TransformerEncoderLayer,
+    Upsample2D, WeightConstraint, WeightNormalization, GRU, LSTM,
+};
+pub use crate::loss::{CrossEntropyLoss, Loss, MeanSquaredError, MultiOutputLoss};
+pub use crate::metrics::{Evaluation, Metric, MetricType};
+pub use crate::model::{NeuralNetwork, TrainConfig, WeightTie};
+pub use crate::optimizer::{Adam, Optimizer, Parameter, SGD};
+pub use crate::visualization::{Progress, TrainingStats};
 
 pub use ndarray;
 pub use rand;
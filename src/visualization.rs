@@ -1,10 +1,16 @@
 // bran/src/visualization.rs
 
+use crate::callbacks::{Callback, CallbackContext};
+use crate::metrics::Evaluation;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::Instant;
 
 /// Estrutura para armazenar estatísticas de treinamento.
-/// Isso inclui o número de épocas, os valores de perda correspondentes e as acurácias.
-#[derive(Serialize, Deserialize, Clone)]
+/// Isso inclui o número de épocas, os valores de perda correspondentes e as acurácias,
+/// além das séries de validação e das métricas configuradas.
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct TrainingStats {
     /// Vetor para armazenar os números das épocas.
     pub epochs: Vec<f32>,
@@ -12,16 +18,22 @@ pub struct TrainingStats {
     pub losses: Vec<f32>,
     /// Vetor para armazenar os valores de acurácia correspondentes a cada época.
     pub accuracies: Vec<f32>,
+    /// Vetor para armazenar a perda de validação de cada época.
+    pub val_losses: Vec<f32>,
+    /// Séries das métricas de treinamento, indexadas pelo nome da métrica.
+    pub metrics: BTreeMap<String, Vec<f32>>,
+    /// Séries das métricas de validação, indexadas pelo nome da métrica.
+    pub val_metrics: BTreeMap<String, Vec<f32>>,
+    /// Melhor época segundo o `EarlyStopping`, se utilizado.
+    pub best_epoch: Option<usize>,
+    /// Época em que o treinamento foi interrompido antecipadamente, se isso ocorreu.
+    pub stopped_epoch: Option<usize>,
 }
 
 impl TrainingStats {
     /// Cria uma nova instância de TrainingStats com vetores vazios.
     pub fn new() -> Self {
-        TrainingStats {
-            epochs: Vec::new(),
-            losses: Vec::new(),
-            accuracies: Vec::new(),
-        }
+        Self::default()
     }
 
     /// Limpa todos os dados armazenados, resetando as estatísticas.
@@ -29,6 +41,11 @@ impl TrainingStats {
         self.epochs.clear();
         self.losses.clear();
         self.accuracies.clear();
+        self.val_losses.clear();
+        self.metrics.clear();
+        self.val_metrics.clear();
+        self.best_epoch = None;
+        self.stopped_epoch = None;
     }
 
     /// Registra os dados de uma época de treinamento.
@@ -43,4 +60,202 @@ impl TrainingStats {
         self.losses.push(loss);
         self.accuracies.push(accuracy);
     }
+
+    /// Registra as métricas de treinamento de uma época.
+    ///
+    /// # Parâmetros
+    ///
+    /// - `metrics`: Pares `(nome, valor)` calculados para esta época.
+    pub fn log_metrics(&mut self, metrics: &[(String, f32)]) {
+        for (name, value) in metrics {
+            self.metrics.entry(name.clone()).or_default().push(*value);
+        }
+    }
+
+    /// Registra o resultado da avaliação no conjunto de validação de uma época.
+    ///
+    /// # Parâmetros
+    ///
+    /// - `evaluation`: Perda e métricas calculadas sobre os dados de validação.
+    pub fn log_validation(&mut self, evaluation: &Evaluation) {
+        self.val_losses.push(evaluation.loss);
+        for (name, value) in &evaluation.metrics {
+            self.val_metrics
+                .entry(name.clone())
+                .or_default()
+                .push(*value);
+        }
+    }
+
+    /// Retorna o valor mais recente de uma série pelo nome.
+    ///
+    /// Aceita `"loss"`, `"accuracy"`, `"val_loss"`, os nomes das métricas configuradas
+    /// e os nomes das métricas de validação prefixados com `"val_"`.
+    pub fn latest(&self, name: &str) -> Option<f32> {
+        let series = match name {
+            "loss" => Some(&self.losses),
+            "accuracy" if !self.metrics.contains_key("accuracy") => Some(&self.accuracies),
+            "val_loss" => Some(&self.val_losses),
+            _ => match name.strip_prefix("val_") {
+                Some(metric) => self.val_metrics.get(metric),
+                None => self.metrics.get(name),
+            },
+        };
+        series.and_then(|values| values.last().copied())
+    }
+}
+
+/// Forma de reportar o progresso durante `NeuralNetwork::train`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Progress {
+    /// Não reporta nada.
+    Silent,
+    /// Imprime uma linha por época na saída padrão.
+    Epoch,
+    /// Exibe uma barra de progresso por mini-lote na saída de erro, com ETA e vazão.
+    Bar,
+    /// Emite eventos pelo crate `log` (alvo `"bran"`): `info` por época e `debug` por mini-lote.
+    Log,
+}
+
+/// Callback que reporta o progresso do treinamento conforme o modo `Progress` escolhido.
+pub struct ProgressReporter {
+    mode: Progress,
+    epoch: usize,
+    epoch_start: Instant,
+    loss_sum: f32,
+    /// Destino das linhas dos modos `Epoch` e `Bar`; `None` usa a saída padrão ou a de erro.
+    pub(crate) writer: Option<Box<dyn Write>>,
+}
+
+impl ProgressReporter {
+    /// Cria um novo reporter no modo indicado.
+    pub fn new(mode: Progress) -> Self {
+        ProgressReporter {
+            mode,
+            epoch: 0,
+            epoch_start: Instant::now(),
+            loss_sum: 0.0,
+            writer: None,
+        }
+    }
+
+    /// Escreve as linhas dos modos `Epoch` e `Bar` em `writer` em vez da saída padrão e da
+    /// saída de erro. O modo `Log` continua passando pelo crate `log`.
+    pub fn with_writer(mut self, writer: impl Write + 'static) -> Self {
+        self.writer = Some(Box::new(writer));
+        self
+    }
+
+    /// Escreve `text` no destino configurado, ou em `default` se não houver um.
+    fn write(&mut self, default: &mut dyn Write, text: std::fmt::Arguments) {
+        let out: &mut dyn Write = match &mut self.writer {
+            Some(writer) => writer,
+            None => default,
+        };
+        let _ = out.write_fmt(text);
+        let _ = out.flush();
+    }
+
+    /// Monta o resumo da última época registrada em `stats`.
+    fn epoch_summary(stats: &TrainingStats) -> String {
+        let mut line = format!("Loss: {:.6}", stats.losses.last().copied().unwrap_or(0.0));
+        // A acurácia é NaN quando não se aplica, como no treinamento com várias saídas
+        let accuracy = stats.accuracies.last().copied().unwrap_or(0.0);
+        if !accuracy.is_nan() {
+            line.push_str(&format!(" - Accuracy: {:.4}", accuracy));
+        }
+        for (name, values) in &stats.metrics {
+            if let Some(value) = values.last() {
+                line.push_str(&format!(" - {}: {:.4}", name, value));
+            }
+        }
+        if let Some(val_loss) = stats.val_losses.last() {
+            line.push_str(&format!(" - Val Loss: {:.6}", val_loss));
+        }
+        for (name, values) in &stats.val_metrics {
+            if let Some(value) = values.last() {
+                line.push_str(&format!(" - val_{}: {:.4}", name, value));
+            }
+        }
+        line
+    }
+}
+
+impl Callback for ProgressReporter {
+    fn on_epoch_begin(&mut self, epoch: usize, _ctx: &mut CallbackContext) {
+        self.epoch = epoch;
+        self.epoch_start = Instant::now();
+        self.loss_sum = 0.0;
+    }
+
+    fn on_batch_end(&mut self, batch: usize, loss: f32, ctx: &mut CallbackContext) {
+        self.loss_sum += loss;
+        let done = batch + 1;
+
+        match self.mode {
+            Progress::Bar => {
+                const WIDTH: usize = 30;
+                let elapsed = self.epoch_start.elapsed().as_secs_f32();
+                let eta = elapsed / done as f32 * ctx.steps_per_epoch.saturating_sub(done) as f32;
+                let samples = usize::min(done * ctx.batch_size, ctx.samples);
+                let throughput = samples as f32 / elapsed.max(f32::EPSILON);
+                let filled = done * WIDTH / ctx.steps_per_epoch.max(1);
+                let bar = format!("{}{}", "=".repeat(filled), " ".repeat(WIDTH - filled));
+
+                let (epoch, mean_loss) = (self.epoch, self.loss_sum / done as f32);
+                self.write(
+                    &mut std::io::stderr(),
+                    format_args!(
+                        "\rEpoch {}/{} [{}] {}/{} - {:.1}s - ETA {:.1}s - {:.0} samples/s - loss: {:.6}",
+                        epoch,
+                        ctx.epochs,
+                        bar,
+                        done,
+                        ctx.steps_per_epoch,
+                        elapsed,
+                        eta,
+                        throughput,
+                        mean_loss
+                    ),
+                );
+            }
+            Progress::Log => {
+                log::debug!(
+                    target: "bran",
+                    "epoch {}/{} batch {}/{} loss={:.6}",
+                    self.epoch,
+                    ctx.epochs,
+                    done,
+                    ctx.steps_per_epoch,
+                    loss
+                );
+            }
+            Progress::Silent | Progress::Epoch => {}
+        }
+    }
+
+    fn on_epoch_end(&mut self, epoch: usize, ctx: &mut CallbackContext) {
+        let summary = Self::epoch_summary(ctx.stats);
+        match self.mode {
+            Progress::Epoch => self.write(
+                &mut std::io::stdout(),
+                format_args!("Epoch {}/{} - {}\n", epoch, ctx.epochs, summary),
+            ),
+            Progress::Bar => {
+                let elapsed = self.epoch_start.elapsed().as_secs_f32();
+                self.write(
+                    &mut std::io::stderr(),
+                    format_args!(
+                        "\rEpoch {}/{} - {:.1}s - {}\n",
+                        epoch, ctx.epochs, elapsed, summary
+                    ),
+                )
+            }
+            Progress::Log => {
+                log::info!(target: "bran", "epoch {}/{} - {}", epoch, ctx.epochs, summary)
+            }
+            Progress::Silent => {}
+        }
+    }
 }